@@ -1,9 +1,7 @@
 extern crate id3;
 
-use std::error::Error;
 use std::fs;
 use std::fs::File;
-use std::path::Path;
 
 use id3::tag::Tag;
 
@@ -16,7 +14,7 @@ fn main() {
 
         let mut file = match File::open(&path) {
             Ok(file) => file,
-            Err(why) => panic!("Couldn't open {}: {}", display, why.description()),
+            Err(why) => panic!("Couldn't open {}: {}", display, why),
         };
 
         let tag = Tag::from_reader(&mut file);
@@ -0,0 +1,65 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use utils;
+
+/// A list specifying the errors that can be encountered when reading or writing an APEv2 tag.
+#[derive(Debug)]
+pub enum Error {
+    /// An item key was not 2 to 255 printable ASCII characters, or was reserved. Holds the key.
+    InvalidKey(Vec<u8>),
+    /// The item type was the reserved value 3. Holds the item flags.
+    InvalidItemType(u32),
+    /// A header or footer did not start with "APETAGEX". Holds the bytes that were found.
+    InvalidPreamble([u8; 8]),
+    /// The tag size was too small to hold the footer, or ran past the start of the stream.
+    /// Holds the size.
+    InvalidSize(u32),
+    /// A text or external locator item was not valid UTF-8. Holds the key.
+    InvalidText(String),
+    /// An error occurred whilst reading or writing the bytes.
+    Io(io::Error),
+    /// The tag ended before all of its items were read.
+    UnexpectedEnd,
+    /// The version was neither 1000 nor 2000. Holds the version.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidKey(ref bytes) => write!(f, "Invalid item key: {}", utils::hex(bytes)),
+            Error::InvalidItemType(flags) => write!(f, "Invalid item type: {:08x}", flags),
+            Error::InvalidPreamble(ref bytes) => {
+                write!(f, "Not an APE preamble: {}", utils::hex(bytes))
+            }
+            Error::InvalidSize(size) => write!(f, "Invalid tag size: {}", size),
+            Error::InvalidText(ref key) => write!(f, "Invalid UTF-8 in item {}", key),
+            Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::UnexpectedEnd => write!(f, "Tag ended unexpectedly"),
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported version: {}", version),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::InvalidKey(_) => None,
+            Error::InvalidItemType(_) => None,
+            Error::InvalidPreamble(_) => None,
+            Error::InvalidSize(_) => None,
+            Error::InvalidText(_) => None,
+            Error::Io(ref err) => Some(err),
+            Error::UnexpectedEnd => None,
+            Error::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,174 @@
+//! Types and functions related to the items of an APEv2 tag.
+
+use ape::{Error, Result};
+
+/// The item flag marking an item as read only.
+const READ_ONLY: u32 = 1;
+
+/// The keys that an item cannot have, since they would be mistaken for other tags.
+const RESERVED_KEYS: [&str; 4] = ["ID3", "TAG", "OggS", "MP+"];
+
+/// A type representing the value of an item in an APEv2 tag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ItemValue {
+    /// UTF-8 text, which can hold several values.
+    Text(Vec<String>),
+    /// Binary data, such as a picture.
+    Binary(Vec<u8>),
+    /// A link to where the value is kept, such as a URL.
+    External(String),
+}
+
+impl ItemValue {
+    /// Gets the item type, as stored in bits 1 and 2 of the item flags.
+    fn item_type(&self) -> u32 {
+        match *self {
+            ItemValue::Text(_) => 0,
+            ItemValue::Binary(_) => 1,
+            ItemValue::External(_) => 2,
+        }
+    }
+}
+
+/// A type representing an item in an APEv2 tag.
+///
+/// # Reference
+///
+/// [APEv2 Specification](https://wiki.hydrogenaud.io/index.php?title=APEv2_specification)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Item {
+    /// The key, which is compared without regard to case.
+    pub key: String,
+    /// The value.
+    pub value: ItemValue,
+    /// Whether or not the item is intended to be read only.
+    pub read_only: bool,
+}
+
+impl Item {
+    /// Construct a new item that is not read only.
+    pub fn new(key: String, value: ItemValue) -> Item {
+        Item { key, value, read_only: false }
+    }
+
+    /// Construct a new text item with a single value.
+    pub fn text(key: &str, text: &str) -> Item {
+        Item::new(key.to_string(), ItemValue::Text(vec![text.to_string()]))
+    }
+
+    /// Construct a new item from the start of `bytes`, also returning the number of bytes it
+    /// took up.
+    ///
+    /// # Errors
+    ///
+    /// If `bytes` ends before the end of the item, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the key is not valid (see `is_valid_key`), then this function will return
+    /// `Error::InvalidKey`.
+    ///
+    /// If the item type is not recognized, then this function will return
+    /// `Error::InvalidItemType`.
+    ///
+    /// If a text or external locator item is not valid UTF-8, then this function will return
+    /// `Error::InvalidText`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Item, usize)> {
+        let field = |index: usize| {
+            bytes.get(4 * index..4 * index + 4)
+                .map(|field| u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+                .ok_or(Error::UnexpectedEnd)
+        };
+        let size = field(0)? as usize;
+        let flags = field(1)?;
+
+        let key_length = bytes[8..]
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or(Error::UnexpectedEnd)?;
+        let key_bytes = &bytes[8..8 + key_length];
+        let key = match String::from_utf8(key_bytes.to_vec()) {
+            Ok(ref key) if is_valid_key(key) => key.clone(),
+            _ => return Err(Error::InvalidKey(key_bytes.to_vec())),
+        };
+
+        let start = 8 + key_length + 1;
+        let data = bytes.get(start..start + size).ok_or(Error::UnexpectedEnd)?;
+        let text = || {
+            String::from_utf8(data.to_vec()).map_err(|_| Error::InvalidText(key.clone()))
+        };
+
+        let value = match flags >> 1 & 0b11 {
+            0 => ItemValue::Text(text()?.split('\0').map(str::to_string).collect()),
+            1 => ItemValue::Binary(data.to_vec()),
+            2 => ItemValue::External(text()?),
+            _ => return Err(Error::InvalidItemType(flags)),
+        };
+
+        let item = Item { key, value, read_only: flags & READ_ONLY != 0 };
+
+        Ok((item, start + size))
+    }
+
+    /// Gets the values of a text item, or `None` if it is not a text item.
+    pub fn text_values(&self) -> Option<&[String]> {
+        match self.value {
+            ItemValue::Text(ref values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Converts the item into bytes, separating the values of a text item with a zero byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data = match self.value {
+            ItemValue::Text(ref values) => values.join("\0").into_bytes(),
+            ItemValue::Binary(ref data) => data.clone(),
+            ItemValue::External(ref link) => link.clone().into_bytes(),
+        };
+        let flags = self.value.item_type() << 1 | if self.read_only { READ_ONLY } else { 0 };
+
+        let mut bytes = (data.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(self.key.as_bytes());
+        bytes.push(0);
+        bytes.extend(data);
+
+        bytes
+    }
+}
+
+/// Gets whether or not `key` can be the key of an item: 2 to 255 printable ASCII characters,
+/// other than the identifiers of other kinds of tags.
+pub fn is_valid_key(key: &str) -> bool {
+    (2..=255).contains(&key.len()) &&
+    key.bytes().all(|byte| (0x20..=0x7e).contains(&byte)) &&
+    !RESERVED_KEYS.iter().any(|reserved| reserved.eq_ignore_ascii_case(key))
+}
+
+#[cfg(test)]
+mod item_tests {
+    use super::{Item, ItemValue, is_valid_key};
+
+    #[test]
+    fn it_reads_and_writes_items() {
+        let bytes = b"\x09\x00\x00\x00\x00\x00\x00\x00Artist\x00Simon\x00Art\
+                      \x03\x00\x00\x00\x03\x00\x00\x00Cover Art (Front)\x00\x89PN";
+
+        let (item, length) = Item::from_bytes(bytes).unwrap();
+        assert_eq!(item.text_values().unwrap(), &["Simon", "Art"]);
+        assert_eq!(item.to_bytes(), &bytes[..length]);
+
+        let (item, _) = Item::from_bytes(&bytes[length..]).unwrap();
+        assert_eq!(item.value, ItemValue::Binary(b"\x89PN".to_vec()));
+        assert!(item.read_only);
+        assert_eq!(item.to_bytes(), &bytes[length..]);
+    }
+
+    #[test]
+    fn it_checks_keys() {
+        assert!(is_valid_key("REPLAYGAIN_TRACK_GAIN"));
+        assert!(!is_valid_key("A"));
+        assert!(!is_valid_key("tag"));
+        assert!(!is_valid_key("Caf\u{e9}"));
+        assert!(Item::from_bytes(b"\x01\x00\x00\x00\x00\x00\x00\x00ID3\x00x").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Types and functions for mapping the items of an APEv2 tag to ID3v2 frames and back, so that
+//! an APEv2 tag can be merged into or migrated to an ID3v2 tag.
+
+use ape::{ApeTag, Item, ItemValue, is_valid_key};
+use frame::Frame;
+use frame::comment::Comment;
+use frame::text::Text;
+use frame::user_text::UserText;
+use tag::{DEFAULT_V23_SEPARATOR, Tag};
+
+/// The APEv2 keys that map to a text information frame, with the `v2.4.0` frame ID of each.
+///
+/// `Year` maps to `TYER` in a `v2.3.0` tag. `Comment` maps to a comment frame (`COMM`) with an
+/// empty description, and every other text item maps to a user defined text information frame
+/// (`TXXX`) with the key as its description.
+///
+/// # Reference
+///
+/// [APE key](https://wiki.hydrogenaud.io/index.php?title=APE_key)
+pub const KEY_MAP: [(&str, &[u8; 4]); 20] = [("Title", b"TIT2"),
+                                            ("Subtitle", b"TIT3"),
+                                            ("Artist", b"TPE1"),
+                                            ("Album Artist", b"TPE2"),
+                                            ("Conductor", b"TPE3"),
+                                            ("Album", b"TALB"),
+                                            ("Year", b"TDRC"),
+                                            ("Track", b"TRCK"),
+                                            ("Disc", b"TPOS"),
+                                            ("Genre", b"TCON"),
+                                            ("Composer", b"TCOM"),
+                                            ("Lyricist", b"TEXT"),
+                                            ("Publisher", b"TPUB"),
+                                            ("Copyright", b"TCOP"),
+                                            ("ISRC", b"TSRC"),
+                                            ("Language", b"TLAN"),
+                                            ("Media", b"TMED"),
+                                            ("Mood", b"TMOO"),
+                                            ("BPM", b"TBPM"),
+                                            ("Encoded By", b"TENC")];
+
+/// The language given to the comment frames made from `Comment` items, which is "unknown".
+const COMMENT_LANGUAGE: [u8; 3] = *b"XXX";
+
+/// Gets the ID of the text information frame that an APEv2 key maps to in a tag of the given
+/// major version, compared without regard to case.
+pub fn frame_id_for_key(key: &str, version: u8) -> Option<&'static [u8; 4]> {
+    if version == 3 && key.eq_ignore_ascii_case("Year") {
+        return Some(b"TYER");
+    }
+
+    KEY_MAP.iter()
+        .find(|&&(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|&(_, frame_id)| frame_id)
+}
+
+/// Gets the APEv2 key that a text information frame ID maps to.
+pub fn key_for_frame_id(frame_id: &[u8; 4]) -> Option<&'static str> {
+    if frame_id == b"TYER" {
+        return Some("Year");
+    }
+
+    KEY_MAP.iter()
+        .find(|&&(_, id)| id == frame_id)
+        .map(|&(key, _)| key)
+}
+
+impl ApeTag {
+    /// Construct a new APEv2 tag from the mapped frames of an ID3v2 tag: the text
+    /// information frames in `KEY_MAP`, the comment frame with an empty description, and the
+    /// user defined text information frames whose description is a valid key.
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn from_tag(tag: &Tag) -> ApeTag {
+        let mut ape = ApeTag::default();
+
+        for frame in tag.frames() {
+            let item = if frame.id() == b"COMM" {
+                Comment::from_frame(frame)
+                    .ok()
+                    .filter(|comment| comment.description.is_empty())
+                    .map(|comment| Item::text("Comment", &comment.text))
+            } else if frame.id() == b"TXXX" {
+                UserText::from_frame(frame)
+                    .ok()
+                    .filter(|text| is_valid_key(&text.description))
+                    .map(|text| Item::text(&text.description, &text.value))
+            } else {
+                key_for_frame_id(frame.id()).map(|key| {
+                    Item::new(key.to_string(), ItemValue::Text(tag.text_values(frame.id())))
+                })
+            };
+
+            // Keep the first of several frames that map to the same key
+            if let Some(item) = item.filter(|item| ape.item(&item.key).is_none()) {
+                ape.set_item(item);
+            }
+        }
+
+        ape
+    }
+
+    /// Converts the text items of the tag into frames for a tag of the given major version.
+    ///
+    /// Binary and external locator items are ignored, since they have no frame to map to. In a
+    /// `v2.3.0` tag several values are joined with `DEFAULT_V23_SEPARATOR`.
+    pub fn to_frames(&self, version: u8) -> Vec<Frame> {
+        self.items()
+            .iter()
+            .filter_map(|item| item_to_frame(item, version, DEFAULT_V23_SEPARATOR))
+            .collect()
+    }
+
+    /// Adds the text items of the tag to an ID3v2 tag, skipping those whose frame is already
+    /// there: a text information frame with the same ID, or a comment or user defined text
+    /// information frame with the same description.
+    ///
+    /// Frames that cannot be decoded are treated as not being there.
+    pub fn merge_into(&self, tag: &mut Tag) {
+        let version = tag.version();
+
+        for item in self.items() {
+            let frame = match item_to_frame(item, version, tag.v23_separator()) {
+                Some(frame) => frame,
+                None => continue,
+            };
+
+            let present = tag.frames().iter().any(|existing| {
+                existing.id() == frame.id() &&
+                match frame.id() {
+                    b"COMM" => Comment::from_frame(existing)
+                        .is_ok_and(|comment| comment.description.is_empty()),
+                    b"TXXX" => UserText::from_frame(existing)
+                        .is_ok_and(|text| text.description.eq_ignore_ascii_case(&item.key)),
+                    _ => true,
+                }
+            });
+
+            if !present {
+                tag.add_frame(frame);
+            }
+        }
+    }
+}
+
+/// Converts a text item into the frame its key maps to.
+fn item_to_frame(item: &Item, version: u8, separator: &str) -> Option<Frame> {
+    let values = item.text_values()?;
+
+    if let Some(frame_id) = frame_id_for_key(&item.key, version) {
+        let mut text = Text::for_version(values.to_vec(), version);
+        if version == 3 {
+            text = text.join(separator);
+        }
+
+        Some(text.to_frame(*frame_id))
+    } else if item.key.eq_ignore_ascii_case("Comment") {
+        let comment = Comment::for_version(COMMENT_LANGUAGE,
+                                           String::new(),
+                                           values.join("\n"),
+                                           version);
+
+        Some(comment.to_frame())
+    } else {
+        let separator = if version == 3 { separator } else { "\0" };
+        let text = UserText::for_version(item.key.clone(), values.join(separator), version);
+
+        Some(text.to_frame())
+    }
+}
+
+#[cfg(test)]
+mod mapping_tests {
+    use ape::{ApeTag, Item, ItemValue};
+    use tag::Tag;
+
+    fn ape_tag() -> ApeTag {
+        let mut ape = ApeTag::default();
+        ape.set_item(Item::text("TITLE", "Title"));
+        ape.set_item(Item::text("Year", "2001"));
+        ape.set_item(Item::text("Comment", "Comment"));
+        ape.set_item(Item::text("REPLAYGAIN_TRACK_GAIN", "-6.50 dB"));
+        ape.set_item(Item::new("Cover Art (Front)".to_string(), ItemValue::Binary(vec![0])));
+        ape
+    }
+
+    #[test]
+    fn it_maps_items_to_frames() {
+        let ids = |version: u8| {
+            ape_tag().to_frames(version).iter().map(|frame| *frame.id()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(4), vec![*b"TIT2", *b"TDRC", *b"COMM", *b"TXXX"]);
+        assert_eq!(ids(3), vec![*b"TIT2", *b"TYER", *b"COMM", *b"TXXX"]);
+    }
+
+    #[test]
+    fn it_merges_and_migrates_tags() {
+        let mut tag = Tag::default();
+        tag.set_text(b"TIT2", "Kept".to_string());
+        ape_tag().merge_into(&mut tag);
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Kept"));
+        assert_eq!(tag.frames().len(), 4);
+
+        let ape = ApeTag::from_tag(&tag);
+        assert_eq!(ape.text("Title"), Some("Kept"));
+        assert_eq!(ape.text("Comment"), Some("Comment"));
+        assert_eq!(ape.text("replaygain_track_gain"), Some("-6.50 dB"));
+        assert_eq!(ape.items().len(), 4);
+    }
+}
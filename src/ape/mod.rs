@@ -0,0 +1,487 @@
+//! Types, structs and functions related to reading and writing APEv2 tags, which are often
+//! found at the end of MP3 files alongside ID3 tags, for example holding the ReplayGain values
+//! written by mp3gain.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::result;
+
+pub use self::error::Error;
+pub use self::item::{Item, ItemValue, is_valid_key};
+pub use self::mapping::{KEY_MAP, frame_id_for_key, key_for_frame_id};
+
+mod error;
+mod item;
+mod mapping;
+
+/// A specialised `Result` type for APEv2 tag operations.
+pub type Result<T> = result::Result<T, Error>;
+
+/// The bytes that every APEv2 header and footer starts with.
+pub const PREAMBLE: &[u8; 8] = b"APETAGEX";
+
+/// The size of an APEv2 header or footer.
+pub const HEADER_SIZE: usize = 32;
+
+/// The version of the APEv2 format that is written.
+const VERSION: u32 = 2000;
+
+/// The size of an ID3v1 tag, which goes after an APEv2 tag.
+const V1_TAG_SIZE: u64 = 128;
+
+/// The most bytes of lyrics a Lyrics3 v1 block can hold, between its start and end markers.
+const LYRICS3_V1_MAX_SIZE: u64 = 5100;
+
+bitflags! {
+    #[derive(Default)]
+    struct TagFlags: u32 {
+        const HAS_HEADER = 1 << 31;
+        const HAS_NO_FOOTER = 1 << 30;
+        const IS_HEADER = 1 << 29;
+        const READ_ONLY = 1;
+    }
+}
+
+/// A type representing the header or footer of an APEv2 tag, which hold the same fields.
+///
+/// # Reference
+///
+/// [APE Tags Header](https://wiki.hydrogenaud.io/index.php?title=APE_Tags_Header)
+#[derive(Debug, Clone, Default)]
+pub struct Header {
+    version: u32,
+    size: u32,
+    item_count: u32,
+    flags: TagFlags,
+}
+
+impl Header {
+    /// Construct a new header or footer from its bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes do not start with `PREAMBLE`, then this function will return
+    /// `Error::InvalidPreamble`.
+    ///
+    /// If the version is not 1000 or 2000, then this function will return
+    /// `Error::UnsupportedVersion`.
+    ///
+    /// If the size is too small to hold a footer, then this function will return
+    /// `Error::InvalidSize`.
+    pub fn from_bytes(bytes: &[u8; HEADER_SIZE]) -> Result<Header> {
+        let field = |index: usize| {
+            u32::from_le_bytes([bytes[index], bytes[index + 1], bytes[index + 2], bytes[index + 3]])
+        };
+
+        if &bytes[..8] != PREAMBLE {
+            let mut preamble = [0u8; 8];
+            preamble.copy_from_slice(&bytes[..8]);
+            return Err(Error::InvalidPreamble(preamble));
+        }
+
+        let header = Header {
+            version: field(8),
+            size: field(12),
+            item_count: field(16),
+            flags: TagFlags::from_bits_truncate(field(20)),
+        };
+
+        if header.version != 1000 && header.version != 2000 {
+            Err(Error::UnsupportedVersion(header.version))
+        } else if (header.size as usize) < HEADER_SIZE {
+            Err(Error::InvalidSize(header.size))
+        } else {
+            Ok(header)
+        }
+    }
+
+    /// Gets the version of the format, 1000 or 2000.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Gets the size of the items and the footer, not including the header.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Gets the number of items.
+    pub fn item_count(&self) -> u32 {
+        self.item_count
+    }
+
+    /// Gets whether or not the tag has a header. _Only version 2000._
+    pub fn has_header(&self) -> bool {
+        self.flags.intersects(TagFlags::HAS_HEADER)
+    }
+
+    /// Gets whether or not the tag has a footer, which it always does in version 1000.
+    pub fn has_footer(&self) -> bool {
+        !self.flags.intersects(TagFlags::HAS_NO_FOOTER)
+    }
+
+    /// Gets whether or not these are the bytes of the header, rather than the footer.
+    pub fn is_header(&self) -> bool {
+        self.flags.intersects(TagFlags::IS_HEADER)
+    }
+
+    /// Gets whether or not the tag is intended to be read only.
+    pub fn is_read_only(&self) -> bool {
+        self.flags.intersects(TagFlags::READ_ONLY)
+    }
+
+    /// Converts the header or footer into bytes.
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..8].copy_from_slice(PREAMBLE);
+        bytes[8..12].copy_from_slice(&self.version.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.size.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.item_count.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self.flags.bits().to_le_bytes());
+
+        bytes
+    }
+}
+
+/// A type representing where an APEv2 tag is in a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ApeLocation {
+    /// The offset of the start of the tag, including the header, from the start of the stream.
+    pub offset: u64,
+    /// The size of the whole tag, including the header and footer.
+    pub size: u64,
+}
+
+/// A type representing an APEv2 tag.
+///
+/// # Reference
+///
+/// [APEv2 Specification](https://wiki.hydrogenaud.io/index.php?title=APEv2_specification)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ApeTag {
+    items: Vec<Item>,
+    read_only: bool,
+}
+
+impl ApeTag {
+    /// Construct a new tag from the APEv2 tag at the end of a stream, if there is one.
+    ///
+    /// See `locate` for where the tag is looked for.
+    ///
+    /// # Errors
+    ///
+    /// If there is an error reading an item, then this function will return the error from
+    /// `Item::from_bytes`.
+    ///
+    /// If there is an error reading or seeking in the reader, then this function will return
+    /// `Error::Io`.
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Option<ApeTag>> {
+        match locate(reader)? {
+            Some(location) => ApeTag::read_at(reader, location).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Construct a new tag from the APEv2 tag at `location`.
+    ///
+    /// # Errors
+    ///
+    /// See `ApeTag::from_bytes`.
+    pub fn read_at<R: Read + Seek>(reader: &mut R, location: ApeLocation) -> Result<ApeTag> {
+        reader.seek(SeekFrom::Start(location.offset))?;
+
+        let mut bytes = Vec::new();
+        reader.take(location.size).read_to_end(&mut bytes)?;
+
+        ApeTag::from_bytes(&bytes)
+    }
+
+    /// Construct a new tag from its bytes, which must end with the footer.
+    ///
+    /// # Errors
+    ///
+    /// If the footer cannot be read, then this function will return the error from
+    /// `Header::from_bytes`.
+    ///
+    /// If there is an error reading an item, then this function will return the error from
+    /// `Item::from_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ApeTag> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let mut footer_bytes = [0u8; HEADER_SIZE];
+        footer_bytes.copy_from_slice(&bytes[bytes.len() - HEADER_SIZE..]);
+        let footer = Header::from_bytes(&footer_bytes)?;
+
+        let items_size = footer.size() as usize - HEADER_SIZE;
+        let end = bytes.len() - HEADER_SIZE;
+        let mut rest = end.checked_sub(items_size)
+            .map(|start| &bytes[start..end])
+            .ok_or(Error::InvalidSize(footer.size()))?;
+
+        let mut items = Vec::new();
+        for _ in 0..footer.item_count() {
+            let (item, length) = Item::from_bytes(rest)?;
+            items.push(item);
+            rest = &rest[length..];
+        }
+
+        Ok(ApeTag { items, read_only: footer.is_read_only() })
+    }
+
+    /// Gets all of the items in the tag, in the order they were read or added.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Gets the item with the given key, compared without regard to case.
+    pub fn item(&self, key: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Gets the first value of the text item with the given key.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.item(key)
+            .and_then(Item::text_values)
+            .and_then(|values| values.first())
+            .map(String::as_str)
+    }
+
+    /// Adds an item, replacing any item with the same key.
+    pub fn set_item(&mut self, item: Item) {
+        self.remove_item(&item.key);
+        self.items.push(item);
+    }
+
+    /// Removes the item with the given key, returning it.
+    pub fn remove_item(&mut self, key: &str) -> Option<Item> {
+        let index = self.items.iter().position(|item| item.key.eq_ignore_ascii_case(key))?;
+
+        Some(self.items.remove(index))
+    }
+
+    /// Gets whether or not the tag is intended to be read only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Converts the tag into bytes, with both a header and a footer as version 2000 tags
+    /// usually have.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let items = self.items.iter().flat_map(Item::to_bytes).collect::<Vec<_>>();
+
+        let mut flags = TagFlags::HAS_HEADER;
+        flags.set(TagFlags::READ_ONLY, self.read_only);
+        let mut header = Header {
+            version: VERSION,
+            size: (items.len() + HEADER_SIZE) as u32,
+            item_count: self.items.len() as u32,
+            flags: flags | TagFlags::IS_HEADER,
+        };
+
+        let mut bytes = header.to_bytes().to_vec();
+        bytes.extend(items);
+        header.flags = flags;
+        bytes.extend_from_slice(&header.to_bytes());
+
+        bytes
+    }
+}
+
+/// Finds the APEv2 tag at the end of a stream, which is either at the very end, or before a
+/// Lyrics3 block and an ID3v1 tag, if they are there.
+///
+/// # Errors
+///
+/// If a footer is found but its size runs past the start of the stream, then this function
+/// will return `Error::InvalidSize`.
+///
+/// If there is an error reading or seeking in the reader, then this function will return
+/// `Error::Io`.
+pub fn locate<R: Read + Seek>(reader: &mut R) -> Result<Option<ApeLocation>> {
+    let end = end_before_trailers(reader)?;
+    if end < HEADER_SIZE as u64 {
+        return Ok(None);
+    }
+
+    let mut bytes = [0u8; HEADER_SIZE];
+    reader.seek(SeekFrom::Start(end - HEADER_SIZE as u64))?;
+    reader.read_exact(&mut bytes)?;
+
+    let footer = match Header::from_bytes(&bytes) {
+        Ok(footer) if !footer.is_header() => footer,
+        _ => return Ok(None),
+    };
+
+    let header_size = if footer.has_header() { HEADER_SIZE as u64 } else { 0 };
+    let size = footer.size() as u64 + header_size;
+    if size > end {
+        return Err(Error::InvalidSize(footer.size()));
+    }
+
+    Ok(Some(ApeLocation { offset: end - size, size }))
+}
+
+/// Writes an APEv2 tag to the end of a stream, replacing the one that is there, or removes it
+/// if `tag` is `None`. The tag goes before any Lyrics3 block and ID3v1 tag, which are moved
+/// after it.
+///
+/// Returns the new length of the stream, which can be shorter than before, so the stream
+/// should be truncated to it, for example with `File::set_len`.
+///
+/// # Errors
+///
+/// See `locate`.
+pub fn write_tag<F: Read + Write + Seek>(file: &mut F, tag: Option<&ApeTag>) -> Result<u64> {
+    let end = end_before_trailers(file)?;
+    let start = match locate(file)? {
+        Some(location) => location.offset,
+        None => end,
+    };
+
+    let mut trailers = Vec::new();
+    file.seek(SeekFrom::Start(end))?;
+    file.read_to_end(&mut trailers)?;
+
+    file.seek(SeekFrom::Start(start))?;
+    if let Some(tag) = tag {
+        file.write_all(&tag.to_bytes())?;
+    }
+    file.write_all(&trailers)?;
+
+    Ok(file.stream_position()?)
+}
+
+/// Gets the offset where the ID3v1 tag, and the Lyrics3 block before it, start, or the length
+/// of the stream if there are none.
+fn end_before_trailers<R: Read + Seek>(reader: &mut R) -> Result<u64> {
+    let mut end = reader.seek(SeekFrom::End(0))?;
+
+    if end >= V1_TAG_SIZE && ends_with(reader, end - V1_TAG_SIZE, b"TAG")? {
+        end -= V1_TAG_SIZE;
+    } else {
+        return Ok(end);
+    }
+
+    // Lyrics3 v2 ends with the size of the block as six digits, then "LYRICS200"
+    if end >= 15 && ends_with(reader, end - 9, b"LYRICS200")? {
+        let mut digits = [0u8; 6];
+        reader.seek(SeekFrom::Start(end - 15))?;
+        reader.read_exact(&mut digits)?;
+
+        let size = String::from_utf8_lossy(&digits).parse::<u64>().ok();
+        if let Some(size) = size.filter(|&size| size + 15 <= end) {
+            end -= size + 15;
+        }
+    } else if end >= 9 && ends_with(reader, end - 9, b"LYRICSEND")? {
+        // Lyrics3 v1 has no size, so its start marker has to be searched for
+        let search = end.min(LYRICS3_V1_MAX_SIZE + 20);
+        let mut bytes = Vec::new();
+        reader.seek(SeekFrom::Start(end - search))?;
+        reader.take(search).read_to_end(&mut bytes)?;
+
+        if let Some(index) = bytes.windows(11).rposition(|window| window == b"LYRICSBEGIN") {
+            end -= search - index as u64;
+        }
+    }
+
+    Ok(end)
+}
+
+/// Gets whether or not the bytes at `position` are `expected`.
+fn ends_with<R: Read + Seek>(reader: &mut R, position: u64, expected: &[u8]) -> Result<bool> {
+    let mut bytes = vec![0u8; expected.len()];
+    reader.seek(SeekFrom::Start(position))?;
+
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(bytes == expected),
+        Err(_) => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod ape_tests {
+    use std::io::Cursor;
+
+    use super::{ApeLocation, ApeTag, Item, locate, write_tag};
+
+    fn tag() -> ApeTag {
+        let mut tag = ApeTag::default();
+        tag.set_item(Item::text("Title", "Title"));
+        tag.set_item(Item::text("REPLAYGAIN_TRACK_GAIN", "-6.50 dB"));
+        tag
+    }
+
+    fn v1_tag() -> Vec<u8> {
+        let mut bytes = b"TAG".to_vec();
+        bytes.extend(vec![0; 125]);
+        bytes
+    }
+
+    #[test]
+    fn it_reads_and_writes_tags() {
+        let bytes = tag().to_bytes();
+
+        assert_eq!(&bytes[..8], b"APETAGEX");
+        assert_eq!(bytes[23], 0xa0);
+        assert_eq!(bytes[bytes.len() - 9], 0x80);
+        assert_eq!(ApeTag::from_bytes(&bytes).unwrap(), tag());
+        assert_eq!(tag().text("title"), Some("Title"));
+    }
+
+    #[test]
+    fn it_finds_tags_before_lyrics_and_id3v1() {
+        let ape = tag().to_bytes();
+        let mut bytes = vec![0xff; 100];
+        bytes.extend_from_slice(&ape);
+        let lyrics = b"LYRICSBEGININD0000210LYR00005Hello";
+        bytes.extend_from_slice(lyrics);
+        bytes.extend(format!("{:06}LYRICS200", lyrics.len()).into_bytes());
+        bytes.extend(v1_tag());
+
+        let location = locate(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(location, Some(ApeLocation { offset: 100, size: ape.len() as u64 }));
+
+        let found = ApeTag::from_reader(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(found, Some(tag()));
+    }
+
+    #[test]
+    fn it_ignores_short_streams() {
+        let mut bytes = b"TAG".to_vec();
+        bytes.extend(vec![0; 47]);
+
+        assert_eq!(locate(&mut Cursor::new(&bytes)).unwrap(), None);
+
+        let mut file = Cursor::new(bytes.clone());
+        let length = write_tag(&mut file, Some(&tag())).unwrap();
+
+        assert_eq!(&file.get_ref()[..50], &bytes[..]);
+        assert_eq!(length, 50 + tag().to_bytes().len() as u64);
+    }
+
+    #[test]
+    fn it_replaces_and_removes_tags() {
+        let mut bytes = vec![0xff; 100];
+        bytes.extend(tag().to_bytes());
+        bytes.extend(v1_tag());
+        let mut file = Cursor::new(bytes);
+
+        let mut changed = tag();
+        changed.remove_item("REPLAYGAIN_TRACK_GAIN");
+        let length = write_tag(&mut file, Some(&changed)).unwrap();
+        let mut bytes = file.into_inner();
+        bytes.truncate(length as usize);
+
+        assert_eq!(ApeTag::from_reader(&mut Cursor::new(&bytes)).unwrap(), Some(changed));
+        assert_eq!(&bytes[bytes.len() - 128..], &v1_tag()[..]);
+
+        let mut file = Cursor::new(bytes);
+        let length = write_tag(&mut file, None).unwrap();
+        let mut bytes = file.into_inner();
+        bytes.truncate(length as usize);
+
+        assert_eq!(bytes.len(), 228);
+        assert_eq!(ApeTag::from_reader(&mut Cursor::new(&bytes)).unwrap(), None);
+    }
+}
@@ -49,7 +49,7 @@ impl <R: Read> ByteReader<R> {
         match self.peeked {
             Some(byte) => Ok(byte),
             None => {
-                let next_byte = try!(self.get_next_byte());
+                let next_byte = self.get_next_byte()?;
                 self.peeked = Some(next_byte);
 
                 Ok(next_byte)
@@ -64,7 +64,7 @@ impl <R: Read> ByteReader<R> {
     /// This will return an io::Error if reading the next byte fails.
     fn get_next_byte(&mut self) -> io::Result<u8> {
         let mut bytes = [0u8; 1];
-        try!(self.inner.read_exact(&mut bytes));
+        self.inner.read_exact(&mut bytes)?;
 
         Ok(bytes[0])
     }
@@ -79,7 +79,7 @@ impl <R: Seek> Skip for ByteReader<R> {
 impl <R: Read> Read for ByteReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         // Don't need to do anything if the buffer has no size
-        if buf.len() == 0 {
+        if buf.is_empty() {
             return Ok(0);
         }
 
@@ -102,7 +102,7 @@ impl <R: Read> Read for ByteReader<R> {
         } else {
             // Otherwise, read into the rest of the buffer and correct the returned size with the
             // offset
-            Ok(try!(self.inner.read(&mut buf[offset..])) + offset)
+            Ok(self.inner.read(&mut buf[offset..])? + offset)
         }
     }
 }
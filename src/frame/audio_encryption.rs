@@ -0,0 +1,85 @@
+//! Types and functions related to the audio encryption frame (`AENC`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of an audio encryption frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.20)](http://id3.org/id3v2.3.0#Audio_encryption)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AudioEncryption {
+    /// A URL or email address of the organisation responsible for the encryption.
+    pub owner: String,
+    /// The first unencrypted MPEG frame of the preview.
+    pub preview_start: u16,
+    /// The number of unencrypted MPEG frames in the preview, or 0 if there is no preview.
+    pub preview_length: u16,
+    /// Any data needed to decrypt the audio.
+    pub encryption_info: Vec<u8>,
+}
+
+impl AudioEncryption {
+    /// Construct a new audio encryption from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the preview length, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<AudioEncryption> {
+        let (owner, rest) = Encoding::Latin1.split_terminated(bytes);
+        if rest.len() < 4 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        Ok(AudioEncryption {
+            owner: Encoding::Latin1.decode(owner),
+            preview_start: u16::from_be_bytes([rest[0], rest[1]]),
+            preview_length: u16::from_be_bytes([rest[2], rest[3]]),
+            encryption_info: rest[4..].to_vec(),
+        })
+    }
+
+    /// Construct a new audio encryption from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `AudioEncryption::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<AudioEncryption> {
+        AudioEncryption::from_bytes(frame.body())
+    }
+
+    /// Converts the audio encryption into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Encoding::Latin1.encode(&self.owner);
+        bytes.push(0);
+        bytes.extend_from_slice(&self.preview_start.to_be_bytes());
+        bytes.extend_from_slice(&self.preview_length.to_be_bytes());
+        bytes.extend_from_slice(&self.encryption_info);
+
+        bytes
+    }
+
+    /// Converts the audio encryption into an `AENC` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"AENC", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod audio_encryption_tests {
+    use super::AudioEncryption;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let bytes = b"drm@example.com\0\x00\x10\x01\x00\xde\xad";
+        let encryption = AudioEncryption::from_bytes(bytes).unwrap();
+
+        assert_eq!(encryption.owner, "drm@example.com");
+        assert_eq!((encryption.preview_start, encryption.preview_length), (16, 256));
+        assert_eq!(encryption.encryption_info, b"\xde\xad");
+        assert_eq!(encryption.to_bytes(), &bytes[..]);
+        assert!(AudioEncryption::from_bytes(b"drm@example.com\0\x00\x10\x01").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Types and functions related to frames that borrow their bodies from the bytes of a tag.
+
+use std::borrow::Cow;
+
+use frame::header::Header;
+use frame::unknown::UnknownFrame;
+use frame::{Error, Frame, Result, decode_body, is_unknown};
+
+/// A type representing a frame in an ID3v2 tag that borrows its body from the bytes it was read
+/// from, rather than copying it.
+///
+/// The body is only copied if the frame was unsynchronised, or if its group symbol is in the
+/// middle of the body, as in compressed or encrypted `v2.3.0` frames.
+#[derive(Debug, Clone)]
+pub struct FrameRef<'a> {
+    header: Header,
+    body: Cow<'a, [u8]>,
+    group_symbol: Option<u8>,
+    raw: &'a [u8],
+    unknown: bool,
+}
+
+impl<'a> FrameRef<'a> {
+    /// Construct a new frame from the start of `bytes`, also returning the bits of the status
+    /// and encoding flags that were not recognized.
+    ///
+    /// See `Header::from_bytes` for `strict` and `synchsafe_size`.
+    ///
+    /// # Errors
+    ///
+    /// If `bytes` ends before the end of the frame, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If there is an error reading the frame header, then this function will return
+    /// `Error::Header`.
+    pub(crate) fn parse(bytes: &'a [u8], version: u8, strict: bool, synchsafe_size: bool)
+                        -> Result<(FrameRef<'a>, [u8; 2])> {
+        let header_bytes = match bytes.get(..10) {
+            Some(header_bytes) => {
+                let mut array = [0u8; 10];
+                array.copy_from_slice(header_bytes);
+                array
+            }
+            None => return Err(Error::UnexpectedEnd),
+        };
+        let (header, unknown_flags) =
+            Header::from_bytes(&header_bytes, version, strict, synchsafe_size)?;
+
+        let raw = bytes.get(..10 + header.size() as usize).ok_or(Error::UnexpectedEnd)?;
+        let (body, group_symbol) = decode_body(&header, version, Cow::Borrowed(&raw[10..]));
+        let unknown = is_unknown(&header, unknown_flags);
+
+        Ok((FrameRef { header, body, group_symbol, raw, unknown }, unknown_flags))
+    }
+
+    /// Gets the header of the frame.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Gets the frame ID (made out of the characters capital A-Z and 0-9).
+    pub fn id(&self) -> &[u8; 4] {
+        self.header.frame_id()
+    }
+
+    /// Gets the body of the frame (everything following the header, except the group symbol).
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Gets the symbol of the group the frame belongs to, if it is grouped.
+    pub fn group_symbol(&self) -> Option<u8> {
+        self.group_symbol
+    }
+
+    /// Gets the frame exactly as it appears in the tag, header included.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Copies the frame into an owned frame, kept exactly as it was read if it is one that this
+    /// library cannot decode.
+    pub fn to_frame(&self) -> Frame {
+        let unknown = if self.unknown {
+            Some(UnknownFrame {
+                id: *self.id(),
+                flags: [self.raw[8], self.raw[9]],
+                raw_body: self.raw[10..].to_vec(),
+            })
+        } else {
+            None
+        };
+
+        Frame {
+            header: self.header.clone(),
+            body: self.body.to_vec(),
+            group_symbol: self.group_symbol,
+            unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod borrowed_tests {
+    use std::borrow::Cow;
+
+    use frame::{Error, FrameRef};
+
+    #[test]
+    fn it_borrows_the_body() {
+        let bytes = b"TIT2\x00\x00\x00\x0b\x00\x41\x80\x00\x00\x00\x06\x00Title";
+        let (frame, _) = FrameRef::parse(bytes, 4, true, true).unwrap();
+
+        assert_eq!(frame.group_symbol(), Some(0x80));
+        assert_eq!(frame.body(), b"\x00Title");
+        assert!(matches!(frame.body, Cow::Borrowed(_)));
+        assert_eq!(frame.raw(), &bytes[..]);
+        assert_eq!(frame.to_frame().body(), b"\x00Title");
+
+        match FrameRef::parse(&bytes[..19], 4, true, true) {
+            Err(Error::UnexpectedEnd) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Types and functions related to the legacy codepages that some taggers write into frames
+//! marked as ISO-8859-1.
+
+use encoding_rs;
+
+/// The codepages that can be detected, in the order they are preferred when they score the
+/// same.
+pub const CODEPAGES: [Codepage; 3] = [Codepage::Windows1251, Codepage::ShiftJis, Codepage::Gbk];
+
+/// The fraction of non-ASCII characters that must look right for a codepage to be detected.
+const MINIMUM_SCORE: f32 = 0.8;
+
+/// A type representing a legacy codepage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codepage {
+    /// Shift-JIS, used for Japanese.
+    ShiftJis,
+    /// GBK, a superset of GB2312, used for simplified Chinese.
+    Gbk,
+    /// Windows-1251, used for Russian and other languages written in Cyrillic.
+    Windows1251,
+}
+
+impl Codepage {
+    /// Decodes `bytes` in this codepage, or returns `None` if they are not valid in it.
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        self.encoding()
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned())
+    }
+
+    /// Guesses which codepage every string in `strings` was written in, so that a whole tag
+    /// is decoded consistently.
+    ///
+    /// Returns `None` if the strings are plain ASCII, or if no codepage decodes all of them
+    /// into text that looks like its language. A codepage only looks right if most of the
+    /// non-ASCII characters are in the scripts it is used for, and for Windows-1251, if they
+    /// make up whole words rather than accents between ASCII letters.
+    pub fn detect<'a, I>(strings: I) -> Option<Codepage>
+        where I: IntoIterator<Item = &'a [u8]>
+    {
+        let strings = strings.into_iter().collect::<Vec<_>>();
+        if strings.iter().all(|bytes| bytes.is_ascii()) {
+            return None;
+        }
+
+        let mut best = None;
+        for codepage in CODEPAGES.iter() {
+            let score = match codepage.score(&strings) {
+                Some(score) if score >= MINIMUM_SCORE => score,
+                _ => continue,
+            };
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((*codepage, score));
+            }
+        }
+
+        best.map(|(codepage, _)| codepage)
+    }
+
+    /// Gets the fraction of non-ASCII characters that look right when `strings` are decoded in
+    /// this codepage, or `None` if any of them cannot be decoded.
+    fn score(&self, strings: &[&[u8]]) -> Option<f32> {
+        let mut good = 0;
+        let mut total = 0;
+
+        for bytes in strings {
+            let text = self.decode(bytes)?.chars().collect::<Vec<_>>();
+
+            for (index, &c) in text.iter().enumerate() {
+                if c.is_ascii() {
+                    continue;
+                }
+
+                total += 1;
+                let looks_right = match *self {
+                    Codepage::ShiftJis => is_japanese(c),
+                    Codepage::Gbk => is_common_chinese(c),
+                    Codepage::Windows1251 => {
+                        let beside_latin = |index: Option<usize>| {
+                            index.and_then(|index| text.get(index))
+                                .is_some_and(|c| c.is_ascii_alphabetic())
+                        };
+
+                        is_cyrillic(c) &&
+                        !beside_latin(index.checked_sub(1)) &&
+                        !beside_latin(Some(index + 1))
+                    }
+                };
+
+                if looks_right {
+                    good += 1;
+                }
+            }
+        }
+
+        Some(good as f32 / total.max(1) as f32)
+    }
+
+    /// Gets the `encoding_rs` encoding for this codepage.
+    fn encoding(&self) -> &'static encoding_rs::Encoding {
+        match *self {
+            Codepage::ShiftJis => encoding_rs::SHIFT_JIS,
+            Codepage::Gbk => encoding_rs::GBK,
+            Codepage::Windows1251 => encoding_rs::WINDOWS_1251,
+        }
+    }
+}
+
+/// Gets whether or not `c` is a kana, kanji or full width character, but not a half width kana,
+/// which Chinese text read as Shift-JIS is full of.
+fn is_japanese(c: char) -> bool {
+    matches!(c, '\u{3000}'..='\u{30ff}' | '\u{4e00}'..='\u{9fff}' | '\u{ff01}'..='\u{ff5e}')
+}
+
+/// Gets whether or not `c` is a symbol or a level 1 (common) character in GB2312. Japanese text
+/// read as GBK is full of the rare characters that GBK adds, and Russian text of the level 2
+/// characters.
+fn is_common_chinese(c: char) -> bool {
+    let mut buffer = [0u8; 4];
+    let (bytes, _, unmappable) = encoding_rs::GBK.encode(c.encode_utf8(&mut buffer));
+
+    !unmappable &&
+    bytes.len() == 2 &&
+    matches!(bytes[0], 0xa1..=0xa9 | 0xb0..=0xd7) &&
+    bytes[1] >= 0xa1
+}
+
+/// Gets whether or not `c` is a Russian letter, or punctuation common in Russian text.
+fn is_cyrillic(c: char) -> bool {
+    matches!(c, '\u{0400}'..='\u{045f}' | '«' | '»' | '—' | '–' | '№' | '…')
+}
+
+#[cfg(test)]
+mod codepage_tests {
+    use super::Codepage;
+
+    fn detect(strings: &[&[u8]]) -> Option<Codepage> {
+        Codepage::detect(strings.iter().cloned())
+    }
+
+    #[test]
+    fn it_detects_windows_1251() {
+        // "Кино", "Группа крови" and "КИНО"
+        let strings: &[&[u8]] = &[b"\xca\xe8\xed\xee",
+                                  b"\xc3\xf0\xf3\xef\xef\xe0 \xea\xf0\xee\xe2\xe8"];
+
+        assert_eq!(detect(strings), Some(Codepage::Windows1251));
+        assert_eq!(Codepage::Windows1251.decode(strings[0]).as_deref(), Some("Кино"));
+        assert_eq!(detect(&[b"\xca\xc8\xcd\xce"]), Some(Codepage::Windows1251));
+    }
+
+    #[test]
+    fn it_detects_shift_jis() {
+        // "さくら" and "桜"
+        let strings: &[&[u8]] = &[b"\x82\xb3\x82\xad\x82\xe7", b"\x8d\xf7"];
+
+        assert_eq!(detect(strings), Some(Codepage::ShiftJis));
+        assert_eq!(Codepage::ShiftJis.decode(strings[0]).as_deref(), Some("さくら"));
+    }
+
+    #[test]
+    fn it_detects_gbk() {
+        // "月亮代表我的心"
+        let strings: &[&[u8]] = &[b"\xd4\xc2\xc1\xc1\xb4\xfa\xb1\xed\xce\xd2\xb5\xc4\xd0\xc4"];
+
+        assert_eq!(detect(strings), Some(Codepage::Gbk));
+        assert_eq!(Codepage::Gbk.decode(strings[0]).as_deref(), Some("月亮代表我的心"));
+    }
+
+    #[test]
+    fn it_ignores_ascii_and_latin1() {
+        assert_eq!(detect(&[b"Title", b"Artist"]), None);
+        // "Café del Mar" and "Über"
+        assert_eq!(detect(&[b"Caf\xe9 del Mar", b"\xdcber"]), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Types and functions related to the comment frame (`COMM`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of a comment frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.11)](http://id3.org/id3v2.3.0#Comments)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comment {
+    /// The encoding used for the description and text.
+    pub encoding: Encoding,
+    /// The ISO-639-2 language code of the comment, such as `eng`.
+    pub language: [u8; 3],
+    /// A short description of the comment, which must be unique among the comments in a tag
+    /// with the same language.
+    pub description: String,
+    /// The text of the comment.
+    pub text: String,
+}
+
+impl Comment {
+    /// Construct a new comment, using the simplest encoding that can represent the description
+    /// and text in a tag of the given major version.
+    pub fn for_version(language: [u8; 3],
+                       description: String,
+                       text: String,
+                       version: u8)
+                       -> Comment {
+        let encoding = Encoding::for_text(&format!("{}{}", description, text), version);
+
+        Comment { encoding, language, description, text }
+    }
+
+    /// Construct a new comment from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the language, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Comment> {
+        if bytes.len() < 4 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let encoding = Encoding::from_byte(bytes[0])?;
+        let language = [bytes[1], bytes[2], bytes[3]];
+
+        let (description, rest) = encoding.split_terminated(&bytes[4..]);
+        let (text, _) = encoding.split_terminated(rest);
+
+        Ok(Comment {
+            encoding,
+            language,
+            description: encoding.decode(description),
+            text: encoding.decode(text),
+        })
+    }
+
+    /// Construct a new comment from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Comment::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Comment> {
+        Comment::from_bytes(frame.body())
+    }
+
+    /// Converts the comment into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend_from_slice(&self.language);
+        bytes.extend(self.encoding.encode_all(&[&self.description, &self.text]));
+
+        bytes
+    }
+
+    /// Converts the comment into a `COMM` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"COMM", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod comment_tests {
+    use frame::Error;
+    use super::Comment;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let bytes = b"\0engiTunNORM\0 00000291 0000029A";
+        let comment = Comment::from_bytes(bytes).unwrap();
+
+        assert_eq!(&comment.language, b"eng");
+        assert_eq!(comment.description, "iTunNORM");
+        assert_eq!(comment.text, " 00000291 0000029A");
+        assert_eq!(comment.to_bytes(), &bytes[..]);
+    }
+
+    #[test]
+    fn it_needs_a_language() {
+        match Comment::from_bytes(b"\0en") {
+            Err(Error::UnexpectedEnd) => {}
+            other => panic!("expected UnexpectedEnd, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! Types and functions related to the commercial frames: ownership (`OWNE`), commercial
+//! information (`COMR`) and terms of use (`USER`).
+//!
+//! The file owner (`TOWN`) is a plain text information frame; see `Tag::file_owner`.
+
+use std::fmt;
+
+use frame::encoding::Encoding;
+use frame::timestamp::Timestamp;
+use frame::{Error, Frame, Result};
+
+/// A type representing a price: a three letter ISO-4217 currency code followed by an amount,
+/// such as `USD9.99`.
+///
+/// The amount is kept as text, so that it is written back exactly as it was read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Price {
+    /// The ISO-4217 currency code, such as `USD`.
+    pub currency: String,
+    /// The amount, with `.` as the decimal point.
+    pub amount: String,
+}
+
+impl Price {
+    /// Construct a new price from text such as `USD9.99`.
+    ///
+    /// The first three characters are the currency and the rest are the amount.
+    pub fn parse(text: &str) -> Price {
+        let text = text.trim();
+        let split = text.char_indices().nth(3).map_or(text.len(), |(index, _)| index);
+
+        Price {
+            currency: text[..split].to_string(),
+            amount: text[split..].to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.currency, self.amount)
+    }
+}
+
+/// A type representing how the audio was delivered, in a `COMR` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReceivedAs {
+    /// Some other way.
+    #[default]
+    Other,
+    /// A standard CD album with other songs.
+    StandardCdAlbum,
+    /// Compressed audio on a CD.
+    CompressedAudioOnCd,
+    /// A file over the internet.
+    FileOverInternet,
+    /// A stream over the internet.
+    StreamOverInternet,
+    /// As note sheets.
+    NoteSheets,
+    /// As note sheets in a book with other sheets.
+    NoteSheetsInBook,
+    /// Music on other media.
+    MusicOnOtherMedia,
+    /// Non-musical merchandise.
+    NonMusicalMerchandise,
+    /// A value that the standard does not define.
+    Unknown(u8),
+}
+
+impl ReceivedAs {
+    /// Gets the delivery described by a received as byte.
+    pub fn from_byte(byte: u8) -> ReceivedAs {
+        match byte {
+            0 => ReceivedAs::Other,
+            1 => ReceivedAs::StandardCdAlbum,
+            2 => ReceivedAs::CompressedAudioOnCd,
+            3 => ReceivedAs::FileOverInternet,
+            4 => ReceivedAs::StreamOverInternet,
+            5 => ReceivedAs::NoteSheets,
+            6 => ReceivedAs::NoteSheetsInBook,
+            7 => ReceivedAs::MusicOnOtherMedia,
+            8 => ReceivedAs::NonMusicalMerchandise,
+            _ => ReceivedAs::Unknown(byte),
+        }
+    }
+
+    /// Gets the received as byte for the delivery.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            ReceivedAs::Other => 0,
+            ReceivedAs::StandardCdAlbum => 1,
+            ReceivedAs::CompressedAudioOnCd => 2,
+            ReceivedAs::FileOverInternet => 3,
+            ReceivedAs::StreamOverInternet => 4,
+            ReceivedAs::NoteSheets => 5,
+            ReceivedAs::NoteSheetsInBook => 6,
+            ReceivedAs::MusicOnOtherMedia => 7,
+            ReceivedAs::NonMusicalMerchandise => 8,
+            ReceivedAs::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// A type representing the body of an ownership frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.24)](http://id3.org/id3v2.3.0#Ownership_frame)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ownership {
+    /// The encoding used for the seller.
+    pub encoding: Encoding,
+    /// The price paid.
+    pub price: Price,
+    /// The date of purchase, if it is a valid date.
+    pub date: Option<Timestamp>,
+    /// The name of the seller.
+    pub seller: String,
+}
+
+impl Ownership {
+    /// Construct a new ownership from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the date of purchase, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Ownership> {
+        let (&encoding_byte, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let encoding = Encoding::from_byte(encoding_byte)?;
+
+        let (price, rest) = Encoding::Latin1.split_terminated(rest);
+        let (date, rest) = split_date(rest)?;
+        let (seller, _) = encoding.split_terminated(rest);
+
+        Ok(Ownership {
+            encoding,
+            price: Price::parse(&Encoding::Latin1.decode(price)),
+            date,
+            seller: encoding.decode(seller),
+        })
+    }
+
+    /// Construct a new ownership from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Ownership::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Ownership> {
+        Ownership::from_bytes(frame.body())
+    }
+
+    /// Converts the ownership into a frame body.
+    ///
+    /// A missing month or day of purchase is written as `01`, and a missing date as
+    /// `00000000`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend(Encoding::Latin1.encode(&self.price.to_string()));
+        bytes.push(0);
+        bytes.extend_from_slice(&format_date(self.date));
+        bytes.extend(self.encoding.encode(&self.seller));
+
+        bytes
+    }
+
+    /// Converts the ownership into an `OWNE` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"OWNE", self.to_bytes())
+    }
+}
+
+/// A type representing the body of a commercial frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.25)](http://id3.org/id3v2.3.0#Commercial_frame)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Commercial {
+    /// The encoding used for the seller and description.
+    pub encoding: Encoding,
+    /// The prices, which are separated by `/` in the frame.
+    pub prices: Vec<Price>,
+    /// The date until which the prices are valid, if it is a valid date.
+    pub valid_until: Option<Timestamp>,
+    /// A URL to contact the seller.
+    pub contact_url: String,
+    /// How the audio is delivered when bought.
+    pub received_as: ReceivedAs,
+    /// The name of the seller.
+    pub seller: String,
+    /// A short description of the product.
+    pub description: String,
+    /// The MIME type (`image/png` or `image/jpeg`) and data of the seller's logo, if there is
+    /// one.
+    pub logo: Option<(String, Vec<u8>)>,
+}
+
+impl Commercial {
+    /// Construct a new commercial frame from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the received as byte, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Commercial> {
+        let (&encoding_byte, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let encoding = Encoding::from_byte(encoding_byte)?;
+
+        let (prices, rest) = Encoding::Latin1.split_terminated(rest);
+        let (valid_until, rest) = split_date(rest)?;
+        let (contact_url, rest) = Encoding::Latin1.split_terminated(rest);
+        let (&received_as, rest) = rest.split_first().ok_or(Error::UnexpectedEnd)?;
+        let (seller, rest) = encoding.split_terminated(rest);
+        let (description, rest) = encoding.split_terminated(rest);
+
+        let logo = if rest.is_empty() {
+            None
+        } else {
+            let (mime_type, data) = Encoding::Latin1.split_terminated(rest);
+            Some((Encoding::Latin1.decode(mime_type), data.to_vec()))
+        };
+
+        Ok(Commercial {
+            encoding,
+            prices: Encoding::Latin1
+                .decode(prices)
+                .split('/')
+                .filter(|price| !price.trim().is_empty())
+                .map(Price::parse)
+                .collect(),
+            valid_until,
+            contact_url: Encoding::Latin1.decode(contact_url),
+            received_as: ReceivedAs::from_byte(received_as),
+            seller: encoding.decode(seller),
+            description: encoding.decode(description),
+            logo,
+        })
+    }
+
+    /// Construct a new commercial frame from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Commercial::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Commercial> {
+        Commercial::from_bytes(frame.body())
+    }
+
+    /// Converts the commercial frame into a frame body.
+    ///
+    /// The valid until date is written in the same way as `Ownership::to_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let prices: Vec<String> = self.prices.iter().map(|price| price.to_string()).collect();
+
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend(Encoding::Latin1.encode(&prices.join("/")));
+        bytes.push(0);
+        bytes.extend_from_slice(&format_date(self.valid_until));
+        bytes.extend(Encoding::Latin1.encode(&self.contact_url));
+        bytes.push(0);
+        bytes.push(self.received_as.to_byte());
+        bytes.extend(self.encoding.encode(&self.seller));
+        bytes.extend_from_slice(self.encoding.terminator());
+        bytes.extend(self.encoding.encode(&self.description));
+        bytes.extend_from_slice(self.encoding.terminator());
+
+        if let Some((ref mime_type, ref data)) = self.logo {
+            bytes.extend(Encoding::Latin1.encode(mime_type));
+            bytes.push(0);
+            bytes.extend_from_slice(data);
+        }
+
+        bytes
+    }
+
+    /// Converts the commercial frame into a `COMR` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"COMR", self.to_bytes())
+    }
+}
+
+/// A type representing the body of a terms of use frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.23)](http://id3.org/id3v2.3.0#Terms_of_use_frame)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TermsOfUse {
+    /// The encoding used for the text.
+    pub encoding: Encoding,
+    /// The ISO-639-2 language code of the text, such as `eng`.
+    pub language: [u8; 3],
+    /// The terms of use.
+    pub text: String,
+}
+
+impl TermsOfUse {
+    /// Construct new terms of use from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the language, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TermsOfUse> {
+        if bytes.len() < 4 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let encoding = Encoding::from_byte(bytes[0])?;
+        let (text, _) = encoding.split_terminated(&bytes[4..]);
+
+        Ok(TermsOfUse {
+            encoding,
+            language: [bytes[1], bytes[2], bytes[3]],
+            text: encoding.decode(text),
+        })
+    }
+
+    /// Construct new terms of use from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `TermsOfUse::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<TermsOfUse> {
+        TermsOfUse::from_bytes(frame.body())
+    }
+
+    /// Converts the terms of use into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend_from_slice(&self.language);
+        bytes.extend(self.encoding.encode(&self.text));
+
+        bytes
+    }
+
+    /// Converts the terms of use into a `USER` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"USER", self.to_bytes())
+    }
+}
+
+/// Splits an 8 character `YYYYMMDD` date off the front of `bytes`. The date is `None` if it is
+/// not a valid date.
+fn split_date(bytes: &[u8]) -> Result<(Option<Timestamp>, &[u8])> {
+    if bytes.len() < 8 {
+        return Err(Error::UnexpectedEnd);
+    }
+
+    let text = Encoding::Latin1.decode(&bytes[..8]);
+    let date = if text.bytes().all(|byte| byte.is_ascii_digit()) {
+        format!("{}-{}-{}", &text[..4], &text[4..6], &text[6..]).parse().ok()
+    } else {
+        None
+    };
+
+    Ok((date, &bytes[8..]))
+}
+
+/// Formats a date as the 8 characters `YYYYMMDD`. A missing month or day is written as `01`,
+/// and a missing date as `00000000`.
+fn format_date(date: Option<Timestamp>) -> [u8; 8] {
+    let text = match date {
+        Some(date) => {
+            format!("{:04}{:02}{:02}",
+                    date.year,
+                    date.month.unwrap_or(1),
+                    date.day.unwrap_or(1))
+        }
+        None => "00000000".to_string(),
+    };
+
+    let mut bytes = [b'0'; 8];
+    bytes.copy_from_slice(&text.as_bytes()[..8]);
+    bytes
+}
+
+#[cfg(test)]
+mod commercial_tests {
+    use frame::timestamp::Timestamp;
+    use super::{Commercial, Ownership, Price, ReceivedAs, TermsOfUse};
+
+    #[test]
+    fn it_reads_ownership() {
+        let bytes = b"\0USD0.99\x0020161015Music Shop";
+        let ownership = Ownership::from_bytes(bytes).unwrap();
+
+        assert_eq!(ownership.price,
+                   Price { currency: "USD".to_string(), amount: "0.99".to_string() });
+        assert_eq!(ownership.date, "2016-10-15".parse::<Timestamp>().ok());
+        assert_eq!(ownership.seller, "Music Shop");
+        assert_eq!(ownership.to_bytes(), &bytes[..]);
+        assert!(Ownership::from_bytes(b"\0USD0.99\x002016").is_err());
+    }
+
+    #[test]
+    fn it_reads_commercial_frames() {
+        let mut bytes = b"\0USD0.99/EUR0.89\x0020171231http://shop.example\0".to_vec();
+        bytes.extend(b"\x03Shop\0Single\0image/png\0\x89PNG");
+        let commercial = Commercial::from_bytes(&bytes).unwrap();
+
+        assert_eq!(commercial.prices.len(), 2);
+        assert_eq!(commercial.prices[1].to_string(), "EUR0.89");
+        assert_eq!(commercial.valid_until.unwrap().month, Some(12));
+        assert_eq!(commercial.contact_url, "http://shop.example");
+        assert_eq!(commercial.received_as, ReceivedAs::FileOverInternet);
+        assert_eq!((commercial.seller.as_str(), commercial.description.as_str()),
+                   ("Shop", "Single"));
+        assert_eq!(commercial.logo, Some(("image/png".to_string(), b"\x89PNG".to_vec())));
+        assert_eq!(commercial.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn it_keeps_going_without_a_valid_date_or_logo() {
+        let bytes = b"\0USD1\0--------\0\x00Shop\0\0";
+        let commercial = Commercial::from_bytes(bytes).unwrap();
+
+        assert_eq!(commercial.valid_until, None);
+        assert_eq!(commercial.logo, None);
+        assert_eq!(commercial.seller, "Shop");
+    }
+
+    #[test]
+    fn it_reads_terms_of_use() {
+        let terms = TermsOfUse::from_bytes(b"\0engPersonal use only").unwrap();
+
+        assert_eq!(&terms.language, b"eng");
+        assert_eq!(terms.text, "Personal use only");
+        assert_eq!(terms.to_bytes(), b"\0engPersonal use only");
+    }
+}
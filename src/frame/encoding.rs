@@ -0,0 +1,228 @@
+//! Types and functions related to the text encodings used by ID3v2 frames.
+
+use frame::{Error, Result};
+
+/// A type representing the encoding of the text in a frame.
+///
+/// # Reference
+///
+/// [ID3v2.4 Informal Standard (Section 4)](http://id3.org/id3v2.4.0-structure)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// ISO-8859-1, terminated with `$00`.
+    #[default]
+    Latin1,
+    /// UTF-16 with a byte order mark, terminated with `$00 $00`.
+    Utf16,
+    /// UTF-16 big endian without a byte order mark, terminated with `$00 $00`. _Only `v2.4.0`._
+    Utf16BE,
+    /// UTF-8, terminated with `$00`. _Only `v2.4.0`._
+    Utf8,
+}
+
+impl Encoding {
+    /// Gets the encoding described by the encoding byte at the start of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the byte is not a valid encoding, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_byte(byte: u8) -> Result<Encoding> {
+        match byte {
+            0 => Ok(Encoding::Latin1),
+            1 => Ok(Encoding::Utf16),
+            2 => Ok(Encoding::Utf16BE),
+            3 => Ok(Encoding::Utf8),
+            _ => Err(Error::InvalidEncoding(byte)),
+        }
+    }
+
+    /// Gets the byte used to describe this encoding in a frame.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            Encoding::Latin1 => 0,
+            Encoding::Utf16 => 1,
+            Encoding::Utf16BE => 2,
+            Encoding::Utf8 => 3,
+        }
+    }
+
+    /// Gets whether or not the encoding can be used in a tag of the given major version.
+    pub fn is_supported(&self, version: u8) -> bool {
+        match *self {
+            Encoding::Latin1 | Encoding::Utf16 => true,
+            Encoding::Utf16BE | Encoding::Utf8 => version >= 4,
+        }
+    }
+
+    /// Gets an encoding that can be used in a tag of the given major version, keeping this one
+    /// if possible.
+    pub fn for_version(&self, version: u8) -> Encoding {
+        if self.is_supported(version) {
+            *self
+        } else {
+            Encoding::Utf16
+        }
+    }
+
+    /// Gets the simplest encoding that can represent `text` in a tag of the given major version.
+    pub fn for_text(text: &str, version: u8) -> Encoding {
+        if text.chars().all(|c| (c as u32) < 0x100) {
+            Encoding::Latin1
+        } else if version >= 4 {
+            Encoding::Utf8
+        } else {
+            Encoding::Utf16
+        }
+    }
+
+    /// Gets the bytes used to terminate (or separate) strings in this encoding.
+    pub fn terminator(&self) -> &'static [u8] {
+        match *self {
+            Encoding::Latin1 | Encoding::Utf8 => &[0],
+            Encoding::Utf16 | Encoding::Utf16BE => &[0, 0],
+        }
+    }
+
+    /// Decodes a string from `bytes`, which must not include a terminator.
+    ///
+    /// Invalid sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match *self {
+            Encoding::Latin1 => bytes.iter().map(|&byte| byte as char).collect(),
+            Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Utf16BE => decode_utf16(bytes, true),
+            Encoding::Utf16 => {
+                if bytes.starts_with(&[0xFF, 0xFE]) {
+                    decode_utf16(&bytes[2..], false)
+                } else if bytes.starts_with(&[0xFE, 0xFF]) {
+                    decode_utf16(&bytes[2..], true)
+                } else {
+                    // Without a byte order mark, assume big endian as the standard does
+                    decode_utf16(bytes, true)
+                }
+            }
+        }
+    }
+
+    /// Encodes `text` into bytes, not including a terminator.
+    ///
+    /// Characters that cannot be represented in ISO-8859-1 are replaced with `?`.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match *self {
+            Encoding::Latin1 => {
+                text.chars().map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' }).collect()
+            }
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf16BE => text.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect(),
+            Encoding::Utf16 => {
+                let mut bytes = vec![0xFF, 0xFE];
+                bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+                bytes
+            }
+        }
+    }
+
+    /// Splits `bytes` on this encoding's terminator, returning the first string and the bytes
+    /// following its terminator.
+    ///
+    /// If there is no terminator, the whole of `bytes` is the string and the remainder is empty.
+    pub fn split_terminated<'a>(&self, bytes: &'a [u8]) -> (&'a [u8], &'a [u8]) {
+        let terminator = self.terminator();
+        let width = terminator.len();
+
+        let mut index = 0;
+        while index + width <= bytes.len() {
+            if &bytes[index..index + width] == terminator {
+                return (&bytes[..index], &bytes[index + width..]);
+            }
+            index += width;
+        }
+
+        (bytes, &[])
+    }
+
+    /// Decodes every string in `bytes`, which are separated by this encoding's terminator.
+    ///
+    /// A single trailing terminator does not produce an empty string at the end of the list.
+    pub fn decode_all(&self, mut bytes: &[u8]) -> Vec<String> {
+        let mut strings = Vec::new();
+
+        while !bytes.is_empty() {
+            let (string, rest) = self.split_terminated(bytes);
+            strings.push(self.decode(string));
+            bytes = rest;
+        }
+
+        strings
+    }
+
+    /// Encodes every string in `strings`, separating them with this encoding's terminator.
+    pub fn encode_all<S: AsRef<str>>(&self, strings: &[S]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for (index, string) in strings.iter().enumerate() {
+            if index > 0 {
+                bytes.extend_from_slice(self.terminator());
+            }
+            bytes.extend(self.encode(string.as_ref()));
+        }
+
+        bytes
+    }
+}
+
+/// Decodes UTF-16 from `bytes`, ignoring a trailing odd byte.
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .filter(|chunk| chunk.len() == 2)
+        .map(|chunk| {
+            if big_endian {
+                u16::from_be_bytes([chunk[0], chunk[1]])
+            } else {
+                u16::from_le_bytes([chunk[0], chunk[1]])
+            }
+        })
+        .collect();
+
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::Encoding;
+
+    #[test]
+    fn it_round_trips() {
+        let text = "Dvořák – Symphony №9";
+
+        for encoding in &[Encoding::Utf16, Encoding::Utf16BE, Encoding::Utf8] {
+            assert_eq!(encoding.decode(&encoding.encode(text)), text);
+        }
+        assert_eq!(Encoding::Latin1.decode(&Encoding::Latin1.encode("Café")), "Café");
+    }
+
+    #[test]
+    fn it_honours_the_byte_order_mark() {
+        assert_eq!(Encoding::Utf16.decode(&[0xFF, 0xFE, b'a', 0]), "a");
+        assert_eq!(Encoding::Utf16.decode(&[0xFE, 0xFF, 0, b'a']), "a");
+    }
+
+    #[test]
+    fn it_splits_on_aligned_terminators() {
+        // The `$00 $00` straddling "\u{100}" and "A" must not be treated as a terminator
+        let bytes = [0x01, 0, 0, b'A', 0, 0, 0, b'b'];
+        let (first, rest) = Encoding::Utf16BE.split_terminated(&bytes);
+
+        assert_eq!(first, &[0x01, 0, 0, b'A']);
+        assert_eq!(rest, &[0, b'b']);
+
+        assert_eq!(Encoding::Latin1.decode_all(&[b'a', 0, 0, b'b']), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn it_ignores_a_single_trailing_terminator() {
+        assert_eq!(Encoding::Latin1.decode_all(b"a\0b\0"), vec!["a", "b"]);
+    }
+}
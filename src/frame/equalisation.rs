@@ -0,0 +1,249 @@
+//! Types and functions related to the equalisation frames (`EQUA` in `v2.3.0` and `EQU2` in
+//! `v2.4.0`).
+
+use frame::encoding::Encoding;
+use frame::volume::{change_to_decibels, decibels_to_change, read_field};
+use frame::{Error, Frame, Result};
+
+/// The number of bits used for each adjustment when writing an `EQUA` frame.
+const WRITE_BITS: u8 = 16;
+
+/// The highest frequency an `EQUA` frame can hold, in hertz.
+const EQUA_MAX_FREQUENCY: f32 = 0x7FFF as f32;
+
+/// A type representing how to interpolate between the points of an equalisation curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Interpolation {
+    /// No interpolation: each adjustment applies from its frequency up to the next point.
+    #[default]
+    Band,
+    /// Adjustments are interpolated linearly between points.
+    Linear,
+    /// A method that the standard does not define.
+    Unknown(u8),
+}
+
+impl Interpolation {
+    /// Gets the interpolation method described by an `EQU2` interpolation byte.
+    pub fn from_byte(byte: u8) -> Interpolation {
+        match byte {
+            0 => Interpolation::Band,
+            1 => Interpolation::Linear,
+            _ => Interpolation::Unknown(byte),
+        }
+    }
+
+    /// Gets the `EQU2` interpolation byte for the method.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            Interpolation::Band => 0,
+            Interpolation::Linear => 1,
+            Interpolation::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// A type representing a single point of an equalisation curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqualisationPoint {
+    /// The frequency in hertz.
+    pub frequency: f32,
+    /// The adjustment in decibels.
+    pub adjustment: f32,
+}
+
+/// A type representing an equalisation curve, common to `EQUA` and `EQU2`.
+///
+/// Like `RVAD`, `v2.3.0` does not define the unit of an `EQUA` adjustment, so it is treated the
+/// same way as in `VolumeAdjustment`: with `b` bits, an adjustment of `n` scales the volume by
+/// `1 + n / 2^b` (or `1 - n / 2^b` if it is a decrement).
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.13)](http://id3.org/id3v2.3.0#Equalisation)
+///
+/// [ID3v2.4 Native Frames (Section 4.12)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Equalisation {
+    /// How to interpolate between points. `EQUA` has no such field, so it is always
+    /// `Interpolation::Band`. _Only `EQU2`._
+    pub interpolation: Interpolation,
+    /// The situation the curve applies to. _Only `EQU2`._
+    pub identification: String,
+    /// The points of the curve, which should be in order of increasing frequency.
+    pub points: Vec<EqualisationPoint>,
+}
+
+impl Equalisation {
+    /// Construct a new equalisation curve from the body of an `EQUA` frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty or ends part way through a point, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the number of bits used for each adjustment is 0, then this function will return
+    /// `Error::InvalidBitWidth`.
+    pub fn from_equa(bytes: &[u8]) -> Result<Equalisation> {
+        let (&bits, mut rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        if bits == 0 {
+            return Err(Error::InvalidBitWidth(bits));
+        }
+
+        let width = (bits as usize).div_ceil(8);
+        let mut points = Vec::new();
+
+        while !rest.is_empty() {
+            if rest.len() < 2 + width {
+                return Err(Error::UnexpectedEnd);
+            }
+
+            // The top bit of the frequency says whether the adjustment is an increment
+            let frequency = u16::from_be_bytes([rest[0], rest[1]]);
+            let change = read_field(rest.get(2..2 + width), width)?;
+
+            points.push(EqualisationPoint {
+                frequency: (frequency & 0x7FFF) as f32,
+                adjustment: change_to_decibels(change, bits, frequency & 0x8000 != 0),
+            });
+            rest = &rest[2 + width..];
+        }
+
+        Ok(Equalisation { points, ..Equalisation::default() })
+    }
+
+    /// Construct a new equalisation curve from the body of an `EQU2` frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty or ends part way through a point, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_equ2(bytes: &[u8]) -> Result<Equalisation> {
+        let (&interpolation, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let (identification, rest) = Encoding::Latin1.split_terminated(rest);
+
+        if rest.len() % 4 != 0 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let points = rest.chunks(4)
+            .map(|point| {
+                EqualisationPoint {
+                    frequency: u16::from_be_bytes([point[0], point[1]]) as f32 / 2.0,
+                    adjustment: i16::from_be_bytes([point[2], point[3]]) as f32 / 512.0,
+                }
+            })
+            .collect();
+
+        Ok(Equalisation {
+            interpolation: Interpolation::from_byte(interpolation),
+            identification: Encoding::Latin1.decode(identification),
+            points,
+        })
+    }
+
+    /// Construct a new equalisation curve from an `EQUA` or `EQU2` frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Equalisation::from_equa` and `Equalisation::from_equ2`.
+    pub fn from_frame(frame: &Frame) -> Result<Equalisation> {
+        match frame.id() {
+            b"EQUA" => Equalisation::from_equa(frame.body()),
+            _ => Equalisation::from_equ2(frame.body()),
+        }
+    }
+
+    /// Converts the curve into the body of an `EQUA` frame, using 16 bits for each adjustment.
+    ///
+    /// The interpolation method and identification are dropped, and frequencies are rounded to
+    /// the nearest hertz up to 32767 Hz.
+    pub fn to_equa(&self) -> Vec<u8> {
+        let mut bytes = vec![WRITE_BITS];
+
+        for point in &self.points {
+            let frequency = point.frequency.clamp(0.0, EQUA_MAX_FREQUENCY).round() as u16;
+            let (change, increment) = decibels_to_change(point.adjustment, WRITE_BITS);
+            let increment = if increment { 0x8000 } else { 0 };
+
+            bytes.extend_from_slice(&(frequency | increment).to_be_bytes());
+            bytes.extend_from_slice(&(change as u16).to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the curve into the body of an `EQU2` frame.
+    ///
+    /// Frequencies are rounded to the nearest half hertz and adjustments to the nearest 1/512
+    /// dB.
+    pub fn to_equ2(&self) -> Vec<u8> {
+        let mut bytes = vec![self.interpolation.to_byte()];
+        bytes.extend(Encoding::Latin1.encode(&self.identification));
+        bytes.push(0);
+
+        for point in &self.points {
+            let frequency = (point.frequency * 2.0).round().max(0.0).min(u16::MAX as f32) as u16;
+            let adjustment = (point.adjustment * 512.0).round();
+            let adjustment = adjustment.max(i16::MIN as f32).min(i16::MAX as f32) as i16;
+
+            bytes.extend_from_slice(&frequency.to_be_bytes());
+            bytes.extend_from_slice(&adjustment.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the curve into an `EQUA` frame for `v2.3.0`, or an `EQU2` frame otherwise.
+    pub fn to_frame(&self, version: u8) -> Frame {
+        if version == 3 {
+            Frame::new(*b"EQUA", self.to_equa())
+        } else {
+            Frame::new(*b"EQU2", self.to_equ2())
+        }
+    }
+}
+
+#[cfg(test)]
+mod equalisation_tests {
+    use super::{Equalisation, EqualisationPoint, Interpolation};
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.01, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn it_reads_equ2() {
+        let bytes = b"\x01rock\0\x00\xc8\x04\x00\x4e\x20\xfe\x00";
+        let equalisation = Equalisation::from_equ2(bytes).unwrap();
+
+        assert_eq!(equalisation.interpolation, Interpolation::Linear);
+        assert_eq!(equalisation.identification, "rock");
+        assert_eq!(equalisation.points,
+                   vec![EqualisationPoint { frequency: 100.0, adjustment: 2.0 },
+                        EqualisationPoint { frequency: 10000.0, adjustment: -1.0 }]);
+        assert_eq!(equalisation.to_equ2(), &bytes[..]);
+    }
+
+    #[test]
+    fn it_reads_equa() {
+        // 100 Hz incremented by half of full scale, then 10 kHz decremented by a quarter
+        let bytes = [16, 0x80, 0x64, 0x80, 0x00, 0x27, 0x10, 0x40, 0x00];
+        let equalisation = Equalisation::from_equa(&bytes).unwrap();
+
+        assert_eq!(equalisation.points.len(), 2);
+        assert_close(equalisation.points[0].frequency, 100.0);
+        assert_close(equalisation.points[0].adjustment, 3.52);
+        assert_close(equalisation.points[1].frequency, 10000.0);
+        assert_close(equalisation.points[1].adjustment, -2.50);
+        assert_eq!(equalisation.to_equa(), &bytes[..]);
+    }
+
+    #[test]
+    fn it_rejects_truncated_frames() {
+        assert!(Equalisation::from_equa(&[]).is_err());
+        assert!(Equalisation::from_equa(&[0]).is_err());
+        assert!(Equalisation::from_equa(&[16, 0x80, 0x64, 0x80]).is_err());
+        assert!(Equalisation::from_equ2(b"\0rock\0\x00\xc8\x04").is_err());
+    }
+}
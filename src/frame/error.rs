@@ -1,5 +1,6 @@
 use std::error;
 use std::fmt;
+use std::io;
 
 use frame::header;
 
@@ -8,26 +9,53 @@ use frame::header;
 pub enum Error {
     /// There was an error reading the header
     Header(header::Error),
+    /// The number of bits used for a field was not usable.
+    InvalidBitWidth(u8),
+    /// The text encoding byte was not recognized.
+    InvalidEncoding(u8),
+    /// A track or disc number was not in the form `n` or `n/total`.
+    InvalidPosition,
+    /// A CD table of contents had no lead-out track.
+    InvalidTableOfContents,
+    /// A date or time was not in a recognized form.
+    InvalidTimestamp,
+    /// An error occurred whilst reading the bytes.
+    Io(io::Error),
+    /// The body of the frame ended before all of its fields were read.
+    UnexpectedEnd,
+    /// An event timing code used the `$FF` event type, which says that the type continues in the
+    /// next byte, but the standard does not define any such types.
+    UnsupportedEventType,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Header(ref err) => write!(f, "Header error: {}", err),
+            Error::InvalidBitWidth(bits) => write!(f, "Invalid number of bits: {}", bits),
+            Error::InvalidEncoding(byte) => write!(f, "Invalid text encoding: {}", byte),
+            Error::InvalidPosition => write!(f, "Invalid track or disc number"),
+            Error::InvalidTableOfContents => write!(f, "Invalid CD table of contents"),
+            Error::InvalidTimestamp => write!(f, "Invalid timestamp"),
+            Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::UnexpectedEnd => write!(f, "Frame body ended unexpectedly"),
+            Error::UnsupportedEventType => write!(f, "Unsupported extended event type"),
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::Header(ref err) => err.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Error::Header(ref err) => Some(err),
+            Error::InvalidBitWidth(_) => None,
+            Error::InvalidEncoding(_) => None,
+            Error::InvalidPosition => None,
+            Error::InvalidTableOfContents => None,
+            Error::InvalidTimestamp => None,
+            Error::Io(ref err) => Some(err),
+            Error::UnexpectedEnd => None,
+            Error::UnsupportedEventType => None,
         }
     }
 }
@@ -37,3 +65,9 @@ impl From<header::Error> for Error {
         Error::Header(err)
     }
 }
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
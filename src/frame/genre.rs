@@ -0,0 +1,273 @@
+//! Types and functions related to the content type frame (`TCON`) and the ID3v1 genre byte.
+
+use std::fmt;
+
+/// The genres of ID3v1, followed by the extensions added by Winamp.
+///
+/// The first 80 genres are from the ID3v1 standard, the rest were added by Winamp and are just
+/// as widely supported. The index of a genre is the value used in an ID3v1 tag, and in a `TCON`
+/// reference.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Appendix A)](http://id3.org/id3v2.3.0#Appendix_A_-_Genre_List_from_ID3v1)
+pub const GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop", "Jazz",
+    "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap", "Reggae", "Rock", "Techno",
+    "Industrial", "Alternative", "Ska", "Death Metal", "Pranks", "Soundtrack", "Euro-Techno",
+    "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance", "Classical", "Instrumental",
+    "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise", "AlternRock", "Bass", "Soul", "Punk",
+    "Space", "Meditative", "Instrumental Pop", "Instrumental Rock", "Ethnic", "Gothic", "Darkwave",
+    "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream", "Southern Rock",
+    "Comedy", "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle",
+    "Native American", "Cabaret", "New Wave", "Psychadelic", "Rave", "Showtunes", "Trailer",
+    "Lo-Fi", "Tribal", "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll",
+    "Hard Rock",
+    // Winamp extensions
+    "Folk", "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebob", "Latin", "Revival",
+    "Celtic", "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock",
+    "Symphonic Rock", "Slow Rock", "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour",
+    "Speech", "Chanson", "Opera", "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus",
+    "Porn Groove", "Satire", "Slow Jam", "Club", "Tango", "Samba", "Folklore", "Ballad",
+    "Power Ballad", "Rhythmic Soul", "Freestyle", "Duet", "Punk Rock", "Drum Solo", "A capella",
+    "Euro-House", "Dance Hall", "Goa", "Drum & Bass", "Club-House", "Hardcore", "Terror", "Indie",
+    "BritPop", "Afro-Punk", "Polsk Punk", "Beat", "Christian Gangsta Rap", "Heavy Metal",
+    "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "JPop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz",
+    "Post-Punk", "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music",
+    "Neoclassical", "Audiobook", "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock",
+    "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
+
+/// The value of an ID3v1 genre byte when there is no genre.
+pub const ID3V1_NONE: u8 = 0xFF;
+
+/// A type representing a single genre of a `TCON` frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.2.1)](http://id3.org/id3v2.3.0#TCON)
+///
+/// [ID3v2.4 Native Frames (Section 4.2.3)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Genre {
+    /// A reference to one of the genres in `GENRES`.
+    Id(u8),
+    /// A remix (`RX`).
+    Remix,
+    /// A cover (`CR`).
+    Cover,
+    /// A genre that is not in the table, or a refinement of a previous genre.
+    Custom(String),
+}
+
+impl Genre {
+    /// Gets the genre referenced by an ID3v1 genre byte, if it is in the table.
+    pub fn from_id3v1(byte: u8) -> Option<Genre> {
+        if (byte as usize) < GENRES.len() {
+            Some(Genre::Id(byte))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the genre with the given name, referencing the table if it is in there (ignoring
+    /// case).
+    pub fn from_name(name: &str) -> Genre {
+        match GENRES.iter().position(|genre| genre.eq_ignore_ascii_case(name)) {
+            Some(index) => Genre::Id(index as u8),
+            None => Genre::Custom(name.to_string()),
+        }
+    }
+
+    /// Gets the name of the genre.
+    pub fn name(&self) -> &str {
+        match *self {
+            Genre::Id(index) => GENRES.get(index as usize).cloned().unwrap_or("Unknown"),
+            Genre::Remix => "Remix",
+            Genre::Cover => "Cover",
+            Genre::Custom(ref name) => name,
+        }
+    }
+
+    /// Gets the ID3v1 genre byte for the genre, or `ID3V1_NONE` if it is not in the table.
+    pub fn to_id3v1(&self) -> u8 {
+        match *self {
+            Genre::Id(index) if (index as usize) < GENRES.len() => index,
+            Genre::Custom(ref name) => match Genre::from_name(name) {
+                Genre::Id(index) => index,
+                _ => ID3V1_NONE,
+            },
+            _ => ID3V1_NONE,
+        }
+    }
+
+    /// Parses the text of a `v2.3.0` `TCON` frame.
+    ///
+    /// The text is a list of references such as `(17)`, `(RX)` or `(CR)`, optionally followed by
+    /// free text (where a literal `(` is escaped as `((`). Free text that only repeats the name of
+    /// the last reference is dropped.
+    pub fn parse_v23(text: &str) -> Vec<Genre> {
+        let mut genres = Vec::new();
+        let mut rest = text.trim_end_matches('\0');
+
+        while rest.starts_with('(') && !rest.starts_with("((") {
+            let end = match rest.find(')') {
+                Some(end) => end,
+                None => break,
+            };
+
+            let genre = match &rest[1..end] {
+                "RX" => Genre::Remix,
+                "CR" => Genre::Cover,
+                reference => match reference.parse::<u8>() {
+                    Ok(index) => Genre::Id(index),
+                    // Not a reference after all, so the rest is free text
+                    Err(_) => break,
+                },
+            };
+
+            genres.push(genre);
+            rest = &rest[end + 1..];
+        }
+
+        let refinement = rest.replace("((", "(");
+        let repeats_last = genres
+            .last()
+            .is_some_and(|last| last.name().eq_ignore_ascii_case(&refinement));
+
+        if !refinement.is_empty() && !repeats_last {
+            genres.push(Genre::Custom(refinement));
+        }
+
+        genres
+    }
+
+    /// Parses the values of a `v2.4.0` `TCON` frame.
+    ///
+    /// Each value is a number referencing the table, `RX`, `CR` or free text. Values using the
+    /// `v2.3.0` style are also understood, since they are common in `v2.4.0` tags.
+    pub fn parse_v24<S: AsRef<str>>(values: &[S]) -> Vec<Genre> {
+        values
+            .iter()
+            .flat_map(|value| {
+                let value = value.as_ref();
+
+                match value {
+                    "RX" => vec![Genre::Remix],
+                    "CR" => vec![Genre::Cover],
+                    _ if value.starts_with('(') => Genre::parse_v23(value),
+                    _ => match value.parse::<u8>() {
+                        Ok(index) => vec![Genre::Id(index)],
+                        Err(_) if value.is_empty() => vec![],
+                        Err(_) => vec![Genre::Custom(value.to_string())],
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Converts a list of genres into the text of a `v2.3.0` `TCON` frame.
+    ///
+    /// Since only one piece of free text can follow the references, custom genres are joined
+    /// with `/`.
+    pub fn to_v23(genres: &[Genre]) -> String {
+        let mut text = String::new();
+        let mut custom = Vec::new();
+
+        for genre in genres {
+            match *genre {
+                Genre::Id(index) => text.push_str(&format!("({})", index)),
+                Genre::Remix => text.push_str("(RX)"),
+                Genre::Cover => text.push_str("(CR)"),
+                Genre::Custom(ref name) => custom.push(name.as_str()),
+            }
+        }
+
+        let custom = custom.join("/");
+        if custom.starts_with('(') {
+            text.push('(');
+        }
+        text.push_str(&custom);
+
+        text
+    }
+
+    /// Converts a list of genres into the values of a `v2.4.0` `TCON` frame.
+    pub fn to_v24(genres: &[Genre]) -> Vec<String> {
+        genres
+            .iter()
+            .map(|genre| match *genre {
+                Genre::Id(index) => index.to_string(),
+                Genre::Remix => "RX".to_string(),
+                Genre::Cover => "CR".to_string(),
+                Genre::Custom(ref name) => name.clone(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Genre {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod genre_tests {
+    use super::{GENRES, Genre, ID3V1_NONE};
+
+    fn custom(name: &str) -> Genre {
+        Genre::Custom(name.to_string())
+    }
+
+    #[test]
+    fn it_has_every_winamp_genre() {
+        assert_eq!(GENRES[79], "Hard Rock");
+        assert_eq!(GENRES[147], "Synthpop");
+        assert_eq!(GENRES[191], "Psybient");
+    }
+
+    #[test]
+    fn it_parses_v23_references() {
+        assert_eq!(Genre::parse_v23("(17)"), vec![Genre::Id(17)]);
+        assert_eq!(Genre::parse_v23("(17)Rock"), vec![Genre::Id(17)]);
+        assert_eq!(Genre::parse_v23("(4)Eurodisco"), vec![Genre::Id(4), custom("Eurodisco")]);
+        assert_eq!(Genre::parse_v23("(51)(39)(RX)(CR)"),
+                   vec![Genre::Id(51), Genre::Id(39), Genre::Remix, Genre::Cover]);
+    }
+
+    #[test]
+    fn it_parses_v23_free_text() {
+        assert_eq!(Genre::parse_v23("Shoegaze"), vec![custom("Shoegaze")]);
+        assert_eq!(Genre::parse_v23("((I think...)"), vec![custom("(I think...)")]);
+        assert_eq!(Genre::parse_v23("(Live)"), vec![custom("(Live)")]);
+        assert_eq!(Genre::parse_v23(""), vec![]);
+    }
+
+    #[test]
+    fn it_parses_v24_values() {
+        assert_eq!(Genre::parse_v24(&["17", "RX", "Eurodisco", "(4)"]),
+                   vec![Genre::Id(17), Genre::Remix, custom("Eurodisco"), Genre::Id(4)]);
+    }
+
+    #[test]
+    fn it_writes_each_version() {
+        let genres = vec![Genre::Id(4), Genre::Remix, custom("(Euro)disco")];
+
+        assert_eq!(Genre::to_v23(&genres), "(4)(RX)((Euro)disco");
+        assert_eq!(Genre::parse_v23(&Genre::to_v23(&genres)), genres);
+        assert_eq!(Genre::to_v24(&genres), vec!["4", "RX", "(Euro)disco"]);
+    }
+
+    #[test]
+    fn it_maps_id3v1_bytes() {
+        assert_eq!(Genre::from_id3v1(17), Some(Genre::Id(17)));
+        assert_eq!(Genre::from_id3v1(ID3V1_NONE), None);
+        assert_eq!(custom("hard rock").to_id3v1(), 79);
+        assert_eq!(custom("Eurodisco").to_id3v1(), ID3V1_NONE);
+    }
+}
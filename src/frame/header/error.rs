@@ -2,41 +2,39 @@ use std::error;
 use std::fmt;
 use std::io;
 
+use utils;
+
 /// A list specifying the errors that can be encountered when constructing a frame header from a reader.
 #[derive(Debug)]
 pub enum Error {
-    /// The size was 0
-    InvalidSize,
+    /// The size was 0. Holds the bytes of the size.
+    InvalidSize([u8; 4]),
     /// An error occurred whilst reading the bytes.
     Io(io::Error),
-    /// An unknown flag was encountered.
-    UnknownFlag,
+    /// An unknown flag was encountered. Holds the bytes of the status and encoding flags.
+    UnknownFlag([u8; 2]),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Error::InvalidSize => write!(f, "Size must be greater than 0"),
+            Error::InvalidSize(ref bytes) => {
+                write!(f, "Size must be greater than 0: {}", utils::hex(bytes))
+            }
             Error::Io(ref err) => write!(f, "IO error: {}", err),
-            Error::UnknownFlag => write!(f, "Unknown flag found"),
+            Error::UnknownFlag(ref bytes) => {
+                write!(f, "Unknown flag found: {}", utils::hex(bytes))
+            }
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::InvalidSize => "size must be greater than 0",
-            Error::Io(ref err) => err.description(),
-            Error::UnknownFlag => "unknown flag found",
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::InvalidSize => None,
+            Error::InvalidSize(_) => None,
             Error::Io(ref err) => Some(err),
-            Error::UnknownFlag => None,
+            Error::UnknownFlag(_) => None,
         }
     }
 }
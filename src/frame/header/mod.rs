@@ -9,22 +9,41 @@ mod error;
 
 bitflags! {
     #[derive(Default)]
-    flags StatusFlags: u8 {
-        const STATUS_TAG_PRESERVE = 0b1000_0000,
-        const STATUS_FILE_PRESERVE = 0b0100_0000,
-        const STATUS_READ_ONLY = 0b0010_0000,
+    struct StatusFlags: u8 {
+        const TAG_PRESERVE = 0b1000_0000;
+        const FILE_PRESERVE = 0b0100_0000;
+        const READ_ONLY = 0b0010_0000;
     }
 }
 
 bitflags! {
     #[derive(Default)]
-    flags EncodingFlags: u8 {
-        const ENCODING_COMPRESSION = 0b1000_0000,
-        const ENCODING_ENCRYPTION = 0b0100_0000,
-        const ENCODING_GROUPING = 0b0010_0000,
+    struct EncodingFlags: u8 {
+        const COMPRESSION = 0b1000_0000;
+        const ENCRYPTION = 0b0100_0000;
+        const GROUPING = 0b0010_0000;
+        const UNSYNCHRONISATION = 0b0000_0010;
+        const DATA_LENGTH_INDICATOR = 0b0000_0001;
     }
 }
 
+/// The bit used for each status flag in `v2.3.0` and `v2.4.0` frame headers.
+const STATUS_BITS: [(StatusFlags, u8, u8); 3] = [
+    (StatusFlags::TAG_PRESERVE, 0b1000_0000, 0b0100_0000),
+    (StatusFlags::FILE_PRESERVE, 0b0100_0000, 0b0010_0000),
+    (StatusFlags::READ_ONLY, 0b0010_0000, 0b0001_0000),
+];
+
+/// The bit used for each encoding flag in `v2.3.0` and `v2.4.0` frame headers, or 0 if the
+/// version does not have the flag.
+const ENCODING_BITS: [(EncodingFlags, u8, u8); 5] = [
+    (EncodingFlags::COMPRESSION, 0b1000_0000, 0b0000_1000),
+    (EncodingFlags::ENCRYPTION, 0b0100_0000, 0b0000_0100),
+    (EncodingFlags::GROUPING, 0b0010_0000, 0b0100_0000),
+    (EncodingFlags::UNSYNCHRONISATION, 0, 0b0000_0010),
+    (EncodingFlags::DATA_LENGTH_INDICATOR, 0, 0b0000_0001),
+];
+
 type HeaderBytes = [u8; 10];
 /// A specialised `Result` type for frame header reading operations.
 pub type Result<T> = result::Result<T, Error>;
@@ -34,7 +53,7 @@ pub type Result<T> = result::Result<T, Error>;
 /// # Reference
 ///
 /// [ID3v2.3 Informal Standard (Section 3.3)](http://id3.org/id3v2.3.0#ID3v2_frame_overview)
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Header {
     frame_id: [u8; 4],
     size: u32,
@@ -43,6 +62,27 @@ pub struct Header {
 }
 
 impl Header {
+    /// Construct a new frame header with no flags set.
+    pub fn new(frame_id: [u8; 4], size: u32) -> Header {
+        Header {
+            frame_id,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Construct a new frame header for a new body of the given size, keeping the status flags
+    /// and whether or not the frame is grouped, but none of the flags that describe the old body.
+    pub(crate) fn for_new_body(&self, size: u32) -> Header {
+        let mut header = Header {
+            status_flags: self.status_flags,
+            ..Header::new(self.frame_id, size)
+        };
+        header.set_grouped(self.is_grouped());
+
+        header
+    }
+
     /// Construct a new frame header from a reader.
     ///
     /// # Guarantees
@@ -59,16 +99,31 @@ impl Header {
     ///
     /// If there is an unrecognized flag, then this function will return `Error::UnknownFlag`.
     pub fn from_reader<R: Read>(reader: &mut R, version: u8) -> Result<Header> {
+        let bytes = Header::read(reader)?;
+
+        Header::from_bytes(&bytes, version, true, true).map(|(header, _)| header)
+    }
+
+    /// Construct a new frame header from its bytes, also returning the bits of the status and
+    /// encoding flags that were not recognized.
+    ///
+    /// If `strict` is false, unrecognized flags are ignored rather than being an error. If
+    /// `synchsafe_size` is false, a `v2.4.0` size is read as a plain 32 bit integer, as old
+    /// versions of iTunes wrote it.
+    pub(crate) fn from_bytes(bytes: &HeaderBytes, version: u8, strict: bool, synchsafe_size: bool)
+                             -> Result<(Header, [u8; 2])> {
         let mut header: Self = Default::default();
 
-        let bytes = try!(Header::read(reader));
+        header.set_frame_id(bytes)?;
+        header.set_size(bytes, version, synchsafe_size)?;
+        let unknown = [header.set_status_flags(bytes, version),
+                       header.set_encoding_flags(bytes, version)];
 
-        try!(header.set_frame_id(&bytes));
-        try!(header.set_size(&bytes, version));
-        try!(header.set_status_flags(&bytes));
-        try!(header.set_encoding_flags(&bytes));
+        if strict && unknown != [0, 0] {
+            return Err(Error::UnknownFlag([bytes[8], bytes[9]]));
+        }
 
-        Ok(header)
+        Ok((header, unknown))
     }
 
     /// Gets the frame ID (made out of the characters capital A-Z and 0-9).
@@ -81,42 +136,111 @@ impl Header {
         self.size
     }
 
-    /// Gets whether or not to preserve the frame if it is unknown and the tag is altered in any
-    /// way. This applies to all kinds of alterations, including adding more padding and reordering the frames.
+    /// Gets whether or not to keep the frame if it is unknown and the tag is altered in any way.
+    /// This applies to all kinds of alterations, including adding more padding and reordering
+    /// the frames.
+    pub fn preserve_on_tag_alter(&self) -> bool {
+        !self.status_flags.intersects(StatusFlags::TAG_PRESERVE)
+    }
+
+    /// Gets whether or not to keep the frame if it is unknown and the file, excluding the tag,
+    /// is altered.
+    pub fn preserve_on_file_alter(&self) -> bool {
+        !self.status_flags.intersects(StatusFlags::FILE_PRESERVE)
+    }
+
+    /// Gets whether or not the tag alter preservation flag is set.
+    ///
+    /// Despite the name, the flag being set means that the frame should be discarded when the
+    /// tag is altered, so this is the opposite of `Header::preserve_on_tag_alter`.
+    #[deprecated(note = "returns true when the frame should be discarded; use \
+                         `preserve_on_tag_alter` instead")]
     pub fn should_preserve_tag(&self) -> bool {
-        self.status_flags.intersects(STATUS_TAG_PRESERVE)
+        self.status_flags.intersects(StatusFlags::TAG_PRESERVE)
     }
 
-    /// Gets whether or not to preserve the frame if it is unknown and the file, excluding the tag, is altered.
+    /// Gets whether or not the file alter preservation flag is set.
+    ///
+    /// Despite the name, the flag being set means that the frame should be discarded when the
+    /// file is altered, so this is the opposite of `Header::preserve_on_file_alter`.
+    #[deprecated(note = "returns true when the frame should be discarded; use \
+                         `preserve_on_file_alter` instead")]
     pub fn should_preserve_file(&self) -> bool {
-        self.status_flags.intersects(STATUS_FILE_PRESERVE)
+        self.status_flags.intersects(StatusFlags::FILE_PRESERVE)
     }
 
     /// Gets whether or not the contents of the frame is intended to be read only. Changing the contents might
     /// break something, e.g. a signature.
     pub fn is_read_only(&self) -> bool {
-        self.status_flags.intersects(STATUS_READ_ONLY)
+        self.status_flags.intersects(StatusFlags::READ_ONLY)
+    }
+
+    /// Sets whether or not the contents of the frame is intended to be read only.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.status_flags.set(StatusFlags::READ_ONLY, read_only);
     }
 
     /// Gets whether or not the frame is compressed.
     pub fn is_compressed(&self) -> bool {
-        self.encoding_flags.intersects(ENCODING_COMPRESSION)
+        self.encoding_flags.intersects(EncodingFlags::COMPRESSION)
     }
 
     /// Gets whether or not the frame is enrypted.
     pub fn is_encrypted(&self) -> bool {
-        self.encoding_flags.intersects(ENCODING_ENCRYPTION)
+        self.encoding_flags.intersects(EncodingFlags::ENCRYPTION)
     }
 
     /// Gest whether or not this frame belongs in a group with other frames.
     pub fn is_grouped(&self) -> bool {
-        self.encoding_flags.intersects(ENCODING_GROUPING)
+        self.encoding_flags.intersects(EncodingFlags::GROUPING)
+    }
+
+    /// Gets whether or not unsynchronisation was applied to the frame. _Only `v2.4.0`._
+    pub fn is_unsynchronised(&self) -> bool {
+        self.encoding_flags.intersects(EncodingFlags::UNSYNCHRONISATION)
+    }
+
+    /// Gets whether or not the frame data is preceded by its length before compression,
+    /// encryption and unsynchronisation. _Only `v2.4.0`._
+    pub fn has_data_length_indicator(&self) -> bool {
+        self.encoding_flags.intersects(EncodingFlags::DATA_LENGTH_INDICATOR)
+    }
+
+    /// Sets whether or not this frame belongs in a group with other frames.
+    pub fn set_grouped(&mut self, grouped: bool) {
+        self.encoding_flags.set(EncodingFlags::GROUPING, grouped);
+    }
+
+    /// Gets the status and encoding flags as they are written in a frame header of the given
+    /// major version.
+    ///
+    /// Unsynchronisation and the data length indicator are left out unless `raw_body` is true,
+    /// since they only describe a body that is kept as it was read.
+    pub(crate) fn flag_bytes(&self, version: u8, raw_body: bool) -> [u8; 2] {
+        let bit = |v23_bit, v24_bit| if version == 3 { v23_bit } else { v24_bit };
+        let mut bytes = [0, 0];
+
+        for &(flag, v23_bit, v24_bit) in &STATUS_BITS {
+            if self.status_flags.contains(flag) {
+                bytes[0] |= bit(v23_bit, v24_bit);
+            }
+        }
+
+        for &(flag, v23_bit, v24_bit) in &ENCODING_BITS {
+            let raw_only = flag == EncodingFlags::UNSYNCHRONISATION ||
+                           flag == EncodingFlags::DATA_LENGTH_INDICATOR;
+            if self.encoding_flags.contains(flag) && (raw_body || !raw_only) {
+                bytes[1] |= bit(v23_bit, v24_bit);
+            }
+        }
+
+        bytes
     }
 
     /// Read and return 10 bytes from the reader.
     fn read<R: Read>(reader: &mut R) -> Result<HeaderBytes> {
         let mut bytes = [0u8; 10];
-        try!(reader.read_exact(&mut bytes));
+        reader.read_exact(&mut bytes)?;
 
         Ok(bytes)
     }
@@ -129,44 +253,60 @@ impl Header {
     }
 
     /// Set the frame size (not including the header).
-    fn set_size(&mut self, bytes: &HeaderBytes, version: u8) -> Result<()> {
+    fn set_size(&mut self, bytes: &HeaderBytes, version: u8, synchsafe: bool) -> Result<()> {
         use utils;
 
-        self.size = match version {
-            3 => {
+        self.size = match (version, synchsafe) {
+            (3, _) | (4, false) => {
                 (bytes[4] as u32) << 24 |
                 (bytes[5] as u32) << 16 |
                 (bytes[6] as u32) << 8  |
                 (bytes[7] as u32)
             },
-            4 => utils::synchsafe_to_u32(&bytes[4..8]).unwrap_or(0),
+            (4, true) => utils::synchsafe_to_u32(&bytes[4..8]).unwrap_or(0),
             _ => 0,
         };
 
         if self.size > 0 {
             Ok(())
         } else {
-            Err(Error::InvalidSize)
+            Err(Error::InvalidSize([bytes[4], bytes[5], bytes[6], bytes[7]]))
         }
     }
 
-    /// Set the status flags of the frame.
-    fn set_status_flags(&mut self, bytes: &HeaderBytes) -> Result<()> {
-        self.status_flags = match StatusFlags::from_bits(bytes[8]) {
-            Some(flags) => flags,
-            None => return Err(Error::UnknownFlag),
-        };
+    /// Set the status flags of the frame, returning the bits that were not recognized.
+    fn set_status_flags(&mut self, bytes: &HeaderBytes, version: u8) -> u8 {
+        let mut unknown = bytes[8];
 
-        Ok(())
+        for &(flag, v23_bit, v24_bit) in &STATUS_BITS {
+            let bit = if version == 3 { v23_bit } else { v24_bit };
+            if bit != 0 && bytes[8] & bit != 0 {
+                self.status_flags.insert(flag);
+                unknown &= !bit;
+            }
+        }
+
+        unknown
     }
 
-    /// Set the encoding flags of the frame.
-    fn set_encoding_flags(&mut self, bytes: &HeaderBytes) -> Result<()> {
-        self.encoding_flags = match EncodingFlags::from_bits(bytes[9]) {
-            Some(flags) => flags,
-            None => return Err(Error::UnknownFlag),
-        };
+    /// Set the encoding flags of the frame, returning the bits that were not recognized.
+    fn set_encoding_flags(&mut self, bytes: &HeaderBytes, version: u8) -> u8 {
+        let mut unknown = bytes[9];
 
-        Ok(())
+        for &(flag, v23_bit, v24_bit) in &ENCODING_BITS {
+            let bit = if version == 3 { v23_bit } else { v24_bit };
+            if bit != 0 && bytes[9] & bit != 0 {
+                self.encoding_flags.insert(flag);
+                unknown &= !bit;
+            }
+        }
+
+        unknown
     }
 }
+
+/// Gets whether or not `bytes` is a valid frame ID, made out of the characters capital A-Z and
+/// 0-9.
+pub(crate) fn is_valid_frame_id(bytes: &[u8]) -> bool {
+    bytes.len() == 4 && bytes.iter().all(|&byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+}
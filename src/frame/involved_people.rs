@@ -0,0 +1,162 @@
+//! Types and functions related to the involved people list frames (`IPLS`, `TIPL` and `TMCL`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// The roles of a `v2.3.0` involved people list (`IPLS`) that are taken to be instruments, and so
+/// belong in the musician credits list (`TMCL`) of a `v2.4.0` tag rather than in `TIPL`.
+///
+/// Roles are compared without regard to case or surrounding whitespace.
+pub const INSTRUMENTS: [&str; 40] = [
+    "accordion", "acoustic guitar", "alto", "backing vocals", "banjo", "baritone", "bass",
+    "bass guitar", "bassoon", "cello", "clarinet", "double bass", "drums", "electric guitar",
+    "flute", "french horn", "guitar", "harmonica", "harp", "harpsichord", "horn", "keyboards",
+    "lead vocals", "mandolin", "oboe", "organ", "percussion", "piano", "saxophone", "soprano",
+    "strings", "synthesizer", "tenor", "trombone", "trumpet", "tuba", "viola", "violin", "vocals",
+    "voice",
+];
+
+/// Gets whether or not a role is one of the `INSTRUMENTS`.
+pub fn is_instrument(role: &str) -> bool {
+    let role = role.trim();
+    INSTRUMENTS.iter().any(|instrument| instrument.eq_ignore_ascii_case(role))
+}
+
+/// A type representing the body of an involved people list frame.
+///
+/// In `v2.3.0` this is the `IPLS` frame. In `v2.4.0` it was replaced by `TIPL` (involved people,
+/// such as the producer or engineer) and `TMCL` (musician credits, mapping an instrument to a
+/// musician), which share the same layout.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.4)](http://id3.org/id3v2.3.0#Involved_people_list)
+///
+/// [ID3v2.4 Native Frames (Section 4.2.2)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InvolvedPeople {
+    encoding: Encoding,
+    people: Vec<(String, String)>,
+}
+
+impl InvolvedPeople {
+    /// Construct a new list of `(role, name)` pairs.
+    pub fn new(encoding: Encoding, people: Vec<(String, String)>) -> InvolvedPeople {
+        InvolvedPeople { encoding, people }
+    }
+
+    /// Construct a new list from the body of a frame.
+    ///
+    /// If there is an odd number of strings, then the last role is given an empty name.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty, then this function will return `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<InvolvedPeople> {
+        let (&encoding_byte, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let encoding = Encoding::from_byte(encoding_byte)?;
+
+        let mut strings = encoding.decode_all(rest).into_iter();
+        let mut people = Vec::new();
+
+        while let Some(role) = strings.next() {
+            let name = strings.next().unwrap_or_default();
+            people.push((role, name));
+        }
+
+        Ok(InvolvedPeople { encoding, people })
+    }
+
+    /// Construct a new list from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `InvolvedPeople::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<InvolvedPeople> {
+        InvolvedPeople::from_bytes(frame.body())
+    }
+
+    /// Gets the encoding used for the text.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets the encoding used for the text.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    /// Gets the `(role, name)` pairs.
+    pub fn people(&self) -> &[(String, String)] {
+        &self.people
+    }
+
+    /// Gets a mutable reference to the `(role, name)` pairs.
+    pub fn people_mut(&mut self) -> &mut Vec<(String, String)> {
+        &mut self.people
+    }
+
+    /// Converts the list into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let strings: Vec<&str> = self.people
+            .iter()
+            .flat_map(|(role, name)| vec![role.as_str(), name.as_str()])
+            .collect();
+
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend(self.encoding.encode_all(&strings));
+
+        bytes
+    }
+
+    /// Converts the list into a frame with the given ID (`IPLS`, `TIPL` or `TMCL`).
+    pub fn to_frame(&self, frame_id: [u8; 4]) -> Frame {
+        Frame::new(frame_id, self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod involved_people_tests {
+    use frame::encoding::Encoding;
+    use super::{InvolvedPeople, is_instrument};
+
+    #[test]
+    fn it_pairs_strings() {
+        let people = InvolvedPeople::from_bytes(b"\0producer\0Karajan\0engineer\0Wolf\0").unwrap();
+
+        assert_eq!(people.people(),
+                   &[("producer".to_string(), "Karajan".to_string()),
+                     ("engineer".to_string(), "Wolf".to_string())]);
+    }
+
+    #[test]
+    fn it_gives_a_dangling_role_an_empty_name() {
+        let people = InvolvedPeople::from_bytes(b"\0mix\0Alice\0mastering").unwrap();
+
+        assert_eq!(people.people()[1], ("mastering".to_string(), String::new()));
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let people = InvolvedPeople::new(Encoding::Utf16,
+                                         vec![("violin".to_string(), "Anne-Sophie".to_string())]);
+
+        assert_eq!(InvolvedPeople::from_bytes(&people.to_bytes()).unwrap(), people);
+    }
+
+    #[test]
+    fn it_recognizes_instruments() {
+        assert!(is_instrument("piano"));
+        assert!(is_instrument(" Lead Vocals "));
+        assert!(!is_instrument("producer"));
+    }
+
+    #[test]
+    fn it_requires_an_encoding() {
+        assert!(InvolvedPeople::from_bytes(&[]).is_err());
+        assert!(InvolvedPeople::from_bytes(&[9]).is_err());
+    }
+}
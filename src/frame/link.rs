@@ -0,0 +1,137 @@
+//! Types and functions related to the linked information frame (`LINK`), which points to a
+//! frame stored somewhere else, such as a file shared between several tags.
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// The frames, other than the text information frames, whose body starts with a text encoding
+/// byte.
+const ENCODED_FRAMES: [&[u8; 4]; 9] = [
+    b"APIC", b"COMM", b"COMR", b"GEOB", b"IPLS", b"OWNE", b"SYLT", b"USER", b"USLT",
+];
+
+/// A type representing the body of a linked information frame.
+///
+/// `v2.3.0` documents the frame identifier as three bytes, but that is a mistake carried over
+/// from `v2.2.0`, and it is four bytes in practice, as it is in `v2.4.0`.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.21)](http://id3.org/id3v2.3.0#Linked_information)
+///
+/// [ID3v2.4 Native Frames (Section 4.20)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Link {
+    /// The ID of the linked frame.
+    pub frame_id: [u8; 4],
+    /// The URL of the file holding the linked frame.
+    pub url: String,
+    /// Data that identifies the linked frame when there could be several with the same ID,
+    /// such as the language and description of a comment.
+    pub id_data: Vec<u8>,
+}
+
+impl Link {
+    /// Construct a new link from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the end of the frame identifier, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Link> {
+        if bytes.len() < 4 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let (url, id_data) = Encoding::Latin1.split_terminated(&bytes[4..]);
+
+        Ok(Link {
+            frame_id: [bytes[0], bytes[1], bytes[2], bytes[3]],
+            url: Encoding::Latin1.decode(url),
+            id_data: id_data.to_vec(),
+        })
+    }
+
+    /// Construct a new link from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Link::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Link> {
+        Link::from_bytes(frame.body())
+    }
+
+    /// Converts the link into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.frame_id.to_vec();
+        bytes.extend(Encoding::Latin1.encode(&self.url));
+        bytes.push(0);
+        bytes.extend_from_slice(&self.id_data);
+
+        bytes
+    }
+
+    /// Converts the link into a `LINK` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"LINK", self.to_bytes())
+    }
+
+    /// Gets whether or not `frame` is the one this link points to, ignoring the URL.
+    ///
+    /// The frame must have the linked ID, and its body must start with the ID data. The text
+    /// encoding byte at the start of frames such as comments is skipped before comparing.
+    pub fn matches(&self, frame: &Frame) -> bool {
+        if frame.id() != &self.frame_id {
+            return false;
+        }
+
+        let body = if frame.id()[0] == b'T' || ENCODED_FRAMES.contains(&frame.id()) {
+            frame.body().get(1..).unwrap_or(&[])
+        } else {
+            frame.body()
+        };
+
+        body.starts_with(&self.id_data)
+    }
+}
+
+/// A trait for fetching the frames that linked information frames point to.
+///
+/// An implementation decides what a URL means, for example by reading the tag of a local
+/// shared metadata file. `Tag` implements it by looking through its own frames, ignoring the
+/// URL, so a tag read from such a file can be used directly.
+pub trait LinkResolver {
+    /// Fetches the frame that `link` points to, or `None` if it cannot be found.
+    fn resolve(&mut self, link: &Link) -> Option<Frame>;
+}
+
+#[cfg(test)]
+mod link_tests {
+    use frame::Frame;
+    use super::Link;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let bytes = b"COMMhttp://example.com/shared.id3\0engLiner notes";
+        let link = Link::from_bytes(bytes).unwrap();
+
+        assert_eq!(&link.frame_id, b"COMM");
+        assert_eq!(link.url, "http://example.com/shared.id3");
+        assert_eq!(link.id_data, b"engLiner notes");
+        assert_eq!(link.to_bytes(), &bytes[..]);
+        assert!(Link::from_bytes(b"COM").is_err());
+    }
+
+    #[test]
+    fn it_matches_frames() {
+        let link = Link::from_bytes(b"COMMshared.id3\0eng").unwrap();
+
+        assert!(link.matches(&Frame::new(*b"COMM", b"\0engLiner notes\0Text".to_vec())));
+        assert!(!link.matches(&Frame::new(*b"COMM", b"\0deuLiner notes\0Text".to_vec())));
+        assert!(!link.matches(&Frame::new(*b"USER", b"\0engText".to_vec())));
+
+        let link = Link::from_bytes(b"TPUBshared.id3\0").unwrap();
+
+        assert!(link.matches(&Frame::new(*b"TPUB", b"\x03Label".to_vec())));
+    }
+}
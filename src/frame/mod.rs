@@ -1,14 +1,42 @@
 //! Types, structs and functions related to reading a frame from an ID3v2 tag.
 
+use std::borrow::Cow;
+use std::io;
 use std::io::Read;
 use std::result;
 
 use self::header::Header;
+use self::unknown::UnknownFrame;
+use utils;
 
+pub use self::borrowed::FrameRef;
 pub use self::error::Error;
 
+mod borrowed;
 mod error;
+pub mod audio_encryption;
+pub mod codepage;
+pub mod comment;
+pub mod commercial;
+pub mod encoding;
+pub mod equalisation;
+pub mod genre;
 pub mod header;
+pub mod involved_people;
+pub mod link;
+pub mod mpeg_location;
+pub mod music_cd;
+pub mod position;
+pub mod replay_gain;
+pub mod reverb;
+pub mod signature;
+pub mod streaming;
+pub mod text;
+pub mod timestamp;
+pub mod timing;
+pub mod unknown;
+pub mod user_text;
+pub mod volume;
 
 /// A specialised `Result` type for frame reading operations.
 pub type Result<T> = result::Result<T, Error>;
@@ -18,23 +46,237 @@ pub type Result<T> = result::Result<T, Error>;
 /// # Reference
 ///
 /// [ID3v2.3 Informal Standard (Section 3.3)](http://id3.org/id3v2.3.0#ID3v2_frame_overview)
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Frame {
     header: Header,
+    body: Vec<u8>,
+    group_symbol: Option<u8>,
+    unknown: Option<UnknownFrame>,
 }
 
 impl Frame {
+    /// Construct a new frame with the given ID and body, and no flags set.
+    pub fn new(frame_id: [u8; 4], body: Vec<u8>) -> Frame {
+        Frame {
+            header: Header::new(frame_id, body.len() as u32),
+            body,
+            group_symbol: None,
+            unknown: None,
+        }
+    }
+
     /// Construct a new frame from a reader.
     ///
     /// # Errors
     ///
     /// If there is an error reading the frame header, then this function will return
     /// `Error::Header`.
+    ///
+    /// If there is an error reading the body of the frame, then this function will return
+    /// `Error::Io`.
     pub fn from_reader<R: Read>(reader: &mut R, version: u8) -> Result<Frame> {
-        let header = try!(Header::from_reader(&mut reader.take(10), version));
+        Frame::read(reader, version, true, true).map(|(frame, _)| frame)
+    }
+
+    /// Construct a new frame from a reader, also returning the bits of the status and encoding
+    /// flags that were not recognized.
+    ///
+    /// See `Header::from_bytes` for `strict` and `synchsafe_size`.
+    pub(crate) fn read<R: Read>(reader: &mut R, version: u8, strict: bool, synchsafe_size: bool)
+                                -> Result<(Frame, [u8; 2])> {
+        let mut bytes = [0u8; 10];
+        reader.read_exact(&mut bytes).map_err(header::Error::Io)?;
+        let (header, unknown_flags) =
+            Header::from_bytes(&bytes, version, strict, synchsafe_size)?;
+
+        // The size comes from the stream, so only allocate as much as is actually there
+        let mut body = Vec::new();
+        reader.take(header.size() as u64).read_to_end(&mut body)?;
+        if body.len() < header.size() as usize {
+            return Err(Error::Io(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+
+        let unknown = if is_unknown(&header, unknown_flags) {
+            Some(UnknownFrame {
+                id: *header.frame_id(),
+                flags: [bytes[8], bytes[9]],
+                raw_body: body.clone(),
+            })
+        } else {
+            None
+        };
+
+        let (body, group_symbol) = decode_body(&header, version, Cow::Owned(body));
+        let frame = Frame { header, body: body.into_owned(), group_symbol, unknown };
+
+        Ok((frame, unknown_flags))
+    }
+
+    /// Gets the header of the frame.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Gets the frame ID (made out of the characters capital A-Z and 0-9).
+    pub fn id(&self) -> &[u8; 4] {
+        self.header.frame_id()
+    }
+
+    /// Gets the body of the frame (everything following the header, except the group symbol).
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Gets the symbol of the group the frame belongs to, if it is grouped.
+    pub fn group_symbol(&self) -> Option<u8> {
+        self.group_symbol
+    }
+
+    /// Sets the symbol of the group the frame belongs to, or removes it from its group.
+    ///
+    /// Changing the group of a frame that was kept exactly as it was read means it is written
+    /// from its header and body from then on.
+    pub fn set_group_symbol(&mut self, group_symbol: Option<u8>) {
+        if self.group_symbol != group_symbol {
+            self.unknown = None;
+        }
+        self.header.set_grouped(group_symbol.is_some());
+        self.group_symbol = group_symbol;
+    }
+
+    /// Gets whether or not the contents of the frame is intended to be read only.
+    ///
+    /// See `Header::is_read_only`.
+    pub fn is_read_only(&self) -> bool {
+        self.header.is_read_only()
+    }
+
+    /// Sets whether or not the contents of the frame is intended to be read only.
+    ///
+    /// Changing the flag of a frame that was kept exactly as it was read means it is written
+    /// from its header and body from then on.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        if self.is_read_only() != read_only {
+            self.unknown = None;
+        }
+        self.header.set_read_only(read_only);
+    }
+
+    /// Replaces the body of the frame, keeping its status flags and group.
+    pub(crate) fn set_body(&mut self, body: Vec<u8>) {
+        self.header = self.header.for_new_body(body.len() as u32);
+        self.body = body;
+        self.unknown = None;
+    }
+
+    /// Gets the frame exactly as it was read, if it is one that this library cannot decode.
+    ///
+    /// See `UnknownFrame` for which frames are kept like this.
+    pub fn unknown(&self) -> Option<&UnknownFrame> {
+        self.unknown.as_ref()
+    }
+
+    /// Converts a frame that was kept exactly as it was read from a tag of major version
+    /// `from` to one of major version `to`, returning false if it cannot be and should be
+    /// removed. See `UnknownFrame::convert`.
+    pub(crate) fn convert_unknown(&mut self, from: u8, to: u8) -> bool {
+        match self.unknown.as_ref().map(|unknown| unknown.convert(from, to)) {
+            Some(Some(unknown)) => {
+                self.unknown = Some(unknown);
+                true
+            }
+            Some(None) => false,
+            None => true,
+        }
+    }
+
+    /// Converts the frame into its bytes in a tag of the given major version (3 or 4), header
+    /// included.
+    ///
+    /// A frame that this library cannot decode is written exactly as it was read. Otherwise,
+    /// the frame is written from its header and body, without unsynchronisation or a data
+    /// length indicator unless the body is compressed or encrypted, since only then is it kept
+    /// as it was read.
+    pub fn to_bytes(&self, version: u8) -> Vec<u8> {
+        if let Some(ref unknown) = self.unknown {
+            return unknown.to_bytes(version);
+        }
+
+        let raw_body = self.header.is_compressed() || self.header.is_encrypted();
+        let flags = self.header.flag_bytes(version, raw_body);
+        let mut body = self.body.clone();
+
+        if let Some(group_symbol) = self.group_symbol {
+            let position = match version {
+                3 => 4 * self.header.is_compressed() as usize + self.header.is_encrypted() as usize,
+                _ => 0,
+            };
+            body.insert(position.min(body.len()), group_symbol);
+        }
 
-        let frame = Frame { header: header };
+        let size = body.len() as u32;
+        let mut bytes = self.id().to_vec();
+        match version {
+            4 => bytes.extend_from_slice(&utils::u32_to_synchsafe(size)),
+            _ => bytes.extend_from_slice(&size.to_be_bytes()),
+        }
+        bytes.extend_from_slice(&flags);
+        bytes.extend(body);
+
+        bytes
+    }
+}
+
+/// Gets whether or not a frame cannot be decoded, and so is kept exactly as it was read: its
+/// ID is not known, it is compressed or encrypted, or it has flags that were not recognized.
+fn is_unknown(header: &Header, unknown_flags: [u8; 2]) -> bool {
+    !unknown::is_known_frame(header.frame_id()) || header.is_compressed() ||
+    header.is_encrypted() || unknown_flags != [0, 0]
+}
+
+/// Removes the group symbol and data length indicator from the body of a frame, and reverses
+/// unsynchronisation, returning the body with the group symbol.
+///
+/// A borrowed body is only copied if a byte has to be removed from the middle of it, or if it
+/// was unsynchronised.
+fn decode_body<'a>(header: &Header, version: u8, mut body: Cow<'a, [u8]>)
+                   -> (Cow<'a, [u8]>, Option<u8>) {
+    // The group symbol follows the decompressed size and encryption method in `v2.3.0`, but
+    // comes before the other header data in `v2.4.0`
+    let mut group_symbol = None;
+    if header.is_grouped() {
+        let position = match version {
+            3 => 4 * header.is_compressed() as usize + header.is_encrypted() as usize,
+            _ => 0,
+        };
+
+        if position < body.len() {
+            group_symbol = Some(body[position]);
+            body = remove(body, position, 1);
+        }
+    }
+
+    // Compressed or encrypted data is kept as it is, along with the data length indicator
+    if !header.is_compressed() && !header.is_encrypted() {
+        if header.has_data_length_indicator() && body.len() >= 4 {
+            body = remove(body, 0, 4);
+        }
+        if header.is_unsynchronised() {
+            body = Cow::Owned(utils::resynchronise(&body));
+        }
+    }
+
+    (body, group_symbol)
+}
 
-        Ok(frame)
+/// Removes `length` bytes at `position` from a body, without copying it if they are at the
+/// start of a borrowed body.
+fn remove(body: Cow<[u8]>, position: usize, length: usize) -> Cow<[u8]> {
+    match body {
+        Cow::Borrowed(bytes) if position == 0 => Cow::Borrowed(&bytes[length..]),
+        mut body => {
+            body.to_mut().drain(position..position + length);
+            body
+        }
     }
 }
@@ -0,0 +1,315 @@
+//! Types and functions related to the MPEG location lookup table frame (`MLLT`).
+
+use std::time::Duration;
+
+use frame::{Error, Frame, Result};
+use mpeg::ScannedFrame;
+
+/// The largest distance between references that `MLLT` can hold, in bytes or milliseconds.
+const MAX_BETWEEN: u64 = 0xFF_FFFF;
+
+/// A type representing a point in the audio that playback can start from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SeekPoint {
+    /// The number of MPEG frames before the point.
+    pub frame: u64,
+    /// The number of bytes between the first MPEG frame and the point.
+    pub offset: u64,
+    /// The time of the point since the start of the audio, in milliseconds.
+    pub time: u64,
+}
+
+/// A type representing the body of an MPEG location lookup table frame.
+///
+/// The table holds a reference every `frames_between` MPEG frames, starting with the first.
+/// Each reference is `bytes_between` bytes and `millis_between` milliseconds after the one
+/// before it, plus a deviation.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::File;
+/// # use id3::tag::Tag;
+/// # use id3::frame::mpeg_location::MpegLocationLookup;
+/// let mut file = File::open("examples/files/test_v3.mp3").unwrap();
+/// Tag::from_reader(&mut file).unwrap();
+///
+/// let frames = id3::mpeg::scan(&mut file).unwrap();
+/// let table = MpegLocationLookup::from_scan(&frames, 10).unwrap();
+///
+/// assert_eq!(table.seek_points().next().unwrap().offset, 0);
+/// ```
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.7)](http://id3.org/id3v2.3.0#MPEG_location_lookup_table)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MpegLocationLookup {
+    /// The number of MPEG frames between references.
+    pub frames_between: u16,
+    /// The number of bytes between references, before the deviation.
+    pub bytes_between: u32,
+    /// The number of milliseconds between references, before the deviation.
+    pub millis_between: u32,
+    /// The number of bits used for each deviation in bytes.
+    pub bits_for_bytes: u8,
+    /// The number of bits used for each deviation in milliseconds.
+    pub bits_for_millis: u8,
+    /// The `(bytes, milliseconds)` deviation of each reference after the first.
+    pub deviations: Vec<(u64, u64)>,
+}
+
+impl MpegLocationLookup {
+    /// Construct a new lookup table from the body of a frame.
+    ///
+    /// Deviations wider than 64 bits keep their lowest 64 bits. Any bits after the last whole
+    /// deviation are ignored.
+    ///
+    /// # Errors
+    ///
+    /// If the body is shorter than 10 bytes, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MpegLocationLookup> {
+        if bytes.len() < 10 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let bits_for_bytes = bytes[8];
+        let bits_for_millis = bytes[9];
+        let mut reader = BitReader { bytes: &bytes[10..], position: 0 };
+        let mut deviations = Vec::new();
+
+        if bits_for_bytes as u16 + bits_for_millis as u16 > 0 {
+            while let (Some(bytes), Some(millis)) = (reader.read(bits_for_bytes),
+                                                     reader.read(bits_for_millis)) {
+                deviations.push((bytes, millis));
+            }
+        }
+
+        Ok(MpegLocationLookup {
+            frames_between: u16::from_be_bytes([bytes[0], bytes[1]]),
+            bytes_between: u32::from_be_bytes([0, bytes[2], bytes[3], bytes[4]]),
+            millis_between: u32::from_be_bytes([0, bytes[5], bytes[6], bytes[7]]),
+            bits_for_bytes,
+            bits_for_millis,
+            deviations,
+        })
+    }
+
+    /// Construct a new lookup table from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `MpegLocationLookup::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<MpegLocationLookup> {
+        MpegLocationLookup::from_bytes(frame.body())
+    }
+
+    /// Construct a new lookup table with a reference every `frames_between` frames, from a scan
+    /// of the audio (see `mpeg::scan`).
+    ///
+    /// The distances between references are the smallest found, so that every deviation is
+    /// positive, and the deviations use as few bits as possible.
+    ///
+    /// Returns `None` if `frames_between` is 0, there are no frames, or the references are too
+    /// far apart for the frame to hold.
+    pub fn from_scan(frames: &[ScannedFrame], frames_between: u16) -> Option<MpegLocationLookup> {
+        let first = frames.first()?;
+        if frames_between == 0 {
+            return None;
+        }
+
+        // The offset and time of each reference, with times rounded down to the millisecond
+        let mut references = Vec::new();
+        let mut samples = 0u64;
+        for (index, frame) in frames.iter().enumerate() {
+            if index % frames_between as usize == 0 {
+                let time = samples * 1000 / frame.header.sample_rate as u64;
+                references.push((frame.offset - first.offset, time));
+            }
+            samples += frame.header.samples() as u64;
+        }
+
+        let distances: Vec<(u64, u64)> = references
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1))
+            .collect();
+
+        let bytes_between = distances.iter().map(|distance| distance.0).min().unwrap_or(0);
+        let millis_between = distances.iter().map(|distance| distance.1).min().unwrap_or(0);
+        if bytes_between > MAX_BETWEEN || millis_between > MAX_BETWEEN {
+            return None;
+        }
+
+        let deviations: Vec<(u64, u64)> = distances
+            .iter()
+            .map(|&(bytes, millis)| (bytes - bytes_between, millis - millis_between))
+            .collect();
+
+        let bits_needed = |value: u64| (64 - value.leading_zeros()) as u8;
+        let bits_for_bytes = deviations.iter().map(|deviation| bits_needed(deviation.0)).max();
+        let bits_for_millis = deviations.iter().map(|deviation| bits_needed(deviation.1)).max();
+        let bits_for_bytes = bits_for_bytes.unwrap_or(0);
+        let mut bits_for_millis = bits_for_millis.unwrap_or(0);
+
+        // The standard only asks for a multiple of 4 bits, but filling whole bytes means a reader
+        // cannot mistake the padding at the end for another deviation
+        while (bits_for_bytes + bits_for_millis) % 8 != 0 {
+            bits_for_millis += 1;
+        }
+
+        Some(MpegLocationLookup {
+            frames_between,
+            bytes_between: bytes_between as u32,
+            millis_between: millis_between as u32,
+            bits_for_bytes,
+            bits_for_millis,
+            deviations,
+        })
+    }
+
+    /// Gets an iterator over every reference in the table, starting with the first MPEG frame.
+    pub fn seek_points<'a>(&'a self) -> impl Iterator<Item = SeekPoint> + 'a {
+        let start = SeekPoint::default();
+
+        Some(start).into_iter().chain(self.deviations.iter().scan(start, move |point, deviation| {
+            point.frame += self.frames_between as u64;
+            point.offset += self.bytes_between as u64 + deviation.0;
+            point.time += self.millis_between as u64 + deviation.1;
+
+            Some(*point)
+        }))
+    }
+
+    /// Gets the last reference at or before `time`, to start playback from.
+    pub fn seek(&self, time: Duration) -> SeekPoint {
+        let millis = time.as_millis() as u64;
+
+        self.seek_points().take_while(|point| point.time <= millis).last().unwrap_or_default()
+    }
+
+    /// Converts the lookup table into a frame body.
+    ///
+    /// The distances between references are written as their lowest 24 bits, and the
+    /// deviations as their lowest bits.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.frames_between.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.bytes_between.to_be_bytes()[1..]);
+        bytes.extend_from_slice(&self.millis_between.to_be_bytes()[1..]);
+        bytes.extend_from_slice(&[self.bits_for_bytes, self.bits_for_millis]);
+
+        let mut writer = BitWriter { bytes, used: 0 };
+        for &(deviation_bytes, deviation_millis) in &self.deviations {
+            writer.write(deviation_bytes, self.bits_for_bytes);
+            writer.write(deviation_millis, self.bits_for_millis);
+        }
+
+        writer.bytes
+    }
+
+    /// Converts the lookup table into an `MLLT` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"MLLT", self.to_bytes())
+    }
+}
+
+/// Reads big endian values of any number of bits from a byte slice.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Reads the next `bits` bits, keeping the lowest 64, or `None` if there are not enough.
+    fn read(&mut self, bits: u8) -> Option<u64> {
+        if self.position + bits as usize > self.bytes.len() * 8 {
+            return None;
+        }
+
+        let mut value = 0u64;
+        for _ in 0..bits {
+            let bit = self.bytes[self.position / 8] >> (7 - self.position % 8) & 1;
+            value = value.wrapping_shl(1) | bit as u64;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+}
+
+/// Writes big endian values of any number of bits onto the end of a byte vector.
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u8,
+}
+
+impl BitWriter {
+    /// Writes the lowest `bits` bits of `value`, padding with zeros beyond 64 bits.
+    fn write(&mut self, value: u64, bits: u8) {
+        for index in (0..bits).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+
+            let bit = if index < 64 { (value >> index) & 1 } else { 0 };
+            *self.bytes.last_mut().unwrap() |= (bit as u8) << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod mpeg_location_tests {
+    use std::time::Duration;
+
+    use mpeg::{AudioFrame, ScannedFrame};
+    use super::{MpegLocationLookup, SeekPoint};
+
+    #[test]
+    fn it_reads_and_writes() {
+        // Deviations of (1, 2) and (3, 0) in 4 bits each
+        let bytes = b"\x00\x0a\x00\x10\x40\x00\x01\x04\x04\x04\x12\x30";
+        let table = MpegLocationLookup::from_bytes(bytes).unwrap();
+
+        assert_eq!(table.frames_between, 10);
+        assert_eq!((table.bytes_between, table.millis_between), (0x1040, 0x104));
+        assert_eq!(table.deviations, vec![(1, 2), (3, 0)]);
+
+        let points: Vec<SeekPoint> = table.seek_points().collect();
+        assert_eq!(points[2], SeekPoint { frame: 20, offset: 0x2084, time: 0x20A });
+
+        assert_eq!(table.to_bytes(), &bytes[..]);
+        assert!(MpegLocationLookup::from_bytes(&bytes[..9]).is_err());
+    }
+
+    #[test]
+    fn it_generates_from_a_scan() {
+        let low = AudioFrame::from_bytes([0xFF, 0xFB, 0x50, 0x44]).unwrap();
+        let high = AudioFrame::from_bytes([0xFF, 0xFB, 0x90, 0x44]).unwrap();
+
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        for index in 0..25 {
+            let header = if index % 3 == 0 { high } else { low };
+            frames.push(ScannedFrame { offset, header });
+            offset += header.length() as u64;
+        }
+
+        let table = MpegLocationLookup::from_scan(&frames, 4).unwrap();
+        let points: Vec<SeekPoint> = table.seek_points().collect();
+
+        assert_eq!(points.len(), 7);
+        assert_eq!((table.bits_for_bytes + table.bits_for_millis) % 8, 0);
+        for point in &points {
+            let frame = point.frame as usize;
+
+            assert_eq!(point.offset, frames[frame].offset);
+            assert_eq!(point.time, frame as u64 * 1152 * 1000 / 44100);
+        }
+
+        let round_trip = MpegLocationLookup::from_bytes(&table.to_bytes()).unwrap();
+        assert_eq!(round_trip.seek_points().collect::<Vec<_>>(), points);
+        assert_eq!(table.seek(Duration::from_millis(500)), points[4]);
+        assert_eq!(MpegLocationLookup::from_scan(&frames, 0), None);
+    }
+}
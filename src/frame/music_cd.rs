@@ -0,0 +1,311 @@
+//! Types and functions related to the music CD identifier frame (`MCDI`), which holds the table
+//! of contents of the CD the audio was taken from.
+
+use sha1_smol::Sha1;
+
+use frame::{Error, Frame, Result};
+
+/// The track number of the lead-out, which marks the end of the last track.
+pub const LEADOUT_TRACK: u8 = 0xaa;
+
+/// The number of frames before the first logical block address, which freedb and MusicBrainz
+/// include in their offsets.
+const PREGAP_FRAMES: u32 = 150;
+
+/// The number of frames in a second of CD audio.
+const FRAMES_PER_SECOND: u32 = 75;
+
+/// How the addresses in a table of contents are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Addressing {
+    /// Logical block addresses, counted in frames from the end of the pregap.
+    #[default]
+    Lba,
+    /// Minutes, seconds and frames from the start of the disc, including the pregap, as
+    /// Windows stores them.
+    Msf,
+}
+
+/// A type representing a track descriptor in a CD table of contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CdTrack {
+    /// The track number, or `LEADOUT_TRACK`.
+    pub number: u8,
+    /// The ADR field in the high four bits and the control field in the low four bits. Audio
+    /// tracks usually have `0x10`, and data tracks `0x14`.
+    pub adr_control: u8,
+    /// The logical block address where the track starts.
+    pub lba: u32,
+}
+
+/// A type representing the body of a music CD identifier frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.5)](http://id3.org/id3v2.3.0#Music_CD_identifier)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MusicCdIdentifier {
+    /// The number of the first track on the disc.
+    pub first_track: u8,
+    /// The number of the last track on the disc.
+    pub last_track: u8,
+    /// The tracks on the disc, in order.
+    pub tracks: Vec<CdTrack>,
+    /// The lead-out, which starts after the last track.
+    pub leadout: CdTrack,
+    /// How the addresses are stored in the frame body.
+    pub addressing: Addressing,
+}
+
+impl MusicCdIdentifier {
+    /// Construct a new music CD identifier from the body of a frame.
+    ///
+    /// The addresses are read as minutes, seconds and frames if every one of them is valid in
+    /// that form and the first track starts two seconds into the disc, and as logical block
+    /// addresses otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends part way through the header or a track descriptor, then this function
+    /// will return `Error::UnexpectedEnd`.
+    ///
+    /// If there is no lead-out track, then this function will return
+    /// `Error::InvalidTableOfContents`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MusicCdIdentifier> {
+        if bytes.len() < 4 || !(bytes.len() - 4).is_multiple_of(8) {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let descriptors = bytes[4..].chunks(8).collect::<Vec<_>>();
+        let addressing = if is_msf(&descriptors) { Addressing::Msf } else { Addressing::Lba };
+
+        let mut tracks = Vec::new();
+        let mut leadout = None;
+
+        for descriptor in descriptors {
+            let address = [descriptor[4], descriptor[5], descriptor[6], descriptor[7]];
+            let track = CdTrack {
+                number: descriptor[2],
+                adr_control: descriptor[1],
+                lba: match addressing {
+                    Addressing::Lba => u32::from_be_bytes(address),
+                    Addressing::Msf => msf_to_frames(address).saturating_sub(PREGAP_FRAMES),
+                },
+            };
+
+            if track.number == LEADOUT_TRACK {
+                leadout = Some(track);
+            } else {
+                tracks.push(track);
+            }
+        }
+
+        Ok(MusicCdIdentifier {
+            first_track: bytes[2],
+            last_track: bytes[3],
+            tracks,
+            leadout: leadout.ok_or(Error::InvalidTableOfContents)?,
+            addressing,
+        })
+    }
+
+    /// Construct a new music CD identifier from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `MusicCdIdentifier::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<MusicCdIdentifier> {
+        MusicCdIdentifier::from_bytes(frame.body())
+    }
+
+    /// Gets the freedb (CDDB) disc ID, which is usually written in hexadecimal.
+    ///
+    /// The ID is made from a checksum of the start time of each track in seconds, the length of
+    /// the disc in seconds and the number of tracks.
+    pub fn freedb_id(&self) -> u32 {
+        let seconds = |track: &CdTrack| track.lba.saturating_add(PREGAP_FRAMES) / FRAMES_PER_SECOND;
+
+        let checksum = self.tracks.iter()
+            .map(|track| {
+                let mut seconds = seconds(track);
+                let mut sum = 0;
+                while seconds > 0 {
+                    sum += seconds % 10;
+                    seconds /= 10;
+                }
+                sum
+            })
+            .sum::<u32>();
+        let start = self.tracks.first().map_or(0, &seconds);
+        let length = seconds(&self.leadout).saturating_sub(start);
+
+        (checksum % 255) << 24 | (length & 0xffff) << 8 | (self.tracks.len() as u32 & 0xff)
+    }
+
+    /// Gets the MusicBrainz disc ID.
+    ///
+    /// # Reference
+    ///
+    /// [MusicBrainz Disc ID Calculation](https://musicbrainz.org/doc/Disc_ID_Calculation)
+    pub fn musicbrainz_id(&self) -> String {
+        let mut offsets = [0u32; 100];
+        offsets[0] = self.leadout.lba.saturating_add(PREGAP_FRAMES);
+        for track in &self.tracks {
+            if track.number > 0 && (track.number as usize) < offsets.len() {
+                offsets[track.number as usize] = track.lba.saturating_add(PREGAP_FRAMES);
+            }
+        }
+
+        let mut sha1 = Sha1::new();
+        sha1.update(format!("{:02X}{:02X}", self.first_track, self.last_track).as_bytes());
+        for offset in offsets.iter() {
+            sha1.update(format!("{:08X}", offset).as_bytes());
+        }
+
+        musicbrainz_base64(&sha1.digest().bytes())
+    }
+
+    /// Converts the music CD identifier into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let length = 2 + 8 * (self.tracks.len() + 1);
+
+        let mut bytes = (length as u16).to_be_bytes().to_vec();
+        bytes.push(self.first_track);
+        bytes.push(self.last_track);
+
+        for track in self.tracks.iter().chain(Some(&self.leadout)) {
+            bytes.extend_from_slice(&[0, track.adr_control, track.number, 0]);
+            bytes.extend_from_slice(&match self.addressing {
+                Addressing::Lba => track.lba.to_be_bytes(),
+                Addressing::Msf => frames_to_msf(track.lba.saturating_add(PREGAP_FRAMES)),
+            });
+        }
+
+        bytes
+    }
+
+    /// Converts the music CD identifier into an `MCDI` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"MCDI", self.to_bytes())
+    }
+}
+
+/// Gets whether or not the addresses of the track descriptors are in minutes, seconds and
+/// frames, which always start with a zero byte and have the first track two seconds in.
+fn is_msf(descriptors: &[&[u8]]) -> bool {
+    let valid = descriptors.iter().all(|descriptor| {
+        descriptor[4] == 0 && descriptor[6] < 60 && (descriptor[7] as u32) < FRAMES_PER_SECOND
+    });
+
+    valid && descriptors.first().is_some_and(|descriptor| descriptor[4..8] == [0, 0, 2, 0])
+}
+
+/// Converts an address in minutes, seconds and frames into a number of frames.
+fn msf_to_frames(address: [u8; 4]) -> u32 {
+    (address[1] as u32 * 60 + address[2] as u32) * FRAMES_PER_SECOND + address[3] as u32
+}
+
+/// Converts a number of frames into an address in minutes, seconds and frames, clamping it to
+/// the largest address that can be written, 255:59:74.
+fn frames_to_msf(frames: u32) -> [u8; 4] {
+    let frames = frames.min(msf_to_frames([0, 255, 59, FRAMES_PER_SECOND as u8 - 1]));
+    let seconds = frames / FRAMES_PER_SECOND;
+
+    [0, (seconds / 60) as u8, (seconds % 60) as u8, (frames % FRAMES_PER_SECOND) as u8]
+}
+
+/// Encodes bytes as base64 with the alphabet MusicBrainz uses, which replaces `+`, `/` and `=`
+/// with `.`, `_` and `-` so that the ID can be used in a URL.
+fn musicbrainz_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789._";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('-');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod music_cd_tests {
+    use super::{Addressing, CdTrack, LEADOUT_TRACK, MusicCdIdentifier};
+
+    fn disc() -> MusicCdIdentifier {
+        let offsets = [
+            150, 9700, 25887, 39297, 53795, 63735, 77517, 94877, 107270, 123552, 135522, 148422,
+            161197, 174790, 192022, 205545, 218010, 228700, 239590, 255470, 266932, 288750,
+        ];
+
+        MusicCdIdentifier {
+            first_track: 1,
+            last_track: 22,
+            tracks: offsets.iter()
+                .enumerate()
+                .map(|(index, offset)| {
+                    CdTrack { number: index as u8 + 1, adr_control: 0x10, lba: offset - 150 }
+                })
+                .collect(),
+            leadout: CdTrack { number: LEADOUT_TRACK, adr_control: 0x10, lba: 303602 - 150 },
+            addressing: Addressing::Lba,
+        }
+    }
+
+    #[test]
+    fn it_computes_disc_ids() {
+        let disc = disc();
+
+        assert_eq!(disc.freedb_id(), 0x370fce16);
+        assert_eq!(disc.musicbrainz_id(), "xUp1F2NkfP8s8jaeFn_Av3jNEI4-");
+    }
+
+    #[test]
+    fn it_reads_and_writes_lba_addresses() {
+        let disc = disc();
+        let bytes = disc.to_bytes();
+
+        assert_eq!(&bytes[..4], b"\x00\xba\x01\x16");
+        assert_eq!(&bytes[4..12], b"\x00\x10\x01\x00\x00\x00\x00\x00");
+        assert_eq!(MusicCdIdentifier::from_bytes(&bytes).unwrap(), disc);
+        assert!(MusicCdIdentifier::from_bytes(&bytes[..bytes.len() - 8]).is_err());
+        assert!(MusicCdIdentifier::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn it_reads_and_writes_msf_addresses() {
+        let bytes = b"\x00\x1a\x01\x02\
+                      \x00\x10\x01\x00\x00\x00\x02\x00\
+                      \x00\x10\x02\x00\x00\x03\x10\x05\
+                      \x00\x10\xaa\x00\x00\x07\x00\x00";
+        let disc = MusicCdIdentifier::from_bytes(bytes).unwrap();
+
+        assert_eq!(disc.addressing, Addressing::Msf);
+        assert_eq!(disc.tracks.iter().map(|track| track.lba).collect::<Vec<_>>(), vec![0, 14555]);
+        assert_eq!(disc.leadout.lba, 31350);
+        assert_eq!(disc.to_bytes(), &bytes[..]);
+    }
+
+    #[test]
+    fn it_handles_addresses_past_the_end_of_a_cd() {
+        let mut disc = disc();
+        disc.leadout.lba = u32::MAX;
+        disc.tracks[21].lba = u32::MAX;
+
+        disc.freedb_id();
+        disc.musicbrainz_id();
+        disc.addressing = Addressing::Msf;
+        let bytes = disc.to_bytes();
+        assert_eq!(&bytes[bytes.len() - 4..], b"\x00\xff\x3b\x4a");
+    }
+}
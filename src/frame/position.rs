@@ -0,0 +1,171 @@
+//! Types and functions related to the track number (`TRCK`) and part of a set (`TPOS`) frames.
+
+use std::fmt;
+use std::str::FromStr;
+
+use frame::Error;
+
+/// A type representing a position in a set, such as a track on an album or a disc in a box set,
+/// in the form `n` or `n/total`.
+///
+/// The width of a number written with leading zeros is remembered, so that the zeros survive
+/// being read and written back. Numbers written without them are not padded.
+///
+/// # Examples
+///
+/// ```
+/// # use id3::frame::position::Position;
+/// let mut position: Position = "03/12".parse().unwrap();
+///
+/// assert_eq!(position.pair(), (3, Some(12)));
+///
+/// position.set_number(4);
+/// assert_eq!(position.to_string(), "04/12");
+/// ```
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.2.1)](http://id3.org/id3v2.3.0#TRCK)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    number: u32,
+    total: Option<u32>,
+    number_width: usize,
+    total_width: usize,
+}
+
+impl Position {
+    /// Construct a new position without leading zeros.
+    pub fn new(number: u32, total: Option<u32>) -> Position {
+        Position { number, total, number_width: 0, total_width: 0 }
+    }
+
+    /// Gets the number.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Gets the total, if there is one.
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+
+    /// Gets the number and total.
+    pub fn pair(&self) -> (u32, Option<u32>) {
+        (self.number, self.total)
+    }
+
+    /// Sets the number, keeping any leading zeros.
+    pub fn set_number(&mut self, number: u32) {
+        self.number = number;
+    }
+
+    /// Sets the total, keeping any leading zeros.
+    pub fn set_total(&mut self, total: Option<u32>) {
+        self.total = total;
+    }
+}
+
+impl FromStr for Position {
+    type Err = Error;
+
+    /// Parses a position in the form `n` or `n/total`, ignoring surrounding whitespace.
+    fn from_str(text: &str) -> Result<Position, Error> {
+        let text = text.trim_end_matches('\0');
+
+        let (number, total) = match text.find('/') {
+            Some(index) => (&text[..index], Some(&text[index + 1..])),
+            None => (text, None),
+        };
+
+        let (number, number_width) = parse_number(number).ok_or(Error::InvalidPosition)?;
+        let (total, total_width) = match total.map(|total| total.trim()) {
+            // A trailing slash is as good as no total at all
+            Some("") | None => (None, 0),
+            Some(total) => {
+                let (total, width) = parse_number(total).ok_or(Error::InvalidPosition)?;
+                (Some(total), width)
+            }
+        };
+
+        Ok(Position { number, total, number_width, total_width })
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0width$}", self.number, width = self.number_width)?;
+
+        if let Some(total) = self.total {
+            write!(f, "/{:0width$}", total, width = self.total_width)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a number, returning it along with the width it was padded to with leading zeros, or 0
+/// if it was not.
+fn parse_number(text: &str) -> Option<(u32, usize)> {
+    let text = text.trim();
+
+    if !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit()) {
+        let width = if text.len() > 1 && text.starts_with('0') { text.len() } else { 0 };
+        text.parse().ok().map(|number| (number, width))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod position_tests {
+    use super::Position;
+
+    fn parse(text: &str) -> Option<(u32, Option<u32>)> {
+        text.parse::<Position>().ok().map(|position| position.pair())
+    }
+
+    #[test]
+    fn it_parses() {
+        assert_eq!(parse("3/12"), Some((3, Some(12))));
+        assert_eq!(parse("03"), Some((3, None)));
+        assert_eq!(parse(" 3 / 12 "), Some((3, Some(12))));
+        assert_eq!(parse("3/"), Some((3, None)));
+    }
+
+    #[test]
+    fn it_rejects_garbage() {
+        for text in ["", "/12", "A1", "3 of 12", "3/12/1", "-3", "99999999999"].iter() {
+            assert_eq!(parse(text), None, "{} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn it_keeps_leading_zeros() {
+        for text in ["03/12", "003/012", "3/012", "07"].iter() {
+            assert_eq!(&text.parse::<Position>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn it_only_pads_numbers_written_with_leading_zeros() {
+        let mut position: Position = "12/12".parse().unwrap();
+        position.set_number(3);
+
+        assert_eq!(position.to_string(), "3/12");
+
+        let mut position: Position = "10".parse().unwrap();
+        position.set_total(Some(5));
+
+        assert_eq!(position.to_string(), "10/5");
+    }
+
+    #[test]
+    fn it_does_not_pad_a_new_total() {
+        let mut position: Position = "03".parse().unwrap();
+        position.set_total(Some(9));
+
+        assert_eq!(position.to_string(), "03/9");
+        assert_eq!(Position::new(3, Some(12)).to_string(), "3/12");
+    }
+}
@@ -0,0 +1,391 @@
+//! Types and functions related to ReplayGain metadata, which different software stores in
+//! different frames.
+
+use frame::user_text::UserText;
+use frame::volume::{Channel, ChannelAdjustment, VolumeAdjustment};
+use frame::{Error, Result};
+
+/// A type representing one of the ways ReplayGain metadata can be stored in a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Convention {
+    /// `TXXX` frames described as `REPLAYGAIN_TRACK_GAIN`, `REPLAYGAIN_TRACK_PEAK`,
+    /// `REPLAYGAIN_ALBUM_GAIN` and `REPLAYGAIN_ALBUM_PEAK`, in any case.
+    UserText,
+    /// `RVA2` frames identified as `track` and `album`, using the master volume channel. _Only
+    /// `v2.4.0`._
+    VolumeAdjustment,
+    /// The `RGAD` frame from the ReplayGain proposal, which never made it into the standard.
+    Rgad,
+    /// The `COMM` frame described as `iTunNORM` that iTunes writes for Sound Check. It only
+    /// holds the track gain and peak.
+    ITunNorm,
+}
+
+/// Every convention, in the order they are read by `Tag::replay_gain`.
+///
+/// Dedicated `TXXX` frames are the most widely used and the most precise, so they come first,
+/// while `iTunNORM` is only an approximation of ReplayGain and comes last.
+pub const CONVENTIONS: [Convention; 4] = [
+    Convention::UserText,
+    Convention::VolumeAdjustment,
+    Convention::Rgad,
+    Convention::ITunNorm,
+];
+
+/// The `TXXX` descriptions used for ReplayGain, in the order track gain, track peak, album gain
+/// and album peak.
+pub const USER_TEXT_KEYS: [&str; 4] = [
+    "REPLAYGAIN_TRACK_GAIN",
+    "REPLAYGAIN_TRACK_PEAK",
+    "REPLAYGAIN_ALBUM_GAIN",
+    "REPLAYGAIN_ALBUM_PEAK",
+];
+
+/// The full scale sample used for the peaks in `iTunNORM`.
+const ITUNNORM_FULL_SCALE: f64 = 32768.0;
+
+/// A type representing ReplayGain metadata.
+///
+/// Gains are in decibels and peaks are a fraction of full scale, so `1.0` is the loudest sample
+/// that can be represented without clipping.
+///
+/// # Reference
+///
+/// [ReplayGain 1.0 specification](http://wiki.hydrogenaud.io/index.php?title=ReplayGain_1.0_specification)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayGain {
+    /// The gain to apply when playing the track on its own.
+    pub track_gain: Option<f32>,
+    /// The peak of the track.
+    pub track_peak: Option<f32>,
+    /// The gain to apply when playing the track as part of its album.
+    pub album_gain: Option<f32>,
+    /// The peak of the album.
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGain {
+    /// Gets whether or not every value is missing.
+    pub fn is_empty(&self) -> bool {
+        *self == ReplayGain::default()
+    }
+
+    /// Fills in any missing values from `other`.
+    pub fn or(self, other: ReplayGain) -> ReplayGain {
+        ReplayGain {
+            track_gain: self.track_gain.or(other.track_gain),
+            track_peak: self.track_peak.or(other.track_peak),
+            album_gain: self.album_gain.or(other.album_gain),
+            album_peak: self.album_peak.or(other.album_peak),
+        }
+    }
+
+    /// Construct new metadata from `TXXX` frames, ignoring any that are not for ReplayGain or
+    /// cannot be parsed.
+    ///
+    /// Gains may have a ` dB` suffix, and descriptions are compared without regard to case.
+    pub fn from_user_text(texts: &[UserText]) -> ReplayGain {
+        let mut gain = ReplayGain::default();
+
+        for text in texts {
+            let value = text.value.trim_end_matches('\0');
+            let key = USER_TEXT_KEYS
+                .iter()
+                .position(|key| key.eq_ignore_ascii_case(&text.description));
+
+            match key {
+                Some(0) => gain.track_gain = gain.track_gain.or(parse_gain(value)),
+                Some(1) => gain.track_peak = gain.track_peak.or(value.trim().parse().ok()),
+                Some(2) => gain.album_gain = gain.album_gain.or(parse_gain(value)),
+                Some(3) => gain.album_peak = gain.album_peak.or(value.trim().parse().ok()),
+                _ => {}
+            }
+        }
+
+        gain
+    }
+
+    /// Converts the metadata into `TXXX` frame bodies for a tag of the given major version, in
+    /// the format `-6.20 dB` for gains and `0.988553` for peaks.
+    pub fn to_user_text(&self, version: u8) -> Vec<UserText> {
+        let values = [
+            self.track_gain.map(format_gain),
+            self.track_peak.map(|peak| format!("{:.6}", peak)),
+            self.album_gain.map(format_gain),
+            self.album_peak.map(|peak| format!("{:.6}", peak)),
+        ];
+
+        USER_TEXT_KEYS
+            .iter()
+            .zip(values)
+            .filter_map(|(key, value)| {
+                value.map(|value| UserText::for_version(key.to_string(), value, version))
+            })
+            .collect()
+    }
+
+    /// Construct new metadata from the master volume of the `track` and `album` volume
+    /// adjustments, ignoring any others.
+    pub fn from_volume_adjustments(volumes: &[VolumeAdjustment]) -> ReplayGain {
+        let find = |identification: &str| {
+            volumes
+                .iter()
+                .filter(|volume| volume.identification.eq_ignore_ascii_case(identification))
+                .filter_map(|volume| volume.channel(Channel::MasterVolume))
+                .next()
+        };
+
+        let track = find("track");
+        let album = find("album");
+
+        ReplayGain {
+            track_gain: track.map(|track| track.adjustment),
+            track_peak: track.and_then(|track| track.peak),
+            album_gain: album.map(|album| album.adjustment),
+            album_peak: album.and_then(|album| album.peak),
+        }
+    }
+
+    /// Converts the metadata into `track` and `album` volume adjustments of the master volume.
+    ///
+    /// An adjustment is only produced if its gain is known.
+    pub fn to_volume_adjustments(&self) -> Vec<VolumeAdjustment> {
+        let adjustments = [
+            ("track", self.track_gain, self.track_peak),
+            ("album", self.album_gain, self.album_peak),
+        ];
+
+        adjustments
+            .iter()
+            .filter_map(|&(identification, gain, peak)| {
+                gain.map(|adjustment| VolumeAdjustment {
+                    identification: identification.to_string(),
+                    channels: vec![ChannelAdjustment {
+                        channel: Channel::MasterVolume,
+                        adjustment,
+                        peak,
+                    }],
+                })
+            })
+            .collect()
+    }
+
+    /// Construct new metadata from the body of an `RGAD` frame.
+    ///
+    /// The peak is read as a big endian floating point number, and a peak of `0.0` is treated
+    /// as missing. The radio adjustment is the track gain and the audiophile adjustment is the
+    /// album gain, whichever field they appear in.
+    ///
+    /// # Errors
+    ///
+    /// If the body is shorter than 8 bytes, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_rgad(bytes: &[u8]) -> Result<ReplayGain> {
+        if bytes.len() < 8 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let peak = f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let mut gain = ReplayGain {
+            track_peak: if peak > 0.0 { Some(peak) } else { None },
+            ..ReplayGain::default()
+        };
+
+        for field in [[bytes[4], bytes[5]], [bytes[6], bytes[7]]].iter() {
+            let field = u16::from_be_bytes(*field);
+            let adjustment = (field & 0x1FF) as f32 / 10.0;
+            let adjustment = if field & 0x200 != 0 { -adjustment } else { adjustment };
+
+            match field >> 13 {
+                1 => gain.track_gain = Some(adjustment),
+                2 => gain.album_gain = Some(adjustment),
+                _ => {}
+            }
+        }
+
+        Ok(gain)
+    }
+
+    /// Converts the metadata into the body of an `RGAD` frame.
+    ///
+    /// The album peak cannot be represented and is dropped. Gains are rounded to the nearest
+    /// tenth of a decibel, and marked as calculated automatically.
+    pub fn to_rgad(&self) -> Vec<u8> {
+        let mut bytes = self.track_peak.unwrap_or(0.0).to_be_bytes().to_vec();
+
+        for &(name, gain) in [(1u16, self.track_gain), (2u16, self.album_gain)].iter() {
+            let field = match gain {
+                Some(gain) => {
+                    let value = (gain.abs() * 10.0).round().min(0x1FF as f32) as u16;
+                    let sign = if gain < 0.0 { 0x200 } else { 0 };
+
+                    // The originator code 3 means the gain was determined automatically
+                    name << 13 | 3 << 10 | sign | value
+                }
+                None => 0,
+            };
+            bytes.extend_from_slice(&field.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Construct new metadata from the text of an `iTunNORM` comment, which is ten hexadecimal
+    /// numbers separated by spaces.
+    ///
+    /// The first two numbers are the loudness of the left and right channels in thousandths of
+    /// a milliwatt, from which the track gain is derived using the louder channel. The seventh
+    /// and eighth are the peak samples of each channel.
+    ///
+    /// Returns `None` if the text cannot be parsed.
+    pub fn from_itunnorm(text: &str) -> Option<ReplayGain> {
+        let values = text.trim_end_matches('\0')
+            .split_whitespace()
+            .map(|value| u32::from_str_radix(value, 16).ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        if values.len() < 2 {
+            return None;
+        }
+
+        let loudness = values[0].max(values[1]);
+        let peak = values.get(6).max(values.get(7)).cloned();
+
+        Some(ReplayGain {
+            track_gain: if loudness > 0 {
+                Some((-10.0 * (loudness as f64 / 1000.0).log10()) as f32)
+            } else {
+                None
+            },
+            track_peak: peak.map(|peak| (peak as f64 / ITUNNORM_FULL_SCALE) as f32),
+            ..ReplayGain::default()
+        })
+    }
+
+    /// Converts the metadata into the text of an `iTunNORM` comment.
+    ///
+    /// Only the track gain and peak can be represented. Returns `None` if the track gain is
+    /// missing.
+    pub fn to_itunnorm(&self) -> Option<String> {
+        let gain = self.track_gain? as f64;
+        let loudness = |base: f64| {
+            (base * 10f64.powf(-gain / 10.0)).round().max(1.0).min(u32::MAX as f64) as u32
+        };
+        let peak = self.track_peak
+            .map_or(0, |peak| (peak.max(0.0) as f64 * ITUNNORM_FULL_SCALE).round() as u32);
+
+        // The second pair is the same loudness in 1/2500ths of a milliwatt. The remaining
+        // values are not understood, and are left as zero.
+        let values = [loudness(1000.0), loudness(1000.0), loudness(2500.0), loudness(2500.0), 0,
+                      0, peak, peak, 0, 0];
+
+        Some(values.iter().map(|value| format!(" {:08X}", value)).collect())
+    }
+}
+
+/// Parses a gain such as `-6.20 dB`, with or without the unit.
+fn parse_gain(text: &str) -> Option<f32> {
+    let text = text.trim();
+    let start = text.len().saturating_sub(2);
+    let text = match text.get(start..) {
+        Some(unit) if unit.eq_ignore_ascii_case("db") => &text[..start],
+        _ => text,
+    };
+
+    text.trim().parse().ok()
+}
+
+/// Formats a gain in the form `-6.20 dB`.
+fn format_gain(gain: f32) -> String {
+    format!("{:+.2} dB", gain)
+}
+
+#[cfg(test)]
+mod replay_gain_tests {
+    use frame::user_text::UserText;
+    use super::ReplayGain;
+
+    fn user_text(description: &str, value: &str) -> UserText {
+        UserText::for_version(description.to_string(), value.to_string(), 4)
+    }
+
+    #[test]
+    fn it_reads_user_text_in_any_case() {
+        let gain = ReplayGain::from_user_text(&[user_text("replaygain_track_gain", "-6.20 dB"),
+                                                user_text("REPLAYGAIN_ALBUM_PEAK", " 0.988553"),
+                                                user_text("Replaygain_Album_Gain", "+1.5db"),
+                                                user_text("REPLAYGAIN_TRACK_PEAK", "loud")]);
+        let garbled = ReplayGain::from_user_text(&[user_text("REPLAYGAIN_TRACK_GAIN", "1\u{20ac}"),
+                                                   user_text("REPLAYGAIN_ALBUM_GAIN", "\u{20ac}")]);
+
+        assert_eq!(gain,
+                   ReplayGain {
+                       track_gain: Some(-6.2),
+                       track_peak: None,
+                       album_gain: Some(1.5),
+                       album_peak: Some(0.988553),
+                   });
+        assert_eq!(garbled, ReplayGain::default());
+    }
+
+    #[test]
+    fn it_writes_user_text() {
+        let gain = ReplayGain {
+            track_gain: Some(-6.2),
+            album_peak: Some(1.0),
+            ..Default::default()
+        };
+        let texts = gain.to_user_text(3);
+
+        assert_eq!(texts.len(), 2);
+        assert_eq!(texts[0].to_bytes(), b"\0REPLAYGAIN_TRACK_GAIN\0-6.20 dB");
+        assert_eq!(texts[1].to_bytes(), b"\0REPLAYGAIN_ALBUM_PEAK\x001.000000");
+        assert_eq!(ReplayGain::from_user_text(&texts), gain);
+    }
+
+    #[test]
+    fn it_reads_and_writes_rgad() {
+        // Peak 0.5, radio -6.2 dB set automatically, audiophile +1.5 dB set by the user
+        let bytes = [0x3F, 0x00, 0x00, 0x00, 0x2E, 0x3E, 0x48, 0x0F];
+        let gain = ReplayGain::from_rgad(&bytes).unwrap();
+
+        assert_eq!(gain,
+                   ReplayGain {
+                       track_gain: Some(-6.2),
+                       track_peak: Some(0.5),
+                       album_gain: Some(1.5),
+                       album_peak: None,
+                   });
+        assert_eq!(ReplayGain::from_rgad(&gain.to_rgad()).unwrap(), gain);
+        assert!(ReplayGain::from_rgad(&bytes[..7]).is_err());
+    }
+
+    #[test]
+    fn it_reads_and_writes_itunnorm() {
+        let text = " 00000291 0000029A 000006A4 00000734 00018B6F 00018B6F 00007FFF 00004000 \
+                    00024CA8 00024CA8";
+        let gain = ReplayGain::from_itunnorm(text).unwrap();
+
+        // 0x29A thousandths of a milliwatt is 0.666 mW
+        assert!((gain.track_gain.unwrap() - 1.765).abs() < 0.001);
+        assert!((gain.track_peak.unwrap() - 1.0).abs() < 0.001);
+
+        let round_trip = ReplayGain::from_itunnorm(&gain.to_itunnorm().unwrap()).unwrap();
+        assert!((round_trip.track_gain.unwrap() - gain.track_gain.unwrap()).abs() < 0.01);
+        assert_eq!(ReplayGain::from_itunnorm(" 0000029A nope"), None);
+    }
+
+    #[test]
+    fn it_fills_in_missing_values() {
+        let first = ReplayGain { track_gain: Some(-1.0), ..Default::default() };
+        let second = ReplayGain {
+            track_gain: Some(-2.0),
+            track_peak: Some(0.5),
+            ..Default::default()
+        };
+
+        assert_eq!(first.or(second).track_gain, Some(-1.0));
+        assert_eq!(first.or(second).track_peak, Some(0.5));
+        assert!(ReplayGain::default().is_empty());
+    }
+}
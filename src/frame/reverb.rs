@@ -0,0 +1,111 @@
+//! Types and functions related to the reverb frame (`RVRB`).
+
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of a reverb frame, which has the same layout in `v2.3.0` and
+/// `v2.4.0`.
+///
+/// The feedback and premix fields are a fraction of 255, so `255` means 100%.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.14)](http://id3.org/id3v2.3.0#Reverb)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Reverb {
+    /// The delay between bounces on the left channel, in milliseconds.
+    pub left: u16,
+    /// The delay between bounces on the right channel, in milliseconds.
+    pub right: u16,
+    /// How many times the left channel bounces. `255` means infinitely.
+    pub bounces_left: u8,
+    /// How many times the right channel bounces. `255` means infinitely.
+    pub bounces_right: u8,
+    /// How much of the left channel is fed back into the left channel.
+    pub feedback_left_to_left: u8,
+    /// How much of the left channel is fed back into the right channel.
+    pub feedback_left_to_right: u8,
+    /// How much of the right channel is fed back into the right channel.
+    pub feedback_right_to_right: u8,
+    /// How much of the right channel is fed back into the left channel.
+    pub feedback_right_to_left: u8,
+    /// How much of the left channel is mixed into the right channel before the reverb.
+    pub premix_left_to_right: u8,
+    /// How much of the right channel is mixed into the left channel before the reverb.
+    pub premix_right_to_left: u8,
+}
+
+impl Reverb {
+    /// Construct a new reverb from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is shorter than 12 bytes, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Reverb> {
+        if bytes.len() < 12 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        Ok(Reverb {
+            left: u16::from_be_bytes([bytes[0], bytes[1]]),
+            right: u16::from_be_bytes([bytes[2], bytes[3]]),
+            bounces_left: bytes[4],
+            bounces_right: bytes[5],
+            feedback_left_to_left: bytes[6],
+            feedback_left_to_right: bytes[7],
+            feedback_right_to_right: bytes[8],
+            feedback_right_to_left: bytes[9],
+            premix_left_to_right: bytes[10],
+            premix_right_to_left: bytes[11],
+        })
+    }
+
+    /// Construct a new reverb from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Reverb::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Reverb> {
+        Reverb::from_bytes(frame.body())
+    }
+
+    /// Converts the reverb into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12);
+        bytes.extend_from_slice(&self.left.to_be_bytes());
+        bytes.extend_from_slice(&self.right.to_be_bytes());
+        bytes.extend_from_slice(&[self.bounces_left,
+                                  self.bounces_right,
+                                  self.feedback_left_to_left,
+                                  self.feedback_left_to_right,
+                                  self.feedback_right_to_right,
+                                  self.feedback_right_to_left,
+                                  self.premix_left_to_right,
+                                  self.premix_right_to_left]);
+
+        bytes
+    }
+
+    /// Converts the reverb into an `RVRB` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"RVRB", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod reverb_tests {
+    use super::Reverb;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let bytes = [0x01, 0x2C, 0x00, 0xFA, 3, 255, 128, 0, 64, 32, 16, 8];
+        let reverb = Reverb::from_bytes(&bytes).unwrap();
+
+        assert_eq!((reverb.left, reverb.right), (300, 250));
+        assert_eq!((reverb.bounces_left, reverb.bounces_right), (3, 255));
+        assert_eq!(reverb.feedback_right_to_left, 32);
+        assert_eq!(reverb.premix_right_to_left, 8);
+        assert_eq!(reverb.to_bytes(), &bytes[..]);
+        assert!(Reverb::from_bytes(&bytes[..11]).is_err());
+    }
+}
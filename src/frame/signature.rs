@@ -0,0 +1,83 @@
+//! Types and functions related to the signature frame (`SIGN`), which signs a group of frames.
+
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of a signature frame. _Only `v2.4.0`._
+///
+/// The signature covers every frame with the same group symbol. The standard does not say how
+/// the signed frames are turned into bytes, so `Tag::group_bytes` uses a canonical form of its
+/// own.
+///
+/// # Reference
+///
+/// [ID3v2.4 Native Frames (Section 4.28)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Signature {
+    /// The symbol of the group of frames that is signed.
+    pub group_symbol: u8,
+    /// The signature itself.
+    pub signature: Vec<u8>,
+}
+
+impl Signature {
+    /// Construct a new signature from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty, then this function will return `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Signature> {
+        match bytes.split_first() {
+            Some((&group_symbol, signature)) => {
+                Ok(Signature { group_symbol, signature: signature.to_vec() })
+            }
+            None => Err(Error::UnexpectedEnd),
+        }
+    }
+
+    /// Construct a new signature from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Signature::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Signature> {
+        Signature::from_bytes(frame.body())
+    }
+
+    /// Converts the signature into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.group_symbol];
+        bytes.extend_from_slice(&self.signature);
+
+        bytes
+    }
+
+    /// Converts the signature into a `SIGN` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"SIGN", self.to_bytes())
+    }
+}
+
+/// A trait for checking the signatures in signature frames.
+///
+/// An implementation decides which signature scheme is used, for example by checking the
+/// signature against a public key. The bytes that were signed are given by `Tag::group_bytes`.
+pub trait SignatureVerifier {
+    /// Checks whether or not `signature` is valid for `signed`, the canonical bytes of the
+    /// group of frames it signs.
+    fn verify(&mut self, signed: &[u8], signature: &Signature) -> bool;
+}
+
+#[cfg(test)]
+mod signature_tests {
+    use super::Signature;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let signature = Signature::from_bytes(b"\x80\xde\xad\xbe\xef").unwrap();
+
+        assert_eq!(signature.group_symbol, 0x80);
+        assert_eq!(signature.signature, b"\xde\xad\xbe\xef");
+        assert_eq!(signature.to_bytes(), b"\x80\xde\xad\xbe\xef");
+        assert!(Signature::from_bytes(b"").is_err());
+    }
+}
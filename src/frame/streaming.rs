@@ -0,0 +1,270 @@
+//! Types and functions related to the frames that help with streaming: recommended buffer size
+//! (`RBUF`), seek (`SEEK`) and audio seek point index (`ASPI`).
+
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of a recommended buffer size frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.19)](http://id3.org/id3v2.3.0#Recommended_buffer_size)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RecommendedBuffer {
+    /// The recommended buffer size in bytes, up to 24 bits.
+    pub size: u32,
+    /// Whether or not ID3 tags may be embedded in the audio stream, rather than only before it.
+    pub embedded_info: bool,
+    /// The number of bytes from the end of this tag to the next one, if it is known.
+    pub next_tag_offset: Option<u32>,
+}
+
+impl RecommendedBuffer {
+    /// Construct a new recommended buffer size from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before the embedded info flag, or part way through the offset to the
+    /// next tag, then this function will return `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<RecommendedBuffer> {
+        if bytes.len() < 4 || (bytes.len() > 4 && bytes.len() < 8) {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        Ok(RecommendedBuffer {
+            size: u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]),
+            embedded_info: bytes[3] & 1 != 0,
+            next_tag_offset: bytes.get(4..8)
+                .map(|offset| u32::from_be_bytes([offset[0], offset[1], offset[2], offset[3]])),
+        })
+    }
+
+    /// Construct a new recommended buffer size from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `RecommendedBuffer::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<RecommendedBuffer> {
+        RecommendedBuffer::from_bytes(frame.body())
+    }
+
+    /// Converts the recommended buffer size into a frame body. Only the lowest 24 bits of the
+    /// size are written.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.size.to_be_bytes()[1..].to_vec();
+        bytes.push(self.embedded_info as u8);
+
+        if let Some(offset) = self.next_tag_offset {
+            bytes.extend_from_slice(&offset.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the recommended buffer size into an `RBUF` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"RBUF", self.to_bytes())
+    }
+}
+
+/// A type representing the body of a seek frame, which says that another tag follows later in
+/// the stream. _Only `v2.4.0`._
+///
+/// # Reference
+///
+/// [ID3v2.4 Native Frames (Section 4.29)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SeekOffset {
+    /// The smallest number of bytes from the end of this tag to the start of the next one.
+    pub offset: u32,
+}
+
+impl SeekOffset {
+    /// Construct a new seek offset from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is shorter than 4 bytes, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SeekOffset> {
+        if bytes.len() < 4 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        Ok(SeekOffset { offset: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) })
+    }
+
+    /// Construct a new seek offset from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `SeekOffset::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<SeekOffset> {
+        SeekOffset::from_bytes(frame.body())
+    }
+
+    /// Converts the seek offset into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.offset.to_be_bytes().to_vec()
+    }
+
+    /// Converts the seek offset into a `SEEK` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"SEEK", self.to_bytes())
+    }
+}
+
+/// A type representing the body of an audio seek point index frame. _Only `v2.4.0`._
+///
+/// The index splits the duration of the audio into equal parts, and gives the byte offset
+/// where each one starts as a fraction of the indexed data.
+///
+/// # Reference
+///
+/// [ID3v2.4 Native Frames (Section 4.30)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AudioSeekPointIndex {
+    /// The byte offset of the indexed data from the start of the file.
+    pub data_start: u32,
+    /// The number of bytes of indexed data.
+    pub data_length: u32,
+    /// The number of bits used for each fraction, which must be 8 or 16.
+    pub bits_per_point: u8,
+    /// The numerator of the fraction of the indexed data where each point starts, over
+    /// `2^bits_per_point`.
+    pub fractions: Vec<u16>,
+}
+
+impl AudioSeekPointIndex {
+    /// Construct a new audio seek point index from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends before all of the index points, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the number of bits per index point is not 8 or 16, then this function will return
+    /// `Error::InvalidBitWidth`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<AudioSeekPointIndex> {
+        if bytes.len() < 11 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let points = u16::from_be_bytes([bytes[8], bytes[9]]) as usize;
+        let bits_per_point = bytes[10];
+        let width = match bits_per_point {
+            8 => 1,
+            16 => 2,
+            _ => return Err(Error::InvalidBitWidth(bits_per_point)),
+        };
+
+        let data = bytes.get(11..11 + points * width).ok_or(Error::UnexpectedEnd)?;
+        let fractions = data.chunks(width)
+            .map(|fraction| match *fraction {
+                [high, low] => u16::from_be_bytes([high, low]),
+                _ => fraction[0] as u16,
+            })
+            .collect();
+
+        Ok(AudioSeekPointIndex {
+            data_start: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            data_length: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            bits_per_point,
+            fractions,
+        })
+    }
+
+    /// Construct a new audio seek point index from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `AudioSeekPointIndex::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<AudioSeekPointIndex> {
+        AudioSeekPointIndex::from_bytes(frame.body())
+    }
+
+    /// Gets an iterator over the byte offset of each index point from the start of the file.
+    pub fn offsets<'a>(&'a self) -> impl Iterator<Item = u64> + 'a {
+        let scale = 1u64 << self.bits_per_point.min(16);
+
+        self.fractions.iter().map(move |&fraction| {
+            self.data_start as u64 + fraction as u64 * self.data_length as u64 / scale
+        })
+    }
+
+    /// Gets the byte offset from the start of the file to start playing from, to reach
+    /// `position` (from `0.0` at the start to `1.0` at the end) of the audio's duration.
+    ///
+    /// Returns `None` if there are no index points.
+    pub fn offset_at(&self, position: f64) -> Option<u64> {
+        let points = self.fractions.len();
+        let index = (position.max(0.0) * points as f64) as usize;
+
+        self.offsets().nth(index.min(points.checked_sub(1)?))
+    }
+
+    /// Converts the audio seek point index into a frame body.
+    ///
+    /// Fractions are written with 16 bits unless `bits_per_point` is 8.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.data_start.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.data_length.to_be_bytes());
+        bytes.extend_from_slice(&(self.fractions.len() as u16).to_be_bytes());
+
+        if self.bits_per_point == 8 {
+            bytes.push(8);
+            bytes.extend(self.fractions.iter().map(|&fraction| fraction as u8));
+        } else {
+            bytes.push(16);
+            for fraction in &self.fractions {
+                bytes.extend_from_slice(&fraction.to_be_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Converts the audio seek point index into an `ASPI` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"ASPI", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod streaming_tests {
+    use super::{AudioSeekPointIndex, RecommendedBuffer, SeekOffset};
+
+    #[test]
+    fn it_reads_recommended_buffers() {
+        let buffer = RecommendedBuffer::from_bytes(b"\x00\x10\x00\x01\x00\x00\x20\x00").unwrap();
+
+        assert_eq!(buffer.size, 4096);
+        assert!(buffer.embedded_info);
+        assert_eq!(buffer.next_tag_offset, Some(8192));
+        assert_eq!(buffer.to_bytes(), b"\x00\x10\x00\x01\x00\x00\x20\x00");
+
+        let buffer = RecommendedBuffer::from_bytes(b"\x00\x10\x00\x00").unwrap();
+
+        assert_eq!(buffer.next_tag_offset, None);
+        assert!(RecommendedBuffer::from_bytes(b"\x00\x10\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn it_reads_seek_offsets() {
+        assert_eq!(SeekOffset::from_bytes(b"\x00\x00\x10\x00").unwrap().offset, 4096);
+        assert!(SeekOffset::from_bytes(b"\x00\x00\x10").is_err());
+    }
+
+    #[test]
+    fn it_reads_audio_seek_point_indexes() {
+        let bytes = b"\x00\x00\x01\x00\x00\x00\x10\x00\x00\x04\x08\x00\x40\x80\xc0";
+        let index = AudioSeekPointIndex::from_bytes(bytes).unwrap();
+
+        assert_eq!(index.offsets().collect::<Vec<_>>(), vec![256, 1280, 2304, 3328]);
+        assert_eq!(index.offset_at(0.6), Some(2304));
+        assert_eq!(index.offset_at(1.0), Some(3328));
+        assert_eq!(index.to_bytes(), &bytes[..]);
+
+        assert!(AudioSeekPointIndex::from_bytes(&bytes[..14]).is_err());
+        assert!(AudioSeekPointIndex::from_bytes(b"\0\0\0\0\0\0\0\0\0\0\x0c").is_err());
+    }
+}
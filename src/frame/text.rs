@@ -0,0 +1,154 @@
+//! Types and functions related to the text information frames (`T000` - `TZZZ`, excluding
+//! `TXXX`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// The frames that `v2.3.0` allows to hold several values, separated with `/`.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.2.1)](http://id3.org/id3v2.3.0#Text_information_frames_-_details)
+pub const V23_MULTI_VALUE_FRAMES: [&[u8; 4]; 5] = [b"TCOM", b"TEXT", b"TOLY", b"TOPE", b"TPE1"];
+
+/// A type representing the body of a text information frame.
+///
+/// A `v2.4.0` frame can hold several values, separated by the encoding's terminator. A `v2.3.0`
+/// frame only holds one, so several values are joined with a separator instead (see
+/// `Text::join`).
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.2)](http://id3.org/id3v2.3.0#Text_information_frames)
+///
+/// [ID3v2.4 Native Frames (Section 4.2)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Text {
+    encoding: Encoding,
+    values: Vec<String>,
+}
+
+impl Text {
+    /// Construct a new text body with a single value.
+    pub fn new(encoding: Encoding, text: String) -> Text {
+        Text::with_values(encoding, vec![text])
+    }
+
+    /// Construct a new text body with several values.
+    pub fn with_values(encoding: Encoding, values: Vec<String>) -> Text {
+        Text { encoding, values }
+    }
+
+    /// Construct a new text body, using the simplest encoding that can represent every value in
+    /// a tag of the given major version.
+    pub fn for_version(values: Vec<String>, version: u8) -> Text {
+        Text::with_values(Encoding::for_text(&values.concat(), version), values)
+    }
+
+    /// Construct a new text body from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty, then this function will return `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Text> {
+        let (&encoding_byte, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let encoding = Encoding::from_byte(encoding_byte)?;
+
+        Ok(Text::with_values(encoding, encoding.decode_all(rest)))
+    }
+
+    /// Construct a new text body from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `Text::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<Text> {
+        Text::from_bytes(frame.body())
+    }
+
+    /// Gets the encoding used for the text.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Gets the first value, or an empty string if there are none.
+    pub fn text(&self) -> &str {
+        self.values.first().map_or("", |value| value.as_str())
+    }
+
+    /// Gets every value. Use `to_vec` for an owned `Vec<String>`, or `Tag::text_values`, which
+    /// also splits `v2.3.0` values on the tag's separator.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// Splits every value on `separator`, trimming whitespace around each new value.
+    ///
+    /// This recovers the values of a `v2.3.0` frame that was written with `Text::join`.
+    pub fn split(&self, separator: &str) -> Text {
+        let values = self.values
+            .iter()
+            .flat_map(|value| value.split(separator).map(|value| value.trim().to_string()))
+            .filter(|value| !value.is_empty())
+            .collect();
+
+        Text::with_values(self.encoding, values)
+    }
+
+    /// Joins every value into one, separated by `separator`, as `v2.3.0` requires.
+    pub fn join(&self, separator: &str) -> Text {
+        Text::new(self.encoding, self.values.join(separator))
+    }
+
+    /// Converts the text into a frame body, separating the values with the encoding's
+    /// terminator.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend(self.encoding.encode_all(&self.values));
+
+        bytes
+    }
+
+    /// Converts the text into a frame with the given ID.
+    pub fn to_frame(&self, frame_id: [u8; 4]) -> Frame {
+        Frame::new(frame_id, self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod text_tests {
+    use frame::encoding::Encoding;
+    use super::Text;
+
+    #[test]
+    fn it_reads_every_value() {
+        let text = Text::from_bytes(b"\x03Simon\0Garfunkel").unwrap();
+
+        assert_eq!(text.values(), &["Simon", "Garfunkel"]);
+        assert_eq!(text.text(), "Simon");
+
+        let text = Text::from_bytes(&[1, 0xFF, 0xFE, b'A', 0, 0, 0, 0xFF, 0xFE, b'B', 0]).unwrap();
+
+        assert_eq!(text.values(), &["A", "B"]);
+    }
+
+    #[test]
+    fn it_splits_and_joins() {
+        let text = Text::from_bytes(b"\x00Simon / Garfunkel").unwrap().split("/");
+
+        assert_eq!(text.values(), &["Simon", "Garfunkel"]);
+        assert_eq!(text.join(";").to_bytes(), b"\x00Simon;Garfunkel");
+        assert_eq!(text.to_bytes(), b"\x00Simon\0Garfunkel");
+    }
+
+    #[test]
+    fn it_handles_no_values() {
+        let text = Text::from_bytes(&[Encoding::Utf8.to_byte()]).unwrap();
+
+        assert!(text.values().is_empty());
+        assert_eq!(text.text(), "");
+    }
+}
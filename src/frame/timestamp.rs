@@ -0,0 +1,255 @@
+//! Types and functions related to the date and time frames.
+//!
+//! In `v2.3.0` a date is spread over `TYER` (yyyy), `TDAT` (DDMM) and `TIME` (HHMM), with `TORY`
+//! holding the original release year and `TRDA` free text recording dates. In `v2.4.0` each date
+//! is a single timestamp frame: `TDRC`, `TDOR`, `TDRL`, `TDEN` and `TDTG`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use frame::Error;
+
+/// The separator before a field of a timestamp, and a check that its value is in range.
+type Field = (u8, fn(u8) -> bool);
+
+/// A type representing an ID3v2.4 timestamp, which may only be precise to part of the time.
+///
+/// Every field after the year is only present if the fields before it are.
+///
+/// # Examples
+///
+/// ```
+/// # use id3::frame::timestamp::Timestamp;
+/// let timestamp: Timestamp = "2016-10-15T09:30".parse().unwrap();
+///
+/// assert_eq!(timestamp.year, 2016);
+/// assert_eq!(timestamp.minute, Some(30));
+/// assert_eq!(timestamp.second, None);
+/// assert_eq!(timestamp.to_string(), "2016-10-15T09:30");
+/// ```
+///
+/// # Reference
+///
+/// [ID3v2.4 Informal Standard (Section 4)](http://id3.org/id3v2.4.0-structure)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// The year, from 0 to 9999.
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: Option<u8>,
+    /// The day of the month, from 1 to 31.
+    pub day: Option<u8>,
+    /// The hour, from 0 to 23.
+    pub hour: Option<u8>,
+    /// The minute, from 0 to 59.
+    pub minute: Option<u8>,
+    /// The second, from 0 to 59.
+    pub second: Option<u8>,
+}
+
+impl Timestamp {
+    /// Construct a new timestamp that is only precise to the year.
+    pub fn new(year: u16) -> Timestamp {
+        Timestamp { year, ..Default::default() }
+    }
+
+    /// Construct a new timestamp from the text of the `v2.3.0` `TYER`, `TDAT` and `TIME` frames.
+    ///
+    /// A date or time that cannot be parsed is ignored, as is a time without a date.
+    ///
+    /// # Errors
+    ///
+    /// If the year is not 4 digits, then this function will return `Error::InvalidTimestamp`.
+    pub fn from_v23(year: &str,
+                    date: Option<&str>,
+                    time: Option<&str>)
+                    -> Result<Timestamp, Error> {
+        let year = parse_digits(year.trim().as_bytes(), 4).ok_or(Error::InvalidTimestamp)?;
+        let mut timestamp = Timestamp::new(year);
+
+        if let Some((day, month)) = date.and_then(|date| parse_pair(date.trim())) {
+            if valid_month(month) && valid_day(day) {
+                timestamp.month = Some(month);
+                timestamp.day = Some(day);
+
+                if let Some((hour, minute)) = time.and_then(|time| parse_pair(time.trim())) {
+                    if hour < 24 && minute < 60 {
+                        timestamp.hour = Some(hour);
+                        timestamp.minute = Some(minute);
+                    }
+                }
+            }
+        }
+
+        Ok(timestamp)
+    }
+
+    /// Converts the timestamp into the text of the `v2.3.0` `TYER`, `TDAT` and `TIME` frames.
+    ///
+    /// `TDAT` is only present if the day is known, and `TIME` if the minute is known. Seconds
+    /// cannot be represented and are dropped.
+    pub fn to_v23(&self) -> (String, Option<String>, Option<String>) {
+        let date = match (self.month, self.day) {
+            (Some(month), Some(day)) => Some(format!("{:02}{:02}", day, month)),
+            _ => None,
+        };
+        let time = match (date.is_some(), self.hour, self.minute) {
+            (true, Some(hour), Some(minute)) => Some(format!("{:02}{:02}", hour, minute)),
+            _ => None,
+        };
+
+        (format!("{:04}", self.year), date, time)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    /// Parses a timestamp in the form `yyyy[-MM[-dd[THH[:mm[:ss]]]]]`.
+    fn from_str(text: &str) -> Result<Timestamp, Error> {
+        let text = text.trim_end_matches('\0').trim();
+
+        // Each field after the year is 2 digits
+        let components: [Field; 5] = [
+            (b'-', valid_month),
+            (b'-', valid_day),
+            (b'T', |hour| hour < 24),
+            (b':', |minute| minute < 60),
+            (b':', |second| second < 60),
+        ];
+
+        let bytes = text.as_bytes();
+        let year = parse_digits(bytes.get(..4).unwrap_or(&[]), 4).ok_or(Error::InvalidTimestamp)?;
+
+        let mut timestamp = Timestamp::new(year);
+        let mut fields = [None; 5];
+        let mut index = 4;
+
+        for (field, &(separator, is_valid)) in fields.iter_mut().zip(components.iter()) {
+            if index == bytes.len() {
+                break;
+            }
+            if bytes[index] != separator || index + 3 > bytes.len() {
+                return Err(Error::InvalidTimestamp);
+            }
+
+            let value = parse_digits(&bytes[index + 1..index + 3], 2)
+                .map(|value| value as u8)
+                .filter(|&value| is_valid(value))
+                .ok_or(Error::InvalidTimestamp)?;
+
+            *field = Some(value);
+            index += 3;
+        }
+
+        if index != bytes.len() {
+            return Err(Error::InvalidTimestamp);
+        }
+
+        timestamp.month = fields[0];
+        timestamp.day = fields[1];
+        timestamp.hour = fields[2];
+        timestamp.minute = fields[3];
+        timestamp.second = fields[4];
+
+        Ok(timestamp)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+
+        let fields = [("-", self.month), ("-", self.day), ("T", self.hour), (":", self.minute),
+                      (":", self.second)];
+
+        for &(separator, field) in fields.iter() {
+            match field {
+                Some(value) => write!(f, "{}{:02}", separator, value)?,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses exactly `width` ASCII digits.
+fn parse_digits(bytes: &[u8], width: usize) -> Option<u16> {
+    if bytes.len() == width && bytes.iter().all(|byte| byte.is_ascii_digit()) {
+        Some(bytes.iter().fold(0, |value, &byte| value * 10 + (byte - b'0') as u16))
+    } else {
+        None
+    }
+}
+
+/// Parses the two 2 digit numbers in the `DDMM` or `HHMM` form used by `TDAT` and `TIME`.
+fn parse_pair(text: &str) -> Option<(u8, u8)> {
+    let bytes = text.as_bytes();
+    if bytes.len() != 4 {
+        return None;
+    }
+
+    let first = parse_digits(&bytes[..2], 2)?;
+    let second = parse_digits(&bytes[2..], 2)?;
+
+    Some((first as u8, second as u8))
+}
+
+fn valid_month(month: u8) -> bool {
+    (1..=12).contains(&month)
+}
+
+fn valid_day(day: u8) -> bool {
+    (1..=31).contains(&day)
+}
+
+#[cfg(test)]
+mod timestamp_tests {
+    use super::Timestamp;
+
+    #[test]
+    fn it_parses_every_precision() {
+        let texts = ["2016", "2016-10", "2016-10-15", "2016-10-15T09", "2016-10-15T09:30",
+                     "2016-10-15T09:30:59"];
+
+        for text in texts.iter() {
+            let timestamp: Timestamp = text.parse().unwrap();
+            assert_eq!(&timestamp.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn it_rejects_malformed_timestamps() {
+        let texts = ["16", "2016-1", "2016-13", "2016/10", "2016-10-15 09:30", "2016-10-15T24",
+                     "2016-10-15T09:30:59Z", "20l6"];
+
+        for text in texts.iter() {
+            assert!(text.parse::<Timestamp>().is_err(), "{} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn it_joins_v23_frames() {
+        let timestamp = Timestamp::from_v23("1999", Some("3112"), Some("2359")).unwrap();
+
+        assert_eq!(timestamp.to_string(), "1999-12-31T23:59");
+    }
+
+    #[test]
+    fn it_ignores_bad_v23_dates() {
+        let timestamp = Timestamp::from_v23("1999", Some("3113"), Some("2359")).unwrap();
+
+        assert_eq!(timestamp, Timestamp::new(1999));
+        assert!(Timestamp::from_v23("99", None, None).is_err());
+    }
+
+    #[test]
+    fn it_splits_into_v23_frames() {
+        let timestamp: Timestamp = "1999-12-31T23:59:30".parse().unwrap();
+        let expected = ("1999".to_string(), Some("3112".to_string()), Some("2359".to_string()));
+
+        assert_eq!(timestamp.to_v23(), expected);
+        assert_eq!(Timestamp::new(1999).to_v23(), ("1999".to_string(), None, None));
+    }
+}
@@ -0,0 +1,533 @@
+//! Types and functions related to the event timing codes (`ETCO`), synchronised tempo codes
+//! (`SYTC`) and position synchronisation (`POSS`) frames.
+
+use std::time::Duration;
+
+use frame::volume::read_field;
+use frame::{Error, Frame, Result};
+
+/// A type representing the unit of the times in a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimestampFormat {
+    /// The number of MPEG frames since the start of the audio.
+    MpegFrames,
+    /// The number of milliseconds since the start of the audio.
+    #[default]
+    Milliseconds,
+    /// A format that the standard does not define.
+    Unknown(u8),
+}
+
+impl TimestampFormat {
+    /// Gets the format described by a time stamp format byte.
+    pub fn from_byte(byte: u8) -> TimestampFormat {
+        match byte {
+            1 => TimestampFormat::MpegFrames,
+            2 => TimestampFormat::Milliseconds,
+            _ => TimestampFormat::Unknown(byte),
+        }
+    }
+
+    /// Gets the time stamp format byte for the format.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            TimestampFormat::MpegFrames => 1,
+            TimestampFormat::Milliseconds => 2,
+            TimestampFormat::Unknown(byte) => byte,
+        }
+    }
+
+    /// Converts a time in this format into the time since the start of the audio, given how
+    /// long each MPEG frame lasts.
+    ///
+    /// Returns `None` if the format is unknown.
+    pub fn to_duration(&self, time: u32, mpeg_frame: Duration) -> Option<Duration> {
+        match *self {
+            TimestampFormat::MpegFrames => Some(mpeg_frame * time),
+            TimestampFormat::Milliseconds => Some(Duration::from_millis(time as u64)),
+            TimestampFormat::Unknown(_) => None,
+        }
+    }
+}
+
+/// A type representing the type of an event in an `ETCO` frame.
+///
+/// # Reference
+///
+/// [ID3v2.4 Native Frames (Section 4.5)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventType {
+    /// Padding, which has no meaning.
+    Padding,
+    /// The end of the silence at the start of the audio.
+    EndOfInitialSilence,
+    /// The start of the intro.
+    IntroStart,
+    /// The start of the main part.
+    MainPartStart,
+    /// The start of the outro.
+    OutroStart,
+    /// The end of the outro.
+    OutroEnd,
+    /// The start of a verse.
+    VerseStart,
+    /// The start of a refrain (or chorus).
+    RefrainStart,
+    /// The start of an interlude.
+    InterludeStart,
+    /// The start of a theme.
+    ThemeStart,
+    /// The start of a variation.
+    VariationStart,
+    /// A change of key.
+    KeyChange,
+    /// A change of time signature.
+    TimeChange,
+    /// A momentary unwanted noise, such as a snap, crackle or pop.
+    MomentaryUnwantedNoise,
+    /// The start of a sustained noise.
+    SustainedNoise,
+    /// The end of a sustained noise.
+    SustainedNoiseEnd,
+    /// The end of the intro.
+    IntroEnd,
+    /// The end of the main part.
+    MainPartEnd,
+    /// The end of a verse.
+    VerseEnd,
+    /// The end of a refrain (or chorus).
+    RefrainEnd,
+    /// The end of a theme.
+    ThemeEnd,
+    /// The start of profanity. _Only `v2.4.0`._
+    Profanity,
+    /// The end of profanity. _Only `v2.4.0`._
+    ProfanityEnd,
+    /// One of the sixteen synchronisation events that the standard leaves to the user
+    /// (`$E0` - `$EF`), numbered from 0.
+    Sync(u8),
+    /// The end of the audio, where silence starts.
+    AudioEnd,
+    /// The end of the audio file.
+    AudioFileEnd,
+    /// An event type that is reserved or that the standard does not define.
+    ///
+    /// `$FF` is never read as one, since it says that the event type continues in the next byte
+    /// (see `EventTimingCodes::from_bytes`).
+    Unknown(u8),
+}
+
+impl EventType {
+    /// Gets the event type described by an event type byte.
+    pub fn from_byte(byte: u8) -> EventType {
+        match byte {
+            0x00 => EventType::Padding,
+            0x01 => EventType::EndOfInitialSilence,
+            0x02 => EventType::IntroStart,
+            0x03 => EventType::MainPartStart,
+            0x04 => EventType::OutroStart,
+            0x05 => EventType::OutroEnd,
+            0x06 => EventType::VerseStart,
+            0x07 => EventType::RefrainStart,
+            0x08 => EventType::InterludeStart,
+            0x09 => EventType::ThemeStart,
+            0x0A => EventType::VariationStart,
+            0x0B => EventType::KeyChange,
+            0x0C => EventType::TimeChange,
+            0x0D => EventType::MomentaryUnwantedNoise,
+            0x0E => EventType::SustainedNoise,
+            0x0F => EventType::SustainedNoiseEnd,
+            0x10 => EventType::IntroEnd,
+            0x11 => EventType::MainPartEnd,
+            0x12 => EventType::VerseEnd,
+            0x13 => EventType::RefrainEnd,
+            0x14 => EventType::ThemeEnd,
+            0x15 => EventType::Profanity,
+            0x16 => EventType::ProfanityEnd,
+            0xE0..=0xEF => EventType::Sync(byte - 0xE0),
+            0xFD => EventType::AudioEnd,
+            0xFE => EventType::AudioFileEnd,
+            _ => EventType::Unknown(byte),
+        }
+    }
+
+    /// Gets the event type byte for the event type.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            EventType::Padding => 0x00,
+            EventType::EndOfInitialSilence => 0x01,
+            EventType::IntroStart => 0x02,
+            EventType::MainPartStart => 0x03,
+            EventType::OutroStart => 0x04,
+            EventType::OutroEnd => 0x05,
+            EventType::VerseStart => 0x06,
+            EventType::RefrainStart => 0x07,
+            EventType::InterludeStart => 0x08,
+            EventType::ThemeStart => 0x09,
+            EventType::VariationStart => 0x0A,
+            EventType::KeyChange => 0x0B,
+            EventType::TimeChange => 0x0C,
+            EventType::MomentaryUnwantedNoise => 0x0D,
+            EventType::SustainedNoise => 0x0E,
+            EventType::SustainedNoiseEnd => 0x0F,
+            EventType::IntroEnd => 0x10,
+            EventType::MainPartEnd => 0x11,
+            EventType::VerseEnd => 0x12,
+            EventType::RefrainEnd => 0x13,
+            EventType::ThemeEnd => 0x14,
+            EventType::Profanity => 0x15,
+            EventType::ProfanityEnd => 0x16,
+            EventType::Sync(number) => 0xE0 | (number & 0x0F),
+            EventType::AudioEnd => 0xFD,
+            EventType::AudioFileEnd => 0xFE,
+            EventType::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// A type representing a single event in an `ETCO` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Event {
+    /// The type of the event.
+    pub event_type: EventType,
+    /// The time of the event since the start of the audio, in the frame's timestamp format.
+    pub time: u32,
+}
+
+/// A type representing the body of an event timing codes frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.6)](http://id3.org/id3v2.3.0#Event_timing_codes)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventTimingCodes {
+    /// The unit of the times.
+    pub format: TimestampFormat,
+    /// The events, which should be in chronological order.
+    pub events: Vec<Event>,
+}
+
+impl EventTimingCodes {
+    /// Construct new event timing codes from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty or ends part way through an event, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If an event has the type `$FF`, which says that the type continues in the next byte, then
+    /// this function will return `Error::UnsupportedEventType`, since the standard defines no
+    /// such types and the events that follow cannot be told apart.
+    pub fn from_bytes(bytes: &[u8]) -> Result<EventTimingCodes> {
+        let (&format, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+
+        if rest.chunks(5).any(|event| event[0] == 0xFF) {
+            return Err(Error::UnsupportedEventType);
+        }
+        if rest.len() % 5 != 0 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let events = rest.chunks(5)
+            .map(|event| {
+                Event {
+                    event_type: EventType::from_byte(event[0]),
+                    time: u32::from_be_bytes([event[1], event[2], event[3], event[4]]),
+                }
+            })
+            .collect();
+
+        Ok(EventTimingCodes { format: TimestampFormat::from_byte(format), events })
+    }
+
+    /// Construct new event timing codes from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `EventTimingCodes::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<EventTimingCodes> {
+        EventTimingCodes::from_bytes(frame.body())
+    }
+
+    /// Gets an iterator over each event type and its time since the start of the audio, given
+    /// how long each MPEG frame lasts.
+    ///
+    /// Nothing is returned if the timestamp format is unknown.
+    pub fn times<'a>(&'a self, mpeg_frame: Duration)
+                     -> impl Iterator<Item = (EventType, Duration)> + 'a {
+        let format = self.format;
+
+        self.events.iter().filter_map(move |event| {
+            format.to_duration(event.time, mpeg_frame).map(|time| (event.event_type, time))
+        })
+    }
+
+    /// Converts the event timing codes into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.format.to_byte()];
+
+        for event in &self.events {
+            bytes.push(event.event_type.to_byte());
+            bytes.extend_from_slice(&event.time.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the event timing codes into an `ETCO` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"ETCO", self.to_bytes())
+    }
+}
+
+/// A type representing a tempo in an `SYTC` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tempo {
+    /// There is no beat.
+    BeatFree,
+    /// A single beat, followed by no beat.
+    SingleBeat,
+    /// A tempo in beats per minute, from 2 to 510.
+    Bpm(u16),
+}
+
+/// A type representing a change of tempo in an `SYTC` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TempoChange {
+    /// The new tempo.
+    pub tempo: Tempo,
+    /// The time of the change since the start of the audio, in the frame's timestamp format.
+    pub time: u32,
+}
+
+/// A type representing the body of a synchronised tempo codes frame.
+///
+/// Each tempo is a single byte, except that `$FF` is followed by a second byte that is added to
+/// it, so tempos up to 510 BPM can be stored.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.8)](http://id3.org/id3v2.3.0#Synchronised_tempo_codes)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SynchronisedTempo {
+    /// The unit of the times.
+    pub format: TimestampFormat,
+    /// The changes of tempo, which should be in chronological order.
+    pub changes: Vec<TempoChange>,
+}
+
+impl SynchronisedTempo {
+    /// Construct new synchronised tempo codes from the body of a frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty or ends part way through a change of tempo, then this function will
+    /// return `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SynchronisedTempo> {
+        let (&format, mut rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let mut changes = Vec::new();
+
+        while let Some((&code, after)) = rest.split_first() {
+            let (bpm, after) = match (code, after.split_first()) {
+                (0xFF, Some((&extra, after))) => (0xFF + extra as u16, after),
+                (0xFF, None) => return Err(Error::UnexpectedEnd),
+                _ => (code as u16, after),
+            };
+
+            if after.len() < 4 {
+                return Err(Error::UnexpectedEnd);
+            }
+
+            let tempo = match bpm {
+                0 => Tempo::BeatFree,
+                1 => Tempo::SingleBeat,
+                _ => Tempo::Bpm(bpm),
+            };
+            let time = u32::from_be_bytes([after[0], after[1], after[2], after[3]]);
+
+            changes.push(TempoChange { tempo, time });
+            rest = &after[4..];
+        }
+
+        Ok(SynchronisedTempo { format: TimestampFormat::from_byte(format), changes })
+    }
+
+    /// Construct new synchronised tempo codes from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `SynchronisedTempo::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<SynchronisedTempo> {
+        SynchronisedTempo::from_bytes(frame.body())
+    }
+
+    /// Gets an iterator over each tempo and the time since the start of the audio that it takes
+    /// effect, given how long each MPEG frame lasts.
+    ///
+    /// Nothing is returned if the timestamp format is unknown.
+    pub fn times<'a>(&'a self, mpeg_frame: Duration)
+                     -> impl Iterator<Item = (Tempo, Duration)> + 'a {
+        let format = self.format;
+
+        self.changes.iter().filter_map(move |change| {
+            format.to_duration(change.time, mpeg_frame).map(|time| (change.tempo, time))
+        })
+    }
+
+    /// Converts the synchronised tempo codes into a frame body.
+    ///
+    /// Tempos above 510 BPM are written as 510 BPM.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.format.to_byte()];
+
+        for change in &self.changes {
+            let bpm = match change.tempo {
+                Tempo::BeatFree => 0,
+                Tempo::SingleBeat => 1,
+                Tempo::Bpm(bpm) => bpm.min(0x1FE),
+            };
+
+            if bpm >= 0xFF {
+                bytes.extend_from_slice(&[0xFF, (bpm - 0xFF) as u8]);
+            } else {
+                bytes.push(bpm as u8);
+            }
+            bytes.extend_from_slice(&change.time.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the synchronised tempo codes into an `SYTC` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"SYTC", self.to_bytes())
+    }
+}
+
+/// A type representing the body of a position synchronisation frame, which gives the position
+/// in the audio where the file starts, for when it is part of a stream.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.22)](http://id3.org/id3v2.3.0#Position_synchronisation_frame)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PositionSync {
+    /// The unit of the position.
+    pub format: TimestampFormat,
+    /// The position, in the timestamp format.
+    pub position: u32,
+}
+
+impl PositionSync {
+    /// Construct a new position synchronisation from the body of a frame.
+    ///
+    /// The position may be any number of bytes long, but only its lowest 32 bits are kept.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty, then this function will return `Error::UnexpectedEnd`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PositionSync> {
+        let (&format, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let position = read_field(Some(rest), rest.len())?;
+
+        Ok(PositionSync { format: TimestampFormat::from_byte(format), position: position as u32 })
+    }
+
+    /// Construct a new position synchronisation from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `PositionSync::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<PositionSync> {
+        PositionSync::from_bytes(frame.body())
+    }
+
+    /// Gets the position as the time since the start of the audio, given how long each MPEG
+    /// frame lasts.
+    ///
+    /// Returns `None` if the format is unknown.
+    pub fn time(&self, mpeg_frame: Duration) -> Option<Duration> {
+        self.format.to_duration(self.position, mpeg_frame)
+    }
+
+    /// Converts the position synchronisation into a frame body, using 4 bytes for the position.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.format.to_byte()];
+        bytes.extend_from_slice(&self.position.to_be_bytes());
+
+        bytes
+    }
+
+    /// Converts the position synchronisation into a `POSS` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"POSS", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod timing_tests {
+    use std::time::Duration;
+
+    use frame::Error;
+    use super::{EventTimingCodes, EventType, PositionSync, SynchronisedTempo, Tempo,
+                TimestampFormat};
+
+    #[test]
+    fn it_reads_event_timing_codes() {
+        let bytes = b"\x02\x02\x00\x00\x00\x00\x06\x00\x00\x3a\x98\xe3\x00\x00\x75\x30";
+        let codes = EventTimingCodes::from_bytes(bytes).unwrap();
+
+        assert_eq!(codes.format, TimestampFormat::Milliseconds);
+
+        let times: Vec<_> = codes.times(Duration::from_millis(26)).collect();
+        assert_eq!(times,
+                   vec![(EventType::IntroStart, Duration::from_secs(0)),
+                        (EventType::VerseStart, Duration::from_secs(15)),
+                        (EventType::Sync(3), Duration::from_secs(30))]);
+        assert_eq!(codes.to_bytes(), &bytes[..]);
+        assert!(EventTimingCodes::from_bytes(&bytes[..15]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_extended_event_types() {
+        let bytes = b"\x02\x02\x00\x00\x00\x00\xff\x01\x00\x00\x3a\x98";
+
+        match EventTimingCodes::from_bytes(bytes) {
+            Err(Error::UnsupportedEventType) => (),
+            result => panic!("expected an unsupported event type, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn it_resolves_mpeg_frames() {
+        let codes = EventTimingCodes::from_bytes(b"\x01\x07\x00\x00\x01\x00").unwrap();
+        let times: Vec<_> = codes.times(Duration::from_millis(26)).collect();
+
+        assert_eq!(times, vec![(EventType::RefrainStart, Duration::from_millis(26 * 256))]);
+    }
+
+    #[test]
+    fn it_reads_synchronised_tempo() {
+        let bytes = b"\x02\x00\x00\x00\x00\x00\x78\x00\x00\x03\xe8\xff\x2d\x00\x00\x07\xd0";
+        let tempo = SynchronisedTempo::from_bytes(bytes).unwrap();
+
+        let times: Vec<_> = tempo.times(Duration::from_millis(26)).collect();
+        assert_eq!(times,
+                   vec![(Tempo::BeatFree, Duration::from_secs(0)),
+                        (Tempo::Bpm(120), Duration::from_secs(1)),
+                        (Tempo::Bpm(300), Duration::from_secs(2))]);
+        assert_eq!(tempo.to_bytes(), &bytes[..]);
+        assert!(SynchronisedTempo::from_bytes(b"\x02\xff").is_err());
+        assert!(SynchronisedTempo::from_bytes(b"\x02\x78\x00\x00").is_err());
+    }
+
+    #[test]
+    fn it_reads_position_sync() {
+        let position = PositionSync::from_bytes(b"\x01\x01\x00").unwrap();
+
+        assert_eq!(position.format, TimestampFormat::MpegFrames);
+        assert_eq!(position.time(Duration::from_millis(26)), Some(Duration::from_millis(6656)));
+        assert_eq!(position.to_bytes(), b"\x01\x00\x00\x01\x00");
+        assert!(PositionSync::from_bytes(b"").is_err());
+    }
+}
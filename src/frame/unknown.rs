@@ -0,0 +1,273 @@
+//! Types and functions related to frames that this library cannot decode, which are kept
+//! exactly as they were read so that they can be written back unchanged.
+
+use utils;
+
+/// The IDs of the frames defined by the `v2.3.0` and `v2.4.0` standards.
+pub const KNOWN_FRAMES: [&[u8; 4]; 92] = [
+    b"AENC", b"APIC", b"ASPI", b"COMM", b"COMR", b"ENCR", b"EQU2", b"EQUA", b"ETCO", b"GEOB",
+    b"GRID", b"IPLS", b"LINK", b"MCDI", b"MLLT", b"OWNE", b"PCNT", b"POPM", b"POSS", b"PRIV",
+    b"RBUF", b"RVA2", b"RVAD", b"RVRB", b"SEEK", b"SIGN", b"SYLT", b"SYTC", b"TALB", b"TBPM",
+    b"TCOM", b"TCON", b"TCOP", b"TDAT", b"TDEN", b"TDLY", b"TDOR", b"TDRC", b"TDRL", b"TDTG",
+    b"TENC", b"TEXT", b"TFLT", b"TIME", b"TIPL", b"TIT1", b"TIT2", b"TIT3", b"TKEY", b"TLAN",
+    b"TLEN", b"TMCL", b"TMED", b"TMOO", b"TOAL", b"TOFN", b"TOLY", b"TOPE", b"TORY", b"TOWN",
+    b"TPE1", b"TPE2", b"TPE3", b"TPE4", b"TPOS", b"TPRO", b"TPUB", b"TRCK", b"TRDA", b"TRSN",
+    b"TRSO", b"TSIZ", b"TSOA", b"TSOP", b"TSOT", b"TSRC", b"TSSE", b"TSST", b"TXXX", b"TYER",
+    b"UFID", b"USER", b"USLT", b"WCOM", b"WCOP", b"WOAF", b"WOAR", b"WOAS", b"WORS", b"WPAY",
+    b"WPUB", b"WXXX",
+];
+
+/// The frames that the standards say should be discarded when the audio is altered, since they
+/// describe positions or properties of the audio itself.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 3.3.1)](http://id3.org/id3v2.3.0#Frame_header_flags)
+pub const FILE_DEPENDENT_FRAMES: [&[u8; 4]; 15] = [
+    b"AENC", b"ASPI", b"EQU2", b"EQUA", b"ETCO", b"MLLT", b"POSS", b"RVA2", b"RVAD", b"SEEK",
+    b"SYLT", b"SYTC", b"TENC", b"TLEN", b"TSIZ",
+];
+
+/// The bit of each status flag in `v2.3.0` and `v2.4.0` frame headers: tag alter
+/// preservation, file alter preservation and read only.
+const STATUS_BITS: [(u8, u8); 3] = [(0b1000_0000, 0b0100_0000),
+                                    (0b0100_0000, 0b0010_0000),
+                                    (0b0010_0000, 0b0001_0000)];
+
+/// The bits of the format flags in `v2.3.0` frame headers.
+const V23_COMPRESSION: u8 = 0b1000_0000;
+const V23_ENCRYPTION: u8 = 0b0100_0000;
+const V23_GROUPING: u8 = 0b0010_0000;
+
+/// The bits of the format flags in `v2.4.0` frame headers, other than unsynchronisation, which
+/// has no equivalent for a single frame in `v2.3.0`.
+const V24_GROUPING: u8 = 0b0100_0000;
+const V24_COMPRESSION: u8 = 0b0000_1000;
+const V24_ENCRYPTION: u8 = 0b0000_0100;
+const V24_DATA_LENGTH_INDICATOR: u8 = 0b0000_0001;
+
+/// A type representing a frame that this library cannot decode, exactly as it was read.
+///
+/// A frame read from a tag is kept like this if its ID is not in `KNOWN_FRAMES`, if it is
+/// compressed or encrypted, or if it has flags that were not recognized. It is then written
+/// back byte for byte, rather than from its header and body.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UnknownFrame {
+    /// The frame ID.
+    pub id: [u8; 4],
+    /// The status and encoding flags, as they were in the frame header.
+    pub flags: [u8; 2],
+    /// Everything following the frame header, including any group symbol, data length
+    /// indicator and unsynchronisation.
+    pub raw_body: Vec<u8>,
+}
+
+impl UnknownFrame {
+    /// Gets whether or not to keep the frame when the tag is altered. The flag is in a
+    /// different bit in `v2.3.0` and `v2.4.0`.
+    pub fn preserve_on_tag_alter(&self, version: u8) -> bool {
+        self.flags[0] & if version == 3 { 0b1000_0000 } else { 0b0100_0000 } == 0
+    }
+
+    /// Gets whether or not to keep the frame when the audio, excluding the tag, is altered.
+    /// The flag is in a different bit in `v2.3.0` and `v2.4.0`.
+    pub fn preserve_on_file_alter(&self, version: u8) -> bool {
+        self.flags[0] & if version == 3 { 0b0100_0000 } else { 0b0010_0000 } == 0
+    }
+
+    /// Converts the frame from a tag of major version `from` to one of major version `to`,
+    /// moving its flags to where the new version keeps them, and reordering the data that the
+    /// format flags add before the body. A compressed frame's decompressed size becomes a data
+    /// length indicator, and back.
+    ///
+    /// Returns `None` if the frame cannot be converted: if it has flags that are not
+    /// recognized, if it is unsynchronised, which `v2.3.0` only does for a whole tag, if it is
+    /// compressed without a decompressed size, or if its body is too short for the data that
+    /// its flags add.
+    pub fn convert(&self, from: u8, to: u8) -> Option<UnknownFrame> {
+        if from == to {
+            return Some(self.clone());
+        }
+
+        let bit = |bits: (u8, u8), version: u8| if version == 3 { bits.0 } else { bits.1 };
+        let mut status = 0;
+        let mut known_status = 0;
+        for &bits in &STATUS_BITS {
+            known_status |= bit(bits, from);
+            if self.flags[0] & bit(bits, from) != 0 {
+                status |= bit(bits, to);
+            }
+        }
+        if self.flags[0] & !known_status != 0 {
+            return None;
+        }
+
+        // Read the data that the format flags add before the body, in the order of `from`
+        let format = self.flags[1];
+        let mut rest = &self.raw_body[..];
+        let (mut size, mut method, mut group) = (None, None, None);
+
+        if from == 3 {
+            if format & !(V23_COMPRESSION | V23_ENCRYPTION | V23_GROUPING) != 0 {
+                return None;
+            }
+            if format & V23_COMPRESSION != 0 {
+                let bytes = split(&mut rest, 4)?;
+                size = Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            }
+            if format & V23_ENCRYPTION != 0 {
+                method = Some(split(&mut rest, 1)?[0]);
+            }
+            if format & V23_GROUPING != 0 {
+                group = Some(split(&mut rest, 1)?[0]);
+            }
+        } else {
+            let known = V24_GROUPING | V24_COMPRESSION | V24_ENCRYPTION | V24_DATA_LENGTH_INDICATOR;
+            if format & !known != 0 {
+                return None;
+            }
+            if format & V24_GROUPING != 0 {
+                group = Some(split(&mut rest, 1)?[0]);
+            }
+            if format & V24_ENCRYPTION != 0 {
+                method = Some(split(&mut rest, 1)?[0]);
+            }
+            if format & V24_DATA_LENGTH_INDICATOR != 0 {
+                size = utils::synchsafe_to_u32(split(&mut rest, 4)?);
+            }
+
+            // `v2.3.0` only has a size for compressed frames, and needs one for them
+            if format & V24_COMPRESSION == 0 {
+                size = None;
+            } else if size.is_none() {
+                return None;
+            }
+        }
+
+        // Write it again in the order of `to`
+        let mut format = 0;
+        let mut raw_body = Vec::new();
+
+        if to == 3 {
+            if let Some(size) = size {
+                format |= V23_COMPRESSION;
+                raw_body.extend_from_slice(&size.to_be_bytes());
+            }
+            if let Some(method) = method {
+                format |= V23_ENCRYPTION;
+                raw_body.push(method);
+            }
+            if let Some(group) = group {
+                format |= V23_GROUPING;
+                raw_body.push(group);
+            }
+        } else {
+            if let Some(group) = group {
+                format |= V24_GROUPING;
+                raw_body.push(group);
+            }
+            if let Some(method) = method {
+                format |= V24_ENCRYPTION;
+                raw_body.push(method);
+            }
+            if let Some(size) = size {
+                if size >= 1 << 28 {
+                    return None;
+                }
+                format |= V24_COMPRESSION | V24_DATA_LENGTH_INDICATOR;
+                raw_body.extend_from_slice(&utils::u32_to_synchsafe(size));
+            }
+        }
+        raw_body.extend_from_slice(rest);
+
+        Some(UnknownFrame { id: self.id, flags: [status, format], raw_body })
+    }
+
+    /// Converts the frame into its bytes in a tag of the given major version, header included.
+    /// Only the size is written differently in each version, so the flags must already be laid
+    /// out for it, as `UnknownFrame::convert` does.
+    pub fn to_bytes(&self, version: u8) -> Vec<u8> {
+        let size = self.raw_body.len() as u32;
+
+        let mut bytes = self.id.to_vec();
+        match version {
+            4 => bytes.extend_from_slice(&utils::u32_to_synchsafe(size)),
+            _ => bytes.extend_from_slice(&size.to_be_bytes()),
+        }
+        bytes.extend_from_slice(&self.flags);
+        bytes.extend_from_slice(&self.raw_body);
+
+        bytes
+    }
+}
+
+/// Splits `length` bytes off the start of `bytes`, if it has that many.
+fn split<'a>(bytes: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+    if bytes.len() < length {
+        return None;
+    }
+
+    let (start, rest) = bytes.split_at(length);
+    *bytes = rest;
+
+    Some(start)
+}
+
+/// Gets whether or not a frame ID is defined by the `v2.3.0` or `v2.4.0` standards.
+pub fn is_known_frame(frame_id: &[u8; 4]) -> bool {
+    KNOWN_FRAMES.contains(&frame_id)
+}
+
+#[cfg(test)]
+mod unknown_tests {
+    use super::{UnknownFrame, is_known_frame};
+
+    #[test]
+    fn it_writes_back_exactly() {
+        let frame = UnknownFrame {
+            id: *b"XHD3",
+            flags: [0x40, 0x00],
+            raw_body: vec![0xff; 200],
+        };
+        let bytes = frame.to_bytes(4);
+
+        assert_eq!(&bytes[..10], b"XHD3\x00\x00\x01\x48\x40\x00");
+        assert_eq!(&bytes[10..], &frame.raw_body[..]);
+        assert_eq!(&frame.to_bytes(3)[4..8], b"\x00\x00\x00\xc8");
+    }
+
+    #[test]
+    fn it_reads_the_discard_flags() {
+        let frame = UnknownFrame { id: *b"XHD3", flags: [0x40, 0x00], raw_body: vec![0] };
+
+        assert!(!frame.preserve_on_tag_alter(4));
+        assert!(frame.preserve_on_file_alter(4));
+        assert!(frame.preserve_on_tag_alter(3));
+        assert!(!frame.preserve_on_file_alter(3));
+        assert!(is_known_frame(b"TIT2"));
+        assert!(!is_known_frame(b"XHD3"));
+    }
+
+    #[test]
+    fn it_converts_flags_between_versions() {
+        let frame = |flags: [u8; 2], raw_body: &[u8]| {
+            UnknownFrame { id: *b"XHD3", flags, raw_body: raw_body.to_vec() }
+        };
+
+        let preserved = frame([0xc0, 0x00], b"body");
+        assert_eq!(preserved.convert(3, 4), Some(frame([0x60, 0x00], b"body")));
+        assert_eq!(preserved.convert(3, 4).unwrap().convert(4, 3), Some(preserved.clone()));
+
+        // Decompressed size, encryption method and group in v2.3.0, but group, method and
+        // data length indicator in v2.4.0
+        let packed = frame([0x20, 0xe0], b"\x00\x00\x01\x00\x02\x07zlib");
+        let converted = frame([0x10, 0x4d], b"\x07\x02\x00\x00\x02\x00zlib");
+        assert_eq!(packed.convert(3, 4), Some(converted.clone()));
+        assert_eq!(converted.convert(4, 3), Some(packed));
+
+        assert_eq!(frame([0x00, 0x02], b"body").convert(4, 3), None);
+        assert_eq!(frame([0x00, 0x08], b"body").convert(4, 3), None);
+        assert_eq!(frame([0x01, 0x00], b"body").convert(3, 4), None);
+        assert_eq!(frame([0x00, 0x80], b"\x00").convert(3, 4), None);
+    }
+}
@@ -0,0 +1,102 @@
+//! Types and functions related to the user defined text information frame (`TXXX`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// A type representing the body of a user defined text information frame.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.2.2)](http://id3.org/id3v2.3.0#User_defined_text_information_frame)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserText {
+    /// The encoding used for the description and value.
+    pub encoding: Encoding,
+    /// The description, which must be unique among the `TXXX` frames in a tag.
+    pub description: String,
+    /// The value.
+    pub value: String,
+}
+
+impl UserText {
+    /// Construct a new user defined text body, using the simplest encoding that can represent
+    /// the description and value in a tag of the given major version.
+    pub fn for_version(description: String, value: String, version: u8) -> UserText {
+        let encoding = Encoding::for_text(&format!("{}{}", description, value), version);
+
+        UserText { encoding, description, value }
+    }
+
+    /// Construct a new user defined text body from the body of a frame.
+    ///
+    /// Only the first value is kept if a `v2.4.0` frame holds several.
+    ///
+    /// # Errors
+    ///
+    /// If the body is empty, then this function will return `Error::UnexpectedEnd`.
+    ///
+    /// If the encoding byte is not recognized, then this function will return
+    /// `Error::InvalidEncoding`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<UserText> {
+        let (&encoding_byte, rest) = bytes.split_first().ok_or(Error::UnexpectedEnd)?;
+        let encoding = Encoding::from_byte(encoding_byte)?;
+
+        let (description, rest) = encoding.split_terminated(rest);
+        let (value, _) = encoding.split_terminated(rest);
+
+        Ok(UserText {
+            encoding,
+            description: encoding.decode(description),
+            value: encoding.decode(value),
+        })
+    }
+
+    /// Construct a new user defined text body from a frame.
+    ///
+    /// # Errors
+    ///
+    /// See `UserText::from_bytes`.
+    pub fn from_frame(frame: &Frame) -> Result<UserText> {
+        UserText::from_bytes(frame.body())
+    }
+
+    /// Converts the text into a frame body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.encoding.to_byte()];
+        bytes.extend(self.encoding.encode_all(&[&self.description, &self.value]));
+
+        bytes
+    }
+
+    /// Converts the text into a `TXXX` frame.
+    pub fn to_frame(&self) -> Frame {
+        Frame::new(*b"TXXX", self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod user_text_tests {
+    use frame::encoding::Encoding;
+    use super::UserText;
+
+    #[test]
+    fn it_reads_the_description_and_value() {
+        let text = UserText::from_bytes(b"\0REPLAYGAIN_TRACK_GAIN\0-6.20 dB").unwrap();
+
+        assert_eq!(text.description, "REPLAYGAIN_TRACK_GAIN");
+        assert_eq!(text.value, "-6.20 dB");
+
+        let text = UserText::from_bytes(&[1, 0xFF, 0xFE, b'A', 0, 0, 0, 0xFF, 0xFE, b'B', 0])
+            .unwrap();
+
+        assert_eq!((text.description.as_str(), text.value.as_str()), ("A", "B"));
+    }
+
+    #[test]
+    fn it_writes_the_description_and_value() {
+        let text = UserText::for_version("key".to_string(), "value".to_string(), 4);
+
+        assert_eq!(text.encoding, Encoding::Latin1);
+        assert_eq!(text.to_bytes(), b"\0key\0value");
+    }
+}
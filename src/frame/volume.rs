@@ -0,0 +1,413 @@
+//! Types and functions related to the relative volume adjustment frames (`RVAD` in `v2.3.0` and
+//! `RVA2` in `v2.4.0`).
+
+use frame::encoding::Encoding;
+use frame::{Error, Frame, Result};
+
+/// A type representing the channel a volume adjustment applies to.
+///
+/// # Reference
+///
+/// [ID3v2.4 Native Frames (Section 4.11)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Any other channel.
+    Other,
+    /// Every channel. _Only `RVA2`._
+    MasterVolume,
+    /// The front right channel (just "right" in `RVAD`).
+    FrontRight,
+    /// The front left channel (just "left" in `RVAD`).
+    FrontLeft,
+    /// The back right channel.
+    BackRight,
+    /// The back left channel.
+    BackLeft,
+    /// The front centre channel (just "center" in `RVAD`).
+    FrontCentre,
+    /// The back centre channel. _Only `RVA2`._
+    BackCentre,
+    /// The subwoofer channel ("bass" in `RVAD`).
+    Subwoofer,
+    /// A channel type that the standard does not define.
+    Unknown(u8),
+}
+
+impl Channel {
+    /// Gets the channel described by an `RVA2` channel type byte.
+    pub fn from_byte(byte: u8) -> Channel {
+        match byte {
+            0 => Channel::Other,
+            1 => Channel::MasterVolume,
+            2 => Channel::FrontRight,
+            3 => Channel::FrontLeft,
+            4 => Channel::BackRight,
+            5 => Channel::BackLeft,
+            6 => Channel::FrontCentre,
+            7 => Channel::BackCentre,
+            8 => Channel::Subwoofer,
+            _ => Channel::Unknown(byte),
+        }
+    }
+
+    /// Gets the `RVA2` channel type byte for the channel.
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            Channel::Other => 0,
+            Channel::MasterVolume => 1,
+            Channel::FrontRight => 2,
+            Channel::FrontLeft => 3,
+            Channel::BackRight => 4,
+            Channel::BackLeft => 5,
+            Channel::FrontCentre => 6,
+            Channel::BackCentre => 7,
+            Channel::Subwoofer => 8,
+            Channel::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// A type representing the volume adjustment of a single channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelAdjustment {
+    /// The channel being adjusted.
+    pub channel: Channel,
+    /// The adjustment in decibels.
+    pub adjustment: f32,
+    /// The peak volume as a fraction of full scale, if it is known.
+    pub peak: Option<f32>,
+}
+
+/// The groups of channels in an `RVAD` frame, in the order they appear. Each group is only
+/// present if all of the groups before it are.
+const RVAD_GROUPS: [&[Channel]; 4] = [
+    &[Channel::FrontRight, Channel::FrontLeft],
+    &[Channel::BackRight, Channel::BackLeft],
+    &[Channel::FrontCentre],
+    &[Channel::Subwoofer],
+];
+
+/// The number of bits used for each field when writing an `RVAD` or `RVA2` frame.
+const WRITE_BITS: u8 = 16;
+
+/// A type representing a relative volume adjustment, common to `RVAD` and `RVA2`.
+///
+/// `v2.3.0` does not define the unit of an `RVAD` volume change, so like most software it is
+/// treated as a fraction of full scale: with `b` bits, a change of `n` scales the volume by
+/// `1 + n / 2^b` (or `1 - n / 2^b` if it is a decrement). Peaks in both frames are treated as a
+/// fraction of `2^(b - 1)`, the largest signed sample.
+///
+/// # Reference
+///
+/// [ID3v2.3 Informal Standard (Section 4.12)](http://id3.org/id3v2.3.0#Relative_volume_adjustment)
+///
+/// [ID3v2.4 Native Frames (Section 4.11)](http://id3.org/id3v2.4.0-frames)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VolumeAdjustment {
+    /// The situation the adjustment applies to, such as `track` or `album`. _Only `RVA2`._
+    pub identification: String,
+    /// The adjustment of each channel.
+    pub channels: Vec<ChannelAdjustment>,
+}
+
+impl VolumeAdjustment {
+    /// Construct a new volume adjustment from the body of an `RVAD` frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends part way through a group of channels, then this function will return
+    /// `Error::UnexpectedEnd`.
+    ///
+    /// If the number of bits used for each field is 0, then this function will return
+    /// `Error::InvalidBitWidth`.
+    pub fn from_rvad(bytes: &[u8]) -> Result<VolumeAdjustment> {
+        if bytes.len() < 2 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let increments = bytes[0];
+        let bits = bytes[1];
+        if bits == 0 {
+            return Err(Error::InvalidBitWidth(bits));
+        }
+
+        let width = (bits as usize).div_ceil(8);
+        let mut fields = bytes[2..].chunks(width);
+        let mut channels = Vec::new();
+        let mut increment_bit = 0;
+
+        for group in RVAD_GROUPS.iter() {
+            if fields.len() == 0 {
+                break;
+            }
+
+            // Every volume change in a group comes before every peak
+            let mut changes = Vec::new();
+            for _ in group.iter() {
+                changes.push(read_field(fields.next(), width)?);
+            }
+
+            for (&channel, change) in group.iter().zip(changes) {
+                let peak = read_field(fields.next(), width)?;
+                let increment = increments & (1 << increment_bit) != 0;
+
+                channels.push(ChannelAdjustment {
+                    channel,
+                    adjustment: change_to_decibels(change, bits, increment),
+                    peak: Some(peak_fraction(peak, bits)),
+                });
+                increment_bit += 1;
+            }
+        }
+
+        Ok(VolumeAdjustment { identification: String::new(), channels })
+    }
+
+    /// Construct a new volume adjustment from the body of an `RVA2` frame.
+    ///
+    /// # Errors
+    ///
+    /// If the body ends part way through a channel, then this function will return
+    /// `Error::UnexpectedEnd`.
+    pub fn from_rva2(bytes: &[u8]) -> Result<VolumeAdjustment> {
+        let (identification, mut rest) = Encoding::Latin1.split_terminated(bytes);
+        let identification = Encoding::Latin1.decode(identification);
+        let mut channels = Vec::new();
+
+        while !rest.is_empty() {
+            if rest.len() < 4 {
+                return Err(Error::UnexpectedEnd);
+            }
+
+            let channel = Channel::from_byte(rest[0]);
+            let adjustment = i16::from_be_bytes([rest[1], rest[2]]) as f32 / 512.0;
+            let bits = rest[3];
+            let width = (bits as usize).div_ceil(8);
+
+            let peak = read_field(rest.get(4..4 + width), width)?;
+            let peak = if bits == 0 {
+                None
+            } else {
+                Some(peak_fraction(peak, bits))
+            };
+
+            channels.push(ChannelAdjustment { channel, adjustment, peak });
+            rest = &rest[4 + width..];
+        }
+
+        Ok(VolumeAdjustment { identification, channels })
+    }
+
+    /// Construct a new volume adjustment from an `RVAD` or `RVA2` frame.
+    ///
+    /// # Errors
+    ///
+    /// See `VolumeAdjustment::from_rvad` and `VolumeAdjustment::from_rva2`.
+    pub fn from_frame(frame: &Frame) -> Result<VolumeAdjustment> {
+        match frame.id() {
+            b"RVAD" => VolumeAdjustment::from_rvad(frame.body()),
+            _ => VolumeAdjustment::from_rva2(frame.body()),
+        }
+    }
+
+    /// Gets the adjustment of the given channel.
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelAdjustment> {
+        self.channels.iter().find(|adjustment| adjustment.channel == channel)
+    }
+
+    /// Converts the adjustment into the body of an `RVAD` frame, using 16 bits for each field.
+    ///
+    /// The identification and any channels `RVAD` cannot represent are dropped, except that the
+    /// master volume is used for the front channels if they are not given.
+    pub fn to_rvad(&self) -> Vec<u8> {
+        let master = self.channel(Channel::MasterVolume);
+        let find = |channel: Channel| match (self.channel(channel), channel) {
+            (Some(adjustment), _) => Some(adjustment),
+            (None, Channel::FrontRight) | (None, Channel::FrontLeft) => master,
+            (None, _) => None,
+        };
+
+        // Groups can only be left out from the end
+        let group_count = RVAD_GROUPS
+            .iter()
+            .rposition(|group| group.iter().any(|&channel| find(channel).is_some()))
+            .map_or(1, |index| index + 1);
+
+        let mut increments = 0u8;
+        let mut fields = Vec::new();
+        let mut increment_bit = 0;
+
+        for group in RVAD_GROUPS.iter().take(group_count) {
+            let mut peaks = Vec::new();
+
+            for &channel in group.iter() {
+                let (adjustment, peak) = find(channel)
+                    .map_or((0.0, 0.0), |found| (found.adjustment, found.peak.unwrap_or(0.0)));
+                let (change, increment) = decibels_to_change(adjustment, WRITE_BITS);
+
+                if increment {
+                    increments |= 1 << increment_bit;
+                }
+                increment_bit += 1;
+
+                fields.push(change as u16);
+                peaks.push(peak_value(peak, WRITE_BITS) as u16);
+            }
+
+            fields.extend(peaks);
+        }
+
+        let mut bytes = vec![increments, WRITE_BITS];
+        for field in fields {
+            bytes.extend_from_slice(&field.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Converts the adjustment into the body of an `RVA2` frame, using 16 bits for each peak.
+    pub fn to_rva2(&self) -> Vec<u8> {
+        let mut bytes = Encoding::Latin1.encode(&self.identification);
+        bytes.push(0);
+
+        for adjustment in &self.channels {
+            let fixed = (adjustment.adjustment * 512.0).round();
+            let fixed = fixed.max(i16::MIN as f32).min(i16::MAX as f32) as i16;
+
+            bytes.push(adjustment.channel.to_byte());
+            bytes.extend_from_slice(&fixed.to_be_bytes());
+
+            match adjustment.peak {
+                Some(peak) => {
+                    bytes.push(WRITE_BITS);
+                    bytes.extend_from_slice(&(peak_value(peak, WRITE_BITS) as u16).to_be_bytes());
+                }
+                None => bytes.push(0),
+            }
+        }
+
+        bytes
+    }
+
+    /// Converts the adjustment into an `RVAD` frame for `v2.3.0`, or an `RVA2` frame otherwise.
+    pub fn to_frame(&self, version: u8) -> Frame {
+        if version == 3 {
+            Frame::new(*b"RVAD", self.to_rvad())
+        } else {
+            Frame::new(*b"RVA2", self.to_rva2())
+        }
+    }
+}
+
+/// Reads a big endian unsigned integer of `width` bytes, keeping the lowest 64 bits.
+pub(crate) fn read_field(bytes: Option<&[u8]>, width: usize) -> Result<u64> {
+    match bytes {
+        Some(bytes) if bytes.len() == width => {
+            Ok(bytes.iter().fold(0u64, |value, &byte| value.wrapping_shl(8) | byte as u64))
+        }
+        _ => Err(Error::UnexpectedEnd),
+    }
+}
+
+/// Converts an unsigned volume change of `bits` bits, as used by `RVAD` and `EQUA`, into
+/// decibels.
+pub(crate) fn change_to_decibels(change: u64, bits: u8, increment: bool) -> f32 {
+    let ratio = change as f64 / 2f64.powi(bits as i32);
+    let factor = if increment { 1.0 + ratio } else { 1.0 - ratio };
+
+    (20.0 * factor.max(f64::MIN_POSITIVE).log10()) as f32
+}
+
+/// Converts decibels into an unsigned volume change of `bits` bits and whether or not it is an
+/// increment, as used by `RVAD` and `EQUA`.
+pub(crate) fn decibels_to_change(adjustment: f32, bits: u8) -> (u64, bool) {
+    let factor = 10f64.powf(adjustment as f64 / 20.0);
+    let max = 2f64.powi(bits as i32) - 1.0;
+    let change = ((factor - 1.0).abs() * 2f64.powi(bits as i32)).round().min(max);
+
+    (change as u64, factor >= 1.0)
+}
+
+/// Converts a peak of `bits` bits into a fraction of full scale.
+fn peak_fraction(peak: u64, bits: u8) -> f32 {
+    (peak as f64 / 2f64.powi(bits as i32 - 1)) as f32
+}
+
+/// Converts a fraction of full scale into a peak of `bits` bits.
+fn peak_value(peak: f32, bits: u8) -> u64 {
+    let max = 2f64.powi(bits as i32) - 1.0;
+    (peak.max(0.0) as f64 * 2f64.powi(bits as i32 - 1)).round().min(max) as u64
+}
+
+#[cfg(test)]
+mod volume_tests {
+    use super::{Channel, ChannelAdjustment, VolumeAdjustment};
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.01, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn it_reads_rva2() {
+        let bytes = b"track\0\x01\xfb\x00\x10\x40\x00\x06\x02\x00\x00";
+        let volume = VolumeAdjustment::from_rva2(bytes).unwrap();
+
+        assert_eq!(volume.identification, "track");
+        assert_eq!(volume.channels.len(), 2);
+        assert_eq!(volume.channels[0].channel, Channel::MasterVolume);
+        assert_close(volume.channels[0].adjustment, -2.5);
+        assert_close(volume.channels[0].peak.unwrap(), 0.5);
+        assert_close(volume.channels[1].adjustment, 1.0);
+        assert_eq!(volume.channels[1].peak, None);
+    }
+
+    #[test]
+    fn it_reads_rvad() {
+        // Right is incremented and left decremented by half of full scale, with no back channels
+        let bytes = [0b01, 16, 0x80, 0x00, 0x80, 0x00, 0x40, 0x00, 0x20, 0x00];
+        let volume = VolumeAdjustment::from_rvad(&bytes).unwrap();
+
+        assert_eq!(volume.channels.len(), 2);
+        assert_close(volume.channel(Channel::FrontRight).unwrap().adjustment, 3.52);
+        assert_close(volume.channel(Channel::FrontLeft).unwrap().adjustment, -6.02);
+        assert_close(volume.channel(Channel::FrontLeft).unwrap().peak.unwrap(), 0.25);
+    }
+
+    #[test]
+    fn it_rejects_truncated_frames() {
+        assert!(VolumeAdjustment::from_rvad(&[0, 16, 0x80, 0x00, 0x80]).is_err());
+        assert!(VolumeAdjustment::from_rvad(&[0, 0]).is_err());
+        assert!(VolumeAdjustment::from_rva2(b"track\0\x01\xfb\x00\x10\x40").is_err());
+    }
+
+    #[test]
+    fn it_converts_between_frames() {
+        let volume = VolumeAdjustment {
+            identification: "track".to_string(),
+            channels: vec![ChannelAdjustment {
+                               channel: Channel::MasterVolume,
+                               adjustment: -6.5,
+                               peak: Some(0.9),
+                           },
+                           ChannelAdjustment {
+                               channel: Channel::Subwoofer,
+                               adjustment: 2.0,
+                               peak: None,
+                           }],
+        };
+
+        let rvad = VolumeAdjustment::from_rvad(&volume.to_rvad()).unwrap();
+
+        // Every group up to the subwoofer has to be written
+        assert_eq!(rvad.channels.len(), 6);
+        assert_close(rvad.channel(Channel::FrontLeft).unwrap().adjustment, -6.5);
+        assert_close(rvad.channel(Channel::FrontRight).unwrap().peak.unwrap(), 0.9);
+        assert_close(rvad.channel(Channel::Subwoofer).unwrap().adjustment, 2.0);
+
+        let rva2 = VolumeAdjustment::from_rva2(&volume.to_rva2()).unwrap();
+
+        assert_eq!(rva2.identification, "track");
+        assert_close(rva2.channels[0].adjustment, -6.5);
+        assert_eq!(rva2.channels[1].peak, None);
+    }
+}
@@ -2,51 +2,57 @@ use std::error;
 use std::fmt;
 use std::io;
 
+use utils;
+
 /// A list specifying the errors that can be encountered when constructing a header from a reader.
 #[derive(Debug)]
 pub enum Error {
-    /// The tag is not an ID3v2 tag.
-    InvalidIdentifier,
-    /// The size was either 0 or greater than 268435455.
-    InvalidSize,
-    /// Either of the versions were 255.
-    InvalidVersion,
+    /// The footer did not match the header. Holds the bytes of the footer.
+    FooterMismatch([u8; 10]),
+    /// The tag is not an ID3v2 tag. Holds the identifier that was found.
+    InvalidIdentifier([u8; 3]),
+    /// The size was either 0 or greater than 268435455. Holds the bytes of the size.
+    InvalidSize([u8; 4]),
+    /// Either of the versions were 255. Holds the bytes of the versions.
+    InvalidVersion([u8; 2]),
     /// An error occurred whilst reading the bytes.
     Io(io::Error),
-    /// An unknown header flag was encountered.
-    UnknownFlag,
+    /// An unknown header flag was encountered. Holds the byte of the flags.
+    UnknownFlag(u8),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Error::InvalidIdentifier => write!(f, "Not an ID3 identifier"),
-            Error::InvalidSize => write!(f, "Size must be greater than 0 and less than 268435456"),
-            Error::InvalidVersion => write!(f, "Major and revision versions must be less than 255"),
+            Error::FooterMismatch(ref bytes) => {
+                write!(f, "Footer does not match the header: {}", utils::hex(bytes))
+            }
+            Error::InvalidIdentifier(ref bytes) => {
+                write!(f, "Not an ID3 identifier: {}", utils::hex(bytes))
+            }
+            Error::InvalidSize(ref bytes) => {
+                write!(f, "Size must be greater than 0 and less than 268435456: {}",
+                       utils::hex(bytes))
+            }
+            Error::InvalidVersion(ref bytes) => {
+                write!(f, "Major and revision versions must be less than 255: {}",
+                       utils::hex(bytes))
+            }
             Error::Io(ref err) => write!(f, "IO error: {}", err),
-            Error::UnknownFlag => write!(f, "Unknown flag found"),
+            Error::UnknownFlag(byte) => write!(f, "Unknown flag found: {:02x}", byte),
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::InvalidIdentifier => "not an ID3 identifier",
-            Error::InvalidSize => "size must be greater than 0 and less than 268435456",
-            Error::InvalidVersion => "major and revision versions must be less than 255",
-            Error::Io(ref err) => err.description(),
-            Error::UnknownFlag => "unknown flag found",
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::InvalidIdentifier => None,
-            Error::InvalidSize => None,
-            Error::InvalidVersion => None,
+            Error::FooterMismatch(_) => None,
+            Error::InvalidIdentifier(_) => None,
+            Error::InvalidSize(_) => None,
+            Error::InvalidVersion(_) => None,
             Error::Io(ref err) => Some(err),
-            Error::UnknownFlag => None,
+            Error::UnknownFlag(_) => None,
         }
     }
 }
@@ -11,11 +11,11 @@ mod version;
 
 bitflags! {
     #[derive(Default)]
-    flags HeaderFlags: u8 {
-        const HEADER_UNSYNC = 0b1000_0000,
-        const HEADER_EXTENDED = 0b0100_0000,
-        const HEADER_EXPERIMENTAL = 0b0010_0000,
-        const HEADER_FOOTER = 0b0001_0000,
+    struct HeaderFlags: u8 {
+        const UNSYNC = 0b1000_0000;
+        const EXTENDED = 0b0100_0000;
+        const EXPERIMENTAL = 0b0010_0000;
+        const FOOTER = 0b0001_0000;
     }
 }
 
@@ -28,7 +28,7 @@ pub type Result<T> = result::Result<T, Error>;
 /// # Reference
 ///
 /// [ID3v2.3 Informal Standard (Section 3.1)](http://id3.org/id3v2.3.0#ID3v2_header)
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Header {
     identifier: [u8; 3],
     version: Version,
@@ -59,19 +59,53 @@ impl Header {
     ///
     /// If there is an unrecognized flag, then this function will return `Error::UnknownFlag`.
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Header> {
-        let mut header: Self = Default::default();
+        Header::read_with_flags(reader, true).map(|(header, _)| header)
+    }
 
-        let bytes = try!(Header::read(reader));
+    /// Construct a new header from a reader, also returning the bits of the flags that were not
+    /// recognized.
+    ///
+    /// If `strict` is false, unrecognized flags are ignored rather than being an error.
+    pub(crate) fn read_with_flags<R: Read>(reader: &mut R, strict: bool) -> Result<(Header, u8)> {
+        let bytes = Header::read(reader)?;
 
-        try!(header.set_identifier(&bytes));
-        try!(header.set_version(&bytes));
-        try!(header.set_size(&bytes));
-        try!(header.set_flags(&bytes));
+        Header::from_bytes(&bytes, b"ID3", strict)
+    }
 
-        Ok(header)
+    /// Construct a new footer from its bytes. A footer is a copy of the header of a `v2.4.0`
+    /// tag with the identifier "3DI", which lets a tag be found from the end of a file, so it
+    /// is read as a header.
+    ///
+    /// # Errors
+    ///
+    /// See `Header::from_reader`, except that the file identifier must be "3DI".
+    pub fn footer_from_bytes(bytes: &[u8; 10]) -> Result<Header> {
+        Header::from_bytes(bytes, b"3DI", true).map(|(footer, _)| footer)
     }
 
-    /// Gets the file identifier (currently always "ID3").
+    /// Checks that the bytes of a footer match this header.
+    ///
+    /// # Errors
+    ///
+    /// If the footer cannot be read, then this function will return the error from
+    /// `Header::footer_from_bytes`, ignoring unrecognized flags.
+    ///
+    /// If the version, flags or size of the footer differ from the header, then this function
+    /// will return `Error::FooterMismatch`.
+    pub fn validate_footer(&self, bytes: &[u8; 10]) -> Result<()> {
+        let (footer, _) = Header::from_bytes(bytes, b"3DI", false)?;
+
+        if footer.version.major == self.version.major &&
+           footer.version.revision == self.version.revision &&
+           footer.flags == self.flags &&
+           footer.size == self.size {
+            Ok(())
+        } else {
+            Err(Error::FooterMismatch(*bytes))
+        }
+    }
+
+    /// Gets the file identifier ("ID3", or "3DI" for a footer).
     pub fn identifier(&self) -> &[u8; 3] {
         &self.identifier
     }
@@ -89,41 +123,66 @@ impl Header {
 
     /// Gets whether or not unsynchronization is used.
     pub fn is_unsynchronized(&self) -> bool {
-        self.flags.intersects(HEADER_UNSYNC)
+        self.flags.intersects(HeaderFlags::UNSYNC)
     }
 
     /// Gets whether or not the header is followed by an extended header.
     pub fn has_extended_header(&self) -> bool {
-        self.flags.intersects(HEADER_EXTENDED)
+        self.flags.intersects(HeaderFlags::EXTENDED)
     }
 
     /// Gets whether or not the tag is in an experimental stage.
     pub fn is_experimental(&self) -> bool {
-        self.flags.intersects(HEADER_EXPERIMENTAL)
+        self.flags.intersects(HeaderFlags::EXPERIMENTAL)
     }
 
-    /// Gets whether or not the tag has a footer.
+    /// Gets whether or not the tag has a footer. _Only `v2.4.0`._
     ///
-    /// Since this library only supports `v2.3.0`, a `v2.4.0` footer will never be parsed, but we
-    /// need to know one exists so it can be safely skipped.
+    /// The footer follows the frames and padding, and is checked against the header when the
+    /// tag is read. See `Header::validate_footer`. In older versions the flag is not
+    /// recognized, so this is always false.
     pub fn has_footer(&self) -> bool {
-        self.flags.intersects(HEADER_FOOTER)
+        self.flags.intersects(HeaderFlags::FOOTER)
+    }
+
+    /// Sets the major version, resetting the revision since it only applies to the old version.
+    pub(crate) fn set_major_version(&mut self, major: u8) {
+        self.version.major = major;
+        self.version.revision = 0;
+    }
+
+    /// Construct a new header or footer from its bytes, also returning the bits of the flags
+    /// that were not recognized.
+    fn from_bytes(bytes: &HeaderBytes, identifier: &[u8; 3], strict: bool)
+                  -> Result<(Header, u8)> {
+        let mut header: Self = Default::default();
+
+        header.set_identifier(bytes, identifier)?;
+        header.set_version(bytes)?;
+        header.set_size(bytes)?;
+        let unknown = header.set_flags(bytes);
+
+        if strict && unknown != 0 {
+            return Err(Error::UnknownFlag(bytes[5]));
+        }
+
+        Ok((header, unknown))
     }
 
     /// Read and return 10 bytes from the reader.
     fn read<R: Read>(reader: &mut R) -> Result<HeaderBytes> {
         let mut bytes = [0u8; 10];
-        try!(reader.read_exact(&mut bytes));
+        reader.read_exact(&mut bytes)?;
 
         Ok(bytes)
     }
 
-    /// Set the file identifier (currently always "ID3").
-    fn set_identifier(&mut self, bytes: &HeaderBytes) -> Result<()> {
+    /// Set the file identifier ("ID3", or "3DI" for a footer).
+    fn set_identifier(&mut self, bytes: &HeaderBytes, identifier: &[u8; 3]) -> Result<()> {
         self.identifier = [bytes[0], bytes[1], bytes[2]];
 
-        if &self.identifier != b"ID3" {
-            Err(Error::InvalidIdentifier)
+        if &self.identifier != identifier {
+            Err(Error::InvalidIdentifier(self.identifier))
         } else {
             Ok(())
         }
@@ -137,7 +196,7 @@ impl Header {
         if self.version.major < 0xFF && self.version.revision < 0xFF {
             Ok(())
         } else {
-            Err(Error::InvalidVersion)
+            Err(Error::InvalidVersion([bytes[3], bytes[4]]))
         }
     }
 
@@ -150,17 +209,20 @@ impl Header {
         if self.size > 0 && self.size < 0x1000_0000 {
             Ok(())
         } else {
-            Err(Error::InvalidSize)
+            Err(Error::InvalidSize([bytes[6], bytes[7], bytes[8], bytes[9]]))
         }
     }
 
-    /// Set the flags of the tag.
-    fn set_flags(&mut self, bytes: &HeaderBytes) -> Result<()> {
-        self.flags = match HeaderFlags::from_bits(bytes[5]) {
-            Some(flags) => flags,
-            None => return Err(Error::UnknownFlag),
-        };
+    /// Set the flags of the tag, returning the bits that were not recognized.
+    ///
+    /// The version must already be set, since only `v2.4.0` tags can have a footer.
+    fn set_flags(&mut self, bytes: &HeaderBytes) -> u8 {
+        let mut known = HeaderFlags::all();
+        if self.version.major != 4 {
+            known.remove(HeaderFlags::FOOTER);
+        }
+        self.flags = HeaderFlags::from_bits_truncate(bytes[5]) & known;
 
-        Ok(())
+        bytes[5] & !known.bits()
     }
 }
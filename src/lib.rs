@@ -1,8 +1,12 @@
 #[macro_use]
 extern crate bitflags;
+extern crate encoding_rs;
+extern crate sha1_smol;
 
 pub mod byte_reader;
 pub mod tag;
 pub mod header;
 pub mod frame;
+pub mod mpeg;
 pub mod utils;
+pub mod ape;
@@ -0,0 +1,254 @@
+//! Types and functions related to scanning the MPEG audio stream that follows an ID3v2 tag.
+
+use std::io;
+use std::io::Read;
+
+/// The bitrates in kbit/s for each bitrate index, excluding free (0) and bad (15), for MPEG-1
+/// layers I, II and III.
+const V1_BITRATES: [[u16; 14]; 3] = [
+    [32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+    [32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+    [32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+];
+
+/// The bitrates in kbit/s for each bitrate index for MPEG-2 and MPEG-2.5 layer I, then layers
+/// II and III.
+const V2_BITRATES: [[u16; 14]; 2] = [
+    [32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+    [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+/// The sample rates in Hz for each sample rate index of MPEG-1.
+const V1_SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+/// A type representing the version of an MPEG audio frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// MPEG-1.
+    Mpeg1,
+    /// MPEG-2.
+    Mpeg2,
+    /// MPEG-2.5, an unofficial extension for low sample rates.
+    Mpeg25,
+}
+
+/// A type representing the header of an MPEG audio frame.
+///
+/// Free format frames (bitrate index 0) are not supported, since their length cannot be worked
+/// out from the header alone.
+///
+/// # Reference
+///
+/// [MPEG Audio Frame Header](http://www.mp3-tech.org/programmer/frame_header.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AudioFrame {
+    /// The version of MPEG.
+    pub version: Version,
+    /// The layer, from 1 to 3.
+    pub layer: u8,
+    /// The bitrate in kbit/s.
+    pub bitrate: u16,
+    /// The sample rate in Hz.
+    pub sample_rate: u32,
+    /// Whether or not the frame has a padding slot.
+    pub padding: bool,
+}
+
+impl AudioFrame {
+    /// Construct a new frame header from the first four bytes of a frame.
+    ///
+    /// Returns `None` if the bytes are not a valid frame header.
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<AudioFrame> {
+        if bytes[0] != 0xFF || bytes[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+
+        let version = match (bytes[1] >> 3) & 0b11 {
+            0 => Version::Mpeg25,
+            2 => Version::Mpeg2,
+            3 => Version::Mpeg1,
+            _ => return None,
+        };
+        let layer = match (bytes[1] >> 1) & 0b11 {
+            0 => return None,
+            bits => 4 - bits,
+        };
+
+        let bitrate_index = (bytes[2] >> 4) as usize;
+        let sample_rate_index = ((bytes[2] >> 2) & 0b11) as usize;
+        if bitrate_index == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+            return None;
+        }
+
+        let bitrate = match (version, layer) {
+            (Version::Mpeg1, _) => V1_BITRATES[layer as usize - 1][bitrate_index - 1],
+            (_, 1) => V2_BITRATES[0][bitrate_index - 1],
+            _ => V2_BITRATES[1][bitrate_index - 1],
+        };
+        let sample_rate = match version {
+            Version::Mpeg1 => V1_SAMPLE_RATES[sample_rate_index],
+            Version::Mpeg2 => V1_SAMPLE_RATES[sample_rate_index] / 2,
+            Version::Mpeg25 => V1_SAMPLE_RATES[sample_rate_index] / 4,
+        };
+
+        Some(AudioFrame {
+            version,
+            layer,
+            bitrate,
+            sample_rate,
+            padding: bytes[2] & 0b10 != 0,
+        })
+    }
+
+    /// Gets the number of samples (per channel) in the frame.
+    pub fn samples(&self) -> u32 {
+        match (self.layer, self.version) {
+            (1, _) => 384,
+            (3, Version::Mpeg2) | (3, Version::Mpeg25) => 576,
+            _ => 1152,
+        }
+    }
+
+    /// Gets the length of the frame in bytes, including the header.
+    pub fn length(&self) -> u32 {
+        let bitrate = self.bitrate as u32 * 1000;
+        let padding = self.padding as u32;
+
+        if self.layer == 1 {
+            (12 * bitrate / self.sample_rate + padding) * 4
+        } else {
+            self.samples() / 8 * bitrate / self.sample_rate + padding
+        }
+    }
+
+    /// Gets whether or not `other` could belong to the same stream as this frame.
+    fn is_compatible(&self, other: &AudioFrame) -> bool {
+        self.version == other.version && self.layer == other.layer &&
+        self.sample_rate == other.sample_rate
+    }
+}
+
+/// A type representing a frame found while scanning an MPEG audio stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScannedFrame {
+    /// The number of bytes between the start of the scan and the start of the frame.
+    pub offset: u64,
+    /// The header of the frame.
+    pub header: AudioFrame,
+}
+
+/// Scans an MPEG audio stream for frames, starting from the current position of the reader
+/// (usually just after the ID3v2 tag).
+///
+/// Bytes that are not part of a frame, such as junk or an ID3v1 tag at the end, are skipped.
+/// Once a frame is found, later frames must have the same version, layer and sample rate, which
+/// keeps stray sync words from being taken as frames.
+///
+/// # Errors
+///
+/// If there is an error reading from the reader, then this function will return the
+/// `io::Error`.
+pub fn scan<R: Read>(reader: &mut R) -> io::Result<Vec<ScannedFrame>> {
+    let mut frames: Vec<ScannedFrame> = Vec::new();
+    let mut window = [0u8; 4];
+    let mut offset = 0u64;
+
+    if read_fully(reader, &mut window)? < window.len() {
+        return Ok(frames);
+    }
+
+    loop {
+        let header = AudioFrame::from_bytes(window).filter(|header| {
+            frames.last().is_none_or(|last| last.header.is_compatible(header))
+        });
+
+        match header {
+            Some(header) => {
+                let remaining = header.length() as u64 - window.len() as u64;
+                if io::copy(&mut reader.take(remaining), &mut io::sink())? < remaining {
+                    break;
+                }
+
+                frames.push(ScannedFrame { offset, header });
+                offset += header.length() as u64;
+
+                if read_fully(reader, &mut window)? < window.len() {
+                    break;
+                }
+            }
+            None => {
+                // Slide along by one byte to look for the next sync word
+                window.copy_within(1.., 0);
+                if read_fully(reader, &mut window[3..])? == 0 {
+                    break;
+                }
+                offset += 1;
+            }
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Reads into `buffer` until it is full or the reader ends, returning the number of bytes read.
+fn read_fully<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(count) => read += count,
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(read)
+}
+
+#[cfg(test)]
+mod mpeg_tests {
+    use super::{AudioFrame, Version, scan};
+
+    /// Builds an MPEG-1 layer III frame at 44.1 kHz with the given bitrate index.
+    fn frame(bitrate_index: u8, padding: bool) -> Vec<u8> {
+        let header = [0xFF, 0xFB, bitrate_index << 4 | (padding as u8) << 1, 0x44];
+        let mut bytes = header.to_vec();
+        bytes.resize(AudioFrame::from_bytes(header).unwrap().length() as usize, 0);
+
+        bytes
+    }
+
+    #[test]
+    fn it_reads_headers() {
+        let header = AudioFrame::from_bytes([0xFF, 0xFB, 0x90, 0x44]).unwrap();
+
+        assert_eq!(header.version, Version::Mpeg1);
+        assert_eq!((header.layer, header.bitrate, header.sample_rate), (3, 128, 44100));
+        assert_eq!((header.samples(), header.length()), (1152, 417));
+
+        let header = AudioFrame::from_bytes([0xFF, 0xF3, 0x82, 0x44]).unwrap();
+
+        assert_eq!(header.version, Version::Mpeg2);
+        assert_eq!((header.bitrate, header.sample_rate), (64, 22050));
+        assert_eq!((header.samples(), header.length()), (576, 209));
+
+        assert_eq!(AudioFrame::from_bytes([0xFF, 0xFB, 0xF0, 0x44]), None);
+        assert_eq!(AudioFrame::from_bytes([0x49, 0x44, 0x33, 0x03]), None);
+    }
+
+    #[test]
+    fn it_scans_past_junk() {
+        let mut bytes = b"junk".to_vec();
+        bytes.extend(frame(9, false));
+        bytes.extend(frame(5, true));
+        bytes.extend(b"TAG");
+
+        let frames = scan(&mut &bytes[..]).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].offset, 4);
+        assert_eq!(frames[1].offset, 4 + 417);
+        assert_eq!(frames[1].header.bitrate, 64);
+    }
+}
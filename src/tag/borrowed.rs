@@ -0,0 +1,192 @@
+//! Types and functions for reading a tag from a byte slice without copying the frame bodies.
+
+use frame;
+use frame::FrameRef;
+use frame::text::Text;
+use header::Header;
+use tag::read::frames_fit;
+use tag::{Error, HEADER_SIZE, Result, Tag};
+
+/// A type representing an ID3v2 tag whose frames borrow their bodies from the bytes it was read
+/// from, for example a memory mapped file.
+///
+/// This reads the frames of a tag like `Tag::from_reader` does, but without copying each frame
+/// body. Unlike `Tag::from_reader`, it keeps the frames of a tag that was cut off before its
+/// end rather than failing (see `TagRef::from_bytes`), since a slice that is too short is
+/// usually only part of a file that is still there. Use `TagRef::to_tag` to get a tag that
+/// can be changed.
+#[derive(Debug)]
+pub struct TagRef<'a> {
+    header: Header,
+    frames: Vec<FrameRef<'a>>,
+}
+
+impl<'a> TagRef<'a> {
+    /// Construct a new tag from the start of `bytes`.
+    ///
+    /// Reading stops at the first padding byte. If `bytes` ends before the end of the tag, the
+    /// frames that end before it are kept, and the frame that was cut off is left out, where
+    /// `Tag::from_reader` would return `Error::UnexpectedEnd`. The footer of a `v2.4.0` tag that
+    /// has one is checked against the header.
+    ///
+    /// # Errors
+    ///
+    /// If there is an error reading the header, or the footer does not match it, then this
+    /// function will return `Error::Header`.
+    ///
+    /// If there is an error reading a frame, then this function will return `Error::Frame`,
+    /// with the offset of the frame from the start of the tag.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<TagRef<'a>> {
+        let header = Header::from_reader(&mut &bytes[..])
+            .map_err(|error| Error::Header { offset: 0, error })?;
+        let version = header.version().major;
+
+        let end = bytes.len().min(HEADER_SIZE + header.size() as usize);
+        let truncated = end < HEADER_SIZE + header.size() as usize;
+        let body = &bytes[HEADER_SIZE..end];
+
+        let mut position = 0;
+        if header.has_extended_header() {
+            let mut rest = body;
+            Tag::skip_extended_header(&mut rest, version)?;
+            position = body.len() - rest.len();
+        }
+
+        let synchsafe_sizes = version != 4 ||
+                              frames_fit(body, position, version, true) ||
+                              !frames_fit(body, position, version, false);
+
+        let mut frames = Vec::new();
+
+        // Stop at the padding, since there are no more frames after it
+        while position < body.len() && body[position] != 0 {
+            let rest = &body[position..];
+            let parsed = FrameRef::parse(rest, version, true, synchsafe_sizes);
+
+            // Keep the frames before the end of a tag that was cut off
+            if truncated && matches!(parsed, Err(frame::Error::UnexpectedEnd)) {
+                break;
+            }
+
+            let (frame, _) = parsed.map_err(|error| {
+                let mut frame_id = [0; 4];
+                let length = rest.len().min(4);
+                frame_id[..length].copy_from_slice(&rest[..length]);
+
+                Error::Frame { offset: (HEADER_SIZE + position) as u64, frame_id, error }
+            })?;
+
+            position += frame.raw().len();
+            frames.push(frame);
+        }
+
+        if header.has_footer() {
+            let offset = HEADER_SIZE + header.size() as usize;
+            if let Some(footer) = bytes.get(offset..offset + HEADER_SIZE) {
+                let mut footer_bytes = [0u8; HEADER_SIZE];
+                footer_bytes.copy_from_slice(footer);
+
+                header.validate_footer(&footer_bytes)
+                    .map_err(|error| Error::Header { offset: offset as u64, error })?;
+            }
+        }
+
+        Ok(TagRef { header, frames })
+    }
+
+    /// Gets the header of the tag.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Gets the major version of the tag.
+    pub fn version(&self) -> u8 {
+        self.header.version().major
+    }
+
+    /// Gets all of the frames in the tag, in the order they were read.
+    pub fn frames(&self) -> &[FrameRef<'a>] {
+        &self.frames
+    }
+
+    /// Gets the first frame with the given ID.
+    pub fn frame(&self, frame_id: &[u8; 4]) -> Option<&FrameRef<'a>> {
+        self.frames.iter().find(|frame| frame.id() == frame_id)
+    }
+
+    /// Gets the text of the first text information frame with the given ID, joining several
+    /// values with `/`.
+    pub fn text(&self, frame_id: &[u8; 4]) -> Option<String> {
+        self.frame(frame_id)
+            .and_then(|frame| Text::from_bytes(frame.body()).ok())
+            .map(|text| text.text().to_string())
+    }
+
+    /// Copies the tag into an owned tag, header included.
+    pub fn to_tag(&self) -> Tag {
+        Tag {
+            header: self.header.clone(),
+            frames: self.frames.iter().map(FrameRef::to_frame).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod borrowed_tests {
+    use tag::{Error, Tag, TagRef};
+
+    const TAG: &[u8] = b"ID3\x03\x00\x00\x00\x00\x00\x26\
+                         TIT2\x00\x00\x00\x06\x00\x00\x00Title\
+                         TALB\x00\x00\x00\x06\x00\x00\x00Album\
+                         \x00\x00\x00\x00\x00\x00";
+
+    #[test]
+    fn it_borrows_frame_bodies() {
+        let tag = TagRef::from_bytes(TAG).unwrap();
+
+        assert_eq!(tag.frames().len(), 2);
+        assert_eq!(tag.text(b"TALB").as_deref(), Some("Album"));
+        assert_eq!(tag.frame(b"TIT2").unwrap().body().as_ptr(), TAG[20..].as_ptr());
+
+        let owned = Tag::from_reader(&mut &TAG[..]).unwrap();
+        let copied = tag.to_tag();
+        assert_eq!(copied.version(), owned.version());
+        assert_eq!(copied.text(b"TIT2"), owned.text(b"TIT2"));
+    }
+
+    #[test]
+    fn it_keeps_frames_before_the_end_of_cut_off_bytes() {
+        let tag = TagRef::from_bytes(&TAG[..40]).unwrap();
+
+        assert_eq!(tag.frames().len(), 1);
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Title"));
+
+        match Tag::from_reader(&mut &TAG[..40]) {
+            Err(Error::UnexpectedEnd { offset: 40, .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn it_copies_the_header() {
+        let mut bytes = TAG.to_vec();
+        bytes[5] = 0x20;
+
+        let tag = TagRef::from_bytes(&bytes).unwrap().to_tag();
+
+        assert!(tag.header().is_experimental());
+        assert_eq!(tag.header().size(), 0x26);
+    }
+
+    #[test]
+    fn it_locates_errors() {
+        let mut bytes = TAG.to_vec();
+        bytes[35] = 0x01;
+
+        match TagRef::from_bytes(&bytes) {
+            Err(Error::Frame { offset: 26, frame_id, .. }) => assert_eq!(&frame_id, b"TALB"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! Functions for converting the frames of a tag between ID3v2 versions.
+
+use frame::encoding::Encoding;
+use frame::equalisation::Equalisation;
+use frame::involved_people::{InvolvedPeople, is_instrument};
+use frame::text::{Text, V23_MULTI_VALUE_FRAMES};
+use frame::volume::VolumeAdjustment;
+use tag::{Error, Result, Tag};
+
+impl Tag {
+    /// Converts the tag to the given major version (3 or 4), translating frames that differ
+    /// between the versions.
+    ///
+    /// * `IPLS` is split into `TMCL`, for the roles that are `INSTRUMENTS`, and `TIPL`, for the
+    ///   rest, when converting to `v2.4.0`.
+    /// * `TIPL` and `TMCL` are joined into `IPLS` when converting to `v2.3.0`.
+    /// * `TYER`, `TDAT` and `TIME` (or `TRDA`) are joined into `TDRC`, and `TORY` becomes `TDOR`
+    ///   when converting to `v2.4.0`.
+    /// * `TDRC` (or `TDRL` if there is no `TDRC`) is split into `TYER`, `TDAT` and `TIME`, and
+    ///   `TDOR` becomes `TORY` when converting to `v2.3.0`.
+    /// * `RVAD` becomes `RVA2` when converting to `v2.4.0`. When converting to `v2.3.0`, the
+    ///   `RVA2` frame identified as `track` (or the first, if there is none) becomes `RVAD`.
+    /// * `TCON` is rewritten in the form used by the new version.
+    /// * Text information frames with several values are joined with `Tag::v23_separator` when
+    ///   converting to `v2.3.0`, and the frames in `V23_MULTI_VALUE_FRAMES` are split on it when
+    ///   converting to `v2.4.0`. Text in an encoding that `v2.3.0` does not support is re-encoded
+    ///   as ISO-8859-1 if possible, or UTF-16 otherwise.
+    ///
+    /// Frames with no equivalent in the new version (`TRDA` in `v2.4.0`, and `TDRL`, `TDEN` and
+    /// `TDTG` in `v2.3.0`) are removed. Other frames that cannot be decoded are left untouched,
+    /// except that their flags are moved to where the new version keeps them. Those whose flags
+    /// cannot be, such as unsynchronised `v2.4.0` frames, are removed. See
+    /// `UnknownFrame::convert`.
+    ///
+    /// # Errors
+    ///
+    /// If the version is not 3 or 4, then this function will return `Error::UnsupportedVersion`.
+    pub fn convert(&mut self, version: u8) -> Result<()> {
+        if version != 3 && version != 4 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let genres = self.frame(b"TCON").map(|_| self.genres());
+        let previous_version = self.version();
+
+        if previous_version != version {
+            self.frames.retain_mut(|frame| frame.convert_unknown(previous_version, version));
+            self.altered = true;
+        }
+
+        self.convert_text(previous_version, version);
+        self.header.set_major_version(version);
+        if let Some(genres) = genres {
+            self.set_genres(&genres);
+        }
+        self.convert_involved_people(version);
+        self.convert_timestamps(version);
+        self.convert_volume_adjustments(version);
+        self.convert_equalisations(version);
+
+        Ok(())
+    }
+
+    /// Splits or joins the values of the text information frames, and re-encodes any text that
+    /// `version` does not support.
+    ///
+    /// Frames that do not need to change are left untouched. See `Tag::replace_bodies` for what
+    /// happens to those that do.
+    fn convert_text(&mut self, previous_version: u8, version: u8) {
+        let separator = self.v23_separator.clone();
+
+        self.replace_bodies(|frame| {
+            let frame_id = *frame.id();
+
+            // These are handled separately, since their values have their own structure
+            if frame_id[0] != b'T' || [b"TXXX", b"TCON", b"TIPL", b"TMCL"].contains(&&frame_id) {
+                return None;
+            }
+
+            let mut text = Text::from_frame(frame).ok()?;
+
+            if previous_version == 3 && V23_MULTI_VALUE_FRAMES.contains(&&frame_id) {
+                text = text.split(&separator);
+            }
+            if !text.encoding().is_supported(version) {
+                text = Text::for_version(text.values().to_vec(), version);
+            }
+            if version == 3 {
+                text = text.join(&separator);
+            }
+
+            Some(text.to_bytes()).filter(|body| body != frame.body())
+        });
+    }
+
+    /// Splits or joins the date and time frames into the frames used by `version`.
+    ///
+    /// The tag must already be set to `version`.
+    fn convert_timestamps(&mut self, version: u8) {
+        if version == 3 {
+            let recording_time = self.timestamp(b"TDRC").or_else(|| self.release_time());
+            let original_release_time = self.timestamp(b"TDOR");
+
+            for &frame_id in &[b"TDRL", b"TDEN", b"TDTG"] {
+                self.remove_frames(frame_id);
+            }
+
+            if let Some(timestamp) = recording_time {
+                self.remove_frames(b"TDRC");
+                self.set_recording_time(timestamp);
+            }
+            if let Some(timestamp) = original_release_time {
+                self.remove_frames(b"TDOR");
+                self.set_original_release_time(timestamp);
+            }
+        } else {
+            let recording_time = self.v23_recording_time();
+            let original_release_time = self.original_release_time();
+
+            for &frame_id in &[b"TYER", b"TDAT", b"TIME", b"TRDA", b"TORY"] {
+                self.remove_frames(frame_id);
+            }
+
+            if let (None, Some(timestamp)) = (self.frame(b"TDRC"), recording_time) {
+                self.set_recording_time(timestamp);
+            }
+            if let (None, Some(timestamp)) = (self.frame(b"TDOR"), original_release_time) {
+                self.set_original_release_time(timestamp);
+            }
+        }
+    }
+
+    /// Converts the relative volume adjustment frames into the frame used by `version`.
+    fn convert_volume_adjustments(&mut self, version: u8) {
+        let source = if version == 3 { b"RVA2" } else { b"RVAD" };
+        let volumes: Vec<VolumeAdjustment> = self.frames
+            .iter()
+            .filter(|frame| frame.id() == source)
+            .filter_map(|frame| VolumeAdjustment::from_frame(frame).ok())
+            .collect();
+
+        if volumes.is_empty() {
+            return;
+        }
+        self.remove_frames(source);
+
+        if version == 3 {
+            let track = volumes.iter().find(|volume| volume.identification == "track");
+            self.add_volume_adjustment(track.unwrap_or(&volumes[0]));
+        } else {
+            for volume in &volumes {
+                self.add_volume_adjustment(volume);
+            }
+        }
+    }
+
+    /// Converts the equalisation frames into the frame used by `version`.
+    fn convert_equalisations(&mut self, version: u8) {
+        let source = if version == 3 { b"EQU2" } else { b"EQUA" };
+        let equalisations: Vec<Equalisation> = self.frames
+            .iter()
+            .filter(|frame| frame.id() == source)
+            .filter_map(|frame| Equalisation::from_frame(frame).ok())
+            .collect();
+
+        if equalisations.is_empty() {
+            return;
+        }
+        self.remove_frames(source);
+
+        // Only one `EQUA` frame is allowed, so the first curve wins
+        let count = if version == 3 { 1 } else { equalisations.len() };
+        for equalisation in equalisations.iter().take(count) {
+            self.add_equalisation(equalisation);
+        }
+    }
+
+    /// Merges the involved people list frames that are not valid in `version` into the frames
+    /// that are.
+    ///
+    /// The roles of an `IPLS` frame that are `INSTRUMENTS` go into `TMCL`, and the rest into
+    /// `TIPL`.
+    fn convert_involved_people(&mut self, version: u8) {
+        if version == 3 {
+            if let Some(people) = self.take_involved_people(&[b"IPLS", b"TIPL", b"TMCL"]) {
+                self.push_involved_people(people, b"IPLS", version);
+            }
+            return;
+        }
+
+        let ipls = match self.take_involved_people(&[b"IPLS"]) {
+            Some(ipls) => ipls,
+            None => return,
+        };
+
+        let (musicians, others): (Vec<_>, Vec<_>) = ipls.people()
+            .iter()
+            .cloned()
+            .partition(|(role, _)| is_instrument(role));
+
+        for (frame_id, people) in [(b"TIPL", others), (b"TMCL", musicians)] {
+            if people.is_empty() {
+                continue;
+            }
+
+            let mut list = self.take_involved_people(&[frame_id]).unwrap_or_default();
+            list.people_mut().extend(people);
+            self.push_involved_people(list, frame_id, version);
+        }
+    }
+
+    /// Removes the involved people list frames with the given IDs, returning their people as a
+    /// single list.
+    ///
+    /// Frames that cannot be decoded are kept.
+    fn take_involved_people(&mut self, frame_ids: &[&[u8; 4]]) -> Option<InvolvedPeople> {
+        let mut merged: Option<InvolvedPeople> = None;
+
+        for &frame_id in frame_ids {
+            for frame in self.remove_frames(frame_id) {
+                match InvolvedPeople::from_frame(&frame) {
+                    Ok(list) => match merged {
+                        Some(ref mut merged) => {
+                            merged.people_mut().extend_from_slice(list.people())
+                        }
+                        None => merged = Some(list),
+                    },
+                    Err(_) => self.frames.push(frame),
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Adds a list of involved people as a frame with the given ID.
+    fn push_involved_people(&mut self, mut list: InvolvedPeople, frame_id: &[u8; 4], version: u8) {
+        // The people can come from lists with different encodings, so pick one that holds all
+        // of them
+        let text: String = list.people()
+            .iter()
+            .map(|(role, name)| role.clone() + name)
+            .collect();
+        list.set_encoding(Encoding::for_text(&text, version));
+        self.frames.push(list.to_frame(*frame_id));
+    }
+}
+
+#[cfg(test)]
+mod convert_tests {
+    use frame::Frame;
+    use frame::encoding::Encoding;
+    use tag::Tag;
+    use tag::test_helpers::tag_with;
+
+    #[test]
+    fn it_maps_ipls_to_tipl() {
+        let mut tag = tag_with(3, vec![Frame::new(*b"IPLS", b"\0producer\0Karajan".to_vec())]);
+        tag.convert(4).unwrap();
+
+        assert!(tag.frame(b"IPLS").is_none());
+        assert!(tag.frame(b"TIPL").is_some());
+        assert_eq!(tag.involved_people(), vec![("producer".to_string(), "Karajan".to_string())]);
+    }
+
+    #[test]
+    fn it_maps_instrument_roles_to_tmcl() {
+        let ipls = b"\0producer\0Karajan\0Piano\0Gould\0violin\0Mutter".to_vec();
+        let mut tag = tag_with(3, vec![Frame::new(*b"IPLS", ipls)]);
+        tag.convert(4).unwrap();
+
+        assert_eq!(tag.involved_people(), vec![("producer".to_string(), "Karajan".to_string())]);
+        assert_eq!(tag.musician_credits(),
+                   vec![("Piano".to_string(), "Gould".to_string()),
+                        ("violin".to_string(), "Mutter".to_string())]);
+    }
+
+    #[test]
+    fn it_joins_tipl_and_tmcl_into_ipls() {
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"TIPL", b"\x03producer\0Karajan".to_vec()),
+                                    Frame::new(*b"TMCL", b"\x03piano\0Gould".to_vec())]);
+        tag.convert(3).unwrap();
+
+        let ipls = tag.frame(b"IPLS").unwrap();
+
+        assert_eq!(tag.version(), 3);
+        assert_eq!(ipls.body()[0], Encoding::Latin1.to_byte());
+        assert!(tag.frame(b"TIPL").is_none() && tag.frame(b"TMCL").is_none());
+        assert_eq!(tag.involved_people(),
+                   vec![("producer".to_string(), "Karajan".to_string()),
+                        ("piano".to_string(), "Gould".to_string())]);
+    }
+
+    #[test]
+    fn it_joins_lists_with_different_encodings() {
+        let tmcl = b"\x03piano\0Dvo\xc5\x99\xc3\xa1k".to_vec();
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"TIPL", b"\0producer\0Karajan".to_vec()),
+                                    Frame::new(*b"TMCL", tmcl)]);
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.frame(b"IPLS").unwrap().body()[0], Encoding::Utf16.to_byte());
+        assert_eq!(tag.involved_people(),
+                   vec![("producer".to_string(), "Karajan".to_string()),
+                        ("piano".to_string(), "Dvo\u{159}\u{e1}k".to_string())]);
+    }
+
+    #[test]
+    fn it_keeps_unknown_frames_with_preserve_flags() {
+        let bytes = b"XHD3\x00\x00\x00\x04\xc0\x00body";
+        let frame = Frame::from_reader(&mut &bytes[..], 3).unwrap();
+        let mut tag = tag_with(3, vec![frame]);
+        tag.convert(4).unwrap();
+
+        assert_eq!(tag.frames().len(), 1);
+        assert_eq!(tag.frames()[0].to_bytes(4), b"XHD3\x00\x00\x00\x04\x60\x00body");
+
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.frames()[0].to_bytes(3), &bytes[..]);
+    }
+
+    #[test]
+    fn it_keeps_the_flags_and_group_of_converted_text() {
+        let bytes = b"TPE1\x00\x00\x00\x11\x60\x20\x01\x00Simon/Garfunkel";
+        let frame = Frame::from_reader(&mut &bytes[..], 3).unwrap();
+        let mut title = Frame::new(*b"TIT2", b"\x00Cecilia".to_vec());
+        title.set_group_symbol(Some(1));
+        title.set_read_only(true);
+        let mut tag = tag_with(3, vec![frame, title, Frame::new(*b"SIGN", b"\x01sig".to_vec())]);
+        tag.convert(4).unwrap();
+
+        let frame = tag.frame(b"TPE1").unwrap();
+
+        assert_eq!(frame.body(), b"\x00Simon\0Garfunkel");
+        assert_eq!(frame.group_symbol(), Some(1));
+        assert!(!frame.is_read_only());
+        assert_eq!(frame.to_bytes(4)[8..11], [0x20, 0x40, 0x01]);
+        assert!(!tag.frame(b"TIT2").unwrap().is_read_only());
+        assert!(tag.frame(b"SIGN").is_none());
+    }
+
+    #[test]
+    fn it_joins_v23_dates_into_tdrc() {
+        let mut tag = tag_with(3,
+                               vec![Frame::new(*b"TYER", b"\x001999".to_vec()),
+                                    Frame::new(*b"TDAT", b"\x003112".to_vec()),
+                                    Frame::new(*b"TIME", b"\x002359".to_vec()),
+                                    Frame::new(*b"TORY", b"\x001970".to_vec())]);
+        tag.convert(4).unwrap();
+
+        assert_eq!(tag.frames().len(), 2);
+        assert_eq!(tag.text(b"TDRC"), Some("1999-12-31T23:59".to_string()));
+        assert_eq!(tag.text(b"TDOR"), Some("1970".to_string()));
+    }
+
+    #[test]
+    fn it_splits_tdrc_into_v23_dates() {
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"TDRC", b"\x031999-12-31".to_vec()),
+                                    Frame::new(*b"TDOR", b"\x031970-01-01".to_vec()),
+                                    Frame::new(*b"TDTG", b"\x032016-10-15".to_vec())]);
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.frames().len(), 3);
+        assert_eq!(tag.text(b"TYER"), Some("1999".to_string()));
+        assert_eq!(tag.text(b"TDAT"), Some("3112".to_string()));
+        assert_eq!(tag.text(b"TORY"), Some("1970".to_string()));
+    }
+
+    #[test]
+    fn it_rewrites_genres() {
+        let mut tag = tag_with(3, vec![Frame::new(*b"TCON", b"\0(4)(RX)Eurodisco".to_vec())]);
+        tag.convert(4).unwrap();
+
+        assert_eq!(tag.frame(b"TCON").unwrap().body(), b"\x004\0RX\0Eurodisco");
+
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.text(b"TCON"), Some("(4)(RX)Eurodisco".to_string()));
+    }
+
+    #[test]
+    fn it_joins_and_splits_values() {
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"TPE1", b"\x03Simon\0Garfunkel".to_vec()),
+                                    Frame::new(*b"TIT2", b"\x00Cecilia".to_vec())]);
+        tag.set_v23_separator("; ".to_string());
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.frames()[0].body(), b"\x00Simon; Garfunkel");
+        assert_eq!(tag.text_values(b"TPE1"), vec!["Simon", "Garfunkel"]);
+
+        tag.convert(4).unwrap();
+
+        assert_eq!(tag.frames()[0].body(), b"\x00Simon\0Garfunkel");
+        assert_eq!(tag.frames()[1].body(), b"\x00Cecilia");
+    }
+
+    #[test]
+    fn it_re_encodes_unsupported_text() {
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"TIT2", b"\x03Caf\xc3\xa9".to_vec()),
+                                    Frame::new(*b"TALB", b"\x03\xe2\x84\x969".to_vec())]);
+        tag.convert(3).unwrap();
+
+        assert_eq!(tag.frames()[0].body()[0], Encoding::Latin1.to_byte());
+        assert_eq!(tag.frames()[1].body()[0], Encoding::Utf16.to_byte());
+        assert_eq!(tag.text(b"TIT2"), Some("Café".to_string()));
+        assert_eq!(tag.text(b"TALB"), Some("№9".to_string()));
+    }
+
+    #[test]
+    fn it_converts_equalisations() {
+        let mut tag = tag_with(3, vec![Frame::new(*b"EQUA", vec![16, 0x80, 0x64, 0x80, 0x00])]);
+        tag.convert(4).unwrap();
+
+        assert!(tag.frame(b"EQUA").is_none());
+        assert_eq!(tag.frame(b"EQU2").unwrap().body(), b"\0\0\x00\xc8\x07\x0b");
+
+        tag.convert(3).unwrap();
+
+        assert!(tag.frame(b"EQU2").is_none());
+        assert_eq!(tag.equalisations()[0].points.len(), 1);
+    }
+
+    #[test]
+    fn it_converts_volume_adjustments() {
+        let mut tag = tag_with(4,
+                               vec![Frame::new(*b"RVA2", b"album\0\x01\xfc\x00\x00".to_vec()),
+                                    Frame::new(*b"RVA2", b"track\0\x01\xfb\x00\x00".to_vec())]);
+        tag.convert(3).unwrap();
+
+        let volumes = tag.volume_adjustments();
+
+        assert!(tag.frame(b"RVA2").is_none());
+        assert_eq!(volumes.len(), 1);
+        assert!((volumes[0].channels[0].adjustment + 2.5).abs() < 0.01);
+
+        tag.convert(4).unwrap();
+
+        assert!(tag.frame(b"RVAD").is_none());
+        assert_eq!(tag.volume_adjustments()[0].channels.len(), 2);
+    }
+
+    #[test]
+    fn it_rejects_unsupported_versions() {
+        assert!(Tag::default().convert(2).is_err());
+    }
+}
@@ -1,50 +1,95 @@
 use std::error;
 use std::fmt;
+use std::io;
 
 use header;
 use frame;
 
 /// A list specifying the errors that can be encountered when constructing a tag from a reader.
+///
+/// Offsets are counted in bytes from the start of the tag header, or from the start of the
+/// stream for `Tag::from_seekable_reader`.
 #[derive(Debug)]
 pub enum Error {
     /// There was an error reading the header
-    Header(header::Error),
-    Frame(frame::Error),
+    Header {
+        /// The offset of the header.
+        offset: u64,
+        /// What was wrong with the header.
+        error: header::Error,
+    },
+    /// There was an error reading a frame
+    Frame {
+        /// The offset of the frame header.
+        offset: u64,
+        /// The ID of the frame.
+        frame_id: [u8; 4],
+        /// What was wrong with the frame.
+        error: frame::Error,
+    },
+    /// An error occurred whilst reading the bytes.
+    Io(io::Error),
+    /// The stream ended before the end of the tag.
+    UnexpectedEnd {
+        /// The offset at which the stream ended.
+        offset: u64,
+        /// The number of bytes of the tag that were missing.
+        missing: u64,
+    },
+    /// The tag cannot be converted to, or written in, the requested major version.
+    UnsupportedVersion(u8),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Error::Header(ref err) => write!(f, "Header error: {}", err),
-            Error::Frame(ref err) => write!(f, "Frame error: {}", err),
+            Error::Header { offset, ref error } => {
+                write!(f, "Header error at offset {}: {}", offset, error)
+            }
+            Error::Frame { offset, ref frame_id, ref error } => {
+                write!(f, "Frame error in {} at offset {}: {}",
+                       String::from_utf8_lossy(frame_id), offset, error)
+            }
+            Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::UnexpectedEnd { offset, missing } => {
+                write!(f, "Tag ended {} bytes early at offset {}", missing, offset)
+            }
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported version: 2.{}", version),
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::Header(ref err) => err.description(),
-            Error::Frame(ref err) => err.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
-        match *self {
-            Error::Header(ref err) => Some(err),
-            Error::Frame(ref err) => Some(err),
+            Error::Header { ref error, .. } => Some(error),
+            Error::Frame { ref error, .. } => Some(error),
+            Error::Io(ref err) => Some(err),
+            Error::UnexpectedEnd { .. } => None,
+            Error::UnsupportedVersion(_) => None,
         }
     }
 }
 
-impl From<header::Error> for Error {
-    fn from(err: header::Error) -> Error {
-        Error::Header(err)
+impl Error {
+    /// Moves the offset of the error on by `base` bytes, for a tag that did not start at the
+    /// start of the stream.
+    pub(crate) fn offset_by(self, base: u64) -> Error {
+        match self {
+            Error::Header { offset, error } => Error::Header { offset: offset + base, error },
+            Error::Frame { offset, frame_id, error } => {
+                Error::Frame { offset: offset + base, frame_id, error }
+            }
+            Error::UnexpectedEnd { offset, missing } => {
+                Error::UnexpectedEnd { offset: offset + base, missing }
+            }
+            err => err,
+        }
     }
 }
 
-impl From<frame::Error> for Error {
-    fn from(err: frame::Error) -> Error {
-        Error::Frame(err)
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
     }
 }
@@ -0,0 +1,256 @@
+//! Types and functions for reading the frames of a tag one at a time, without reading their
+//! bodies unless they are asked for.
+
+use std::io::{Read, Seek};
+
+use byte_reader::{ByteReader, Skip};
+use frame;
+use frame::Frame;
+use header::Header;
+use tag::{Error, HEADER_SIZE, Result, Tag};
+
+/// A type representing a frame header found by a `FrameIter`, before its body is read.
+#[derive(Debug, Clone)]
+pub struct FrameEntry {
+    /// The offset of the frame header from the start of the tag header.
+    pub offset: u64,
+    /// The frame header.
+    pub header: frame::header::Header,
+}
+
+/// An iterator over the frames of a tag that only reads the frame headers, skipping over the
+/// bodies, so that large frames such as pictures are never read unless they are asked for with
+/// `FrameIter::read_frame`.
+///
+/// Iteration stops at the first padding byte or at the end of the tag. After an error, no more
+/// frames are returned, since the position of the next frame is not known.
+pub struct FrameIter<R> {
+    reader: ByteReader<R>,
+    header: Header,
+    position: u64,
+    next: u64,
+    done: bool,
+}
+
+impl<R: Read + Seek> FrameIter<R> {
+    /// Construct a new frame iterator from a reader positioned at the start of a tag, reading
+    /// the tag header and skipping any extended header.
+    ///
+    /// # Errors
+    ///
+    /// If there is an error reading the header, then this function will return
+    /// `Error::Header`.
+    ///
+    /// If there is an error reading or seeking in the reader, then this function will return
+    /// `Error::Io`.
+    pub fn new(reader: R) -> Result<FrameIter<R>> {
+        let mut reader = ByteReader::new(reader);
+        let start = reader.skip(0)?;
+
+        let header = Header::from_reader(&mut reader)
+            .map_err(|error| Error::Header { offset: 0, error })?;
+        if header.has_extended_header() {
+            Tag::skip_extended_header(&mut reader, header.version().major)?;
+        }
+        let position = reader.skip(0)? - start;
+
+        Ok(FrameIter { reader, header, position, next: position, done: false })
+    }
+
+    /// Gets the header of the tag.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the whole frame that `entry` describes, which must have been returned by this
+    /// iterator. Frames can be read in any order, and iteration carries on where it was.
+    ///
+    /// # Errors
+    ///
+    /// If there is an error reading the frame, including a frame that runs past the end of the
+    /// tag, then this function will return `Error::Frame`.
+    ///
+    /// If there is an error seeking in the reader, then this function will return `Error::Io`.
+    pub fn read_frame(&mut self, entry: &FrameEntry) -> Result<Frame> {
+        let frame_error = |error| {
+            Error::Frame { offset: entry.offset, frame_id: *entry.header.frame_id(), error }
+        };
+
+        let end = self.end();
+        if entry.offset + HEADER_SIZE as u64 > end {
+            return Err(frame_error(frame::Error::UnexpectedEnd));
+        }
+        self.seek(entry.offset)?;
+
+        let version = self.header.version().major;
+        let mut reader = (&mut self.reader).take(end - entry.offset);
+        let result = Frame::read(&mut reader, version, true, true);
+        self.position = end - reader.limit();
+        let (frame, _) = result.map_err(frame_error)?;
+
+        Ok(frame)
+    }
+
+    /// Gets the offset of the end of the tag from the start of the tag header, not counting
+    /// the footer.
+    fn end(&self) -> u64 {
+        (HEADER_SIZE + self.header.size() as usize) as u64
+    }
+
+    /// Moves the reader to `offset` bytes from the start of the tag header.
+    fn seek(&mut self, offset: u64) -> Result<()> {
+        if offset != self.position {
+            self.reader.skip(offset as i64 - self.position as i64)?;
+            self.position = offset;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the frame header at the next offset, or `None` if the padding or the end of the
+    /// tag has been reached.
+    ///
+    /// A frame whose body runs past the end of the tag is an error.
+    fn read_entry(&mut self) -> Result<Option<FrameEntry>> {
+        let offset = self.next;
+        let end = self.end();
+        if offset + HEADER_SIZE as u64 > end {
+            return Ok(None);
+        }
+
+        self.seek(offset)?;
+        let mut bytes = [0u8; HEADER_SIZE];
+        self.reader.read_exact(&mut bytes)?;
+        self.position += HEADER_SIZE as u64;
+
+        // Padding has been reached, so there are no more frames
+        if bytes[0] == 0 {
+            return Ok(None);
+        }
+
+        let frame_id = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let version = self.header.version().major;
+        let (header, _) = frame::header::Header::from_bytes(&bytes, version, true, true)
+            .map_err(|error| {
+                Error::Frame { offset, frame_id, error: frame::Error::Header(error) }
+            })?;
+
+        self.next = offset + (HEADER_SIZE + header.size() as usize) as u64;
+        if self.next > end {
+            return Err(Error::Frame { offset, frame_id, error: frame::Error::UnexpectedEnd });
+        }
+
+        Ok(Some(FrameEntry { offset, header }))
+    }
+}
+
+impl<R: Read + Seek> Iterator for FrameIter<R> {
+    type Item = Result<FrameEntry>;
+
+    fn next(&mut self) -> Option<Result<FrameEntry>> {
+        if self.done {
+            return None;
+        }
+
+        let entry = self.read_entry().transpose();
+        self.done = !matches!(entry, Some(Ok(_)));
+
+        entry
+    }
+}
+
+#[cfg(test)]
+mod iter_tests {
+    use std::cell::Cell;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::io;
+    use std::rc::Rc;
+
+    use frame;
+    use frame::header::Header;
+    use tag::{Error, FrameEntry, FrameIter};
+
+    /// A reader that counts how many bytes have been read from it.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        read: Rc<Cell<usize>>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.read.set(self.read.get() + read);
+            Ok(read)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(position)
+        }
+    }
+
+    fn tag() -> Vec<u8> {
+        let mut bytes = b"ID3\x03\x00\x00\x00\x00\x20\x22".to_vec();
+        bytes.extend_from_slice(b"APIC\x00\x00\x10\x00\x00\x00");
+        bytes.extend(vec![0xff; 0x1000]);
+        bytes.extend_from_slice(b"TIT2\x00\x00\x00\x06\x00\x00\x00Title");
+        bytes.extend_from_slice(&[0; 8]);
+        bytes
+    }
+
+    #[test]
+    fn it_skips_frame_bodies() {
+        let read = Rc::new(Cell::new(0));
+        let reader = CountingReader { inner: Cursor::new(tag()), read: read.clone() };
+        let mut frames = FrameIter::new(reader).unwrap();
+
+        let entries = frames.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        let ids = entries.iter().map(|entry| *entry.header.frame_id()).collect::<Vec<_>>();
+        let offsets = entries.iter().map(|entry| entry.offset).collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![*b"APIC", *b"TIT2"]);
+        assert_eq!(offsets, vec![10, 0x1014]);
+        assert!(read.get() < 100);
+
+        let title = frames.read_frame(&entries[1]).unwrap();
+        assert_eq!(title.body(), b"\x00Title");
+        assert_eq!(frames.read_frame(&entries[0]).unwrap().body().len(), 0x1000);
+    }
+
+    #[test]
+    fn it_stops_after_an_error() {
+        let mut bytes = tag();
+        bytes[0x1014 + 9] = 0x01;
+        let mut frames = FrameIter::new(Cursor::new(bytes)).unwrap();
+
+        assert!(frames.next().unwrap().is_ok());
+        match frames.next() {
+            Some(Err(Error::Frame { offset: 0x1014, .. })) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn it_rejects_frames_past_the_end_of_the_tag() {
+        let mut bytes = tag();
+        bytes[0x1014 + 7] = 0x20;
+        let mut frames = FrameIter::new(Cursor::new(bytes)).unwrap();
+
+        assert!(frames.next().unwrap().is_ok());
+        match frames.next() {
+            Some(Err(Error::Frame { offset: 0x1014, error: frame::Error::UnexpectedEnd, .. })) => {
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        for &offset in &[0x1014, 0x1028] {
+            let entry = FrameEntry { offset, header: Header::new(*b"TIT2", 6) };
+            match frames.read_frame(&entry) {
+                Err(Error::Frame { offset: error_offset, .. }) => assert_eq!(error_offset, offset),
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Functions for repairing text that was written in a legacy codepage, but marked as ISO-8859-1.
+
+use frame::Frame;
+use frame::codepage::Codepage;
+use frame::encoding::Encoding;
+use tag::Tag;
+
+impl Tag {
+    /// Guesses which legacy codepage the text marked as ISO-8859-1 is really in, looking at the
+    /// text of every frame together so that the whole tag is decoded the same way.
+    ///
+    /// Only frames made entirely of text after their encoding byte (and language, if they have
+    /// one) are looked at: the text information frames (including `TXXX`), `IPLS`, `COMM`,
+    /// `USLT` and `USER`.
+    ///
+    /// See `Codepage::detect` for how the codepage is chosen.
+    pub fn detect_legacy_codepage(&self) -> Option<Codepage> {
+        let strings = self.frames
+            .iter()
+            .filter_map(legacy_text)
+            .flat_map(|text| text.split(|&byte| byte == 0));
+
+        Codepage::detect(strings)
+    }
+
+    /// Decodes the text marked as ISO-8859-1 in `codepage` instead, and re-encodes it in
+    /// `encoding`, so that it is read correctly from then on and written in a Unicode encoding.
+    ///
+    /// The frames looked at are the same as for `Tag::detect_legacy_codepage`. Frames that are
+    /// plain ASCII, or that are not valid in `codepage`, are left untouched. If `encoding` is
+    /// not supported by the tag's version, UTF-16 is used instead.
+    ///
+    /// Re-encoded frames keep their flags and group, except that they are no longer read only.
+    /// Re-encoding a read only frame in a signed group breaks the signature, so the group's
+    /// signature frames (`SIGN`) are removed, as in `Tag::remove_frames`.
+    pub fn reencode_legacy_text(&mut self, codepage: Codepage, encoding: Encoding) {
+        let encoding = encoding.for_version(self.version());
+        self.replace_bodies(|frame| reencode(frame, codepage, encoding));
+    }
+
+    /// Detects the legacy codepage of the tag and, if there is one, re-encodes its text as
+    /// UTF-8 in `v2.4.0` tags or UTF-16 in `v2.3.0` tags, returning the codepage.
+    ///
+    /// This is opt-in because the detection is a guess. See `Tag::detect_legacy_codepage` and
+    /// `Tag::reencode_legacy_text`.
+    pub fn repair_legacy_text(&mut self) -> Option<Codepage> {
+        let codepage = self.detect_legacy_codepage()?;
+        self.reencode_legacy_text(codepage, Encoding::Utf8);
+
+        Some(codepage)
+    }
+}
+
+/// Gets the number of bytes before the text of a frame made entirely of text, or `None` if
+/// the frame has other kinds of fields.
+fn text_offset(frame_id: &[u8; 4]) -> Option<usize> {
+    match frame_id {
+        b"COMM" | b"USLT" | b"USER" => Some(4),
+        b"IPLS" => Some(1),
+        _ if frame_id[0] == b'T' => Some(1),
+        _ => None,
+    }
+}
+
+/// Gets the text of a frame made entirely of text that is marked as ISO-8859-1 and is not
+/// plain ASCII.
+fn legacy_text(frame: &Frame) -> Option<&[u8]> {
+    let body = frame.body();
+    if body.first() != Some(&Encoding::Latin1.to_byte()) {
+        return None;
+    }
+
+    body.get(text_offset(frame.id())?..).filter(|text| !text.is_ascii())
+}
+
+/// Gets the body of a frame with its legacy text re-encoded, or `None` if it has none, or it
+/// is not valid in `codepage`.
+fn reencode(frame: &Frame, codepage: Codepage, encoding: Encoding) -> Option<Vec<u8>> {
+    let text = legacy_text(frame)?;
+    let offset = frame.body().len() - text.len();
+
+    let mut body = vec![encoding.to_byte()];
+    body.extend_from_slice(&frame.body()[1..offset]);
+
+    for (index, string) in text.split(|&byte| byte == 0).enumerate() {
+        if index > 0 {
+            body.extend_from_slice(encoding.terminator());
+        }
+        body.extend(encoding.encode(&codepage.decode(string)?));
+    }
+
+    Some(body)
+}
+
+#[cfg(test)]
+mod legacy_tests {
+    use frame::Frame;
+    use frame::codepage::Codepage;
+    use frame::comment::Comment;
+    use frame::encoding::Encoding;
+    use tag::test_helpers::tag_with;
+
+    #[test]
+    fn it_repairs_legacy_text() {
+        // "Кино", "Группа крови" and a comment described as "Текст"
+        let mut tag = tag_with(4, vec![
+            Frame::new(*b"TPE1", b"\x00\xca\xe8\xed\xee".to_vec()),
+            Frame::new(*b"TIT2", b"\x00\xc3\xf0\xf3\xef\xef\xe0 \xea\xf0\xee\xe2\xe8".to_vec()),
+            Frame::new(*b"COMM", b"\x00rus\xd2\xe5\xea\xf1\xf2\x00Notes".to_vec()),
+            Frame::new(*b"TALB", b"\x00Album".to_vec()),
+        ]);
+
+        assert_eq!(tag.repair_legacy_text(), Some(Codepage::Windows1251));
+        assert_eq!(tag.text(b"TPE1").as_deref(), Some("Кино"));
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Группа крови"));
+        assert_eq!(tag.frames()[0].body()[0], Encoding::Utf8.to_byte());
+
+        let comment = Comment::from_frame(&tag.frames()[2]).unwrap();
+        assert_eq!((comment.description.as_str(), comment.text.as_str()), ("Текст", "Notes"));
+        assert_eq!(tag.frames()[3].body(), b"\x00Album");
+    }
+
+    #[test]
+    fn it_reencodes_as_utf16_in_v23() {
+        let frame = Frame::new(*b"TIT2", b"\x00\x82\xb3\x82\xad\x82\xe7".to_vec());
+        let mut tag = tag_with(3, vec![frame]);
+
+        tag.reencode_legacy_text(Codepage::ShiftJis, Encoding::Utf8);
+
+        assert_eq!(tag.frames()[0].body()[0], Encoding::Utf16.to_byte());
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("さくら"));
+    }
+
+    #[test]
+    fn it_keeps_frame_flags() {
+        let bytes = b"TIT2\x00\x00\x00\x08\xa0\x20\x05\x00\x82\xb3\x82\xad\x82\xe7";
+        let frame = Frame::from_reader(&mut &bytes[..], 3).unwrap();
+        let mut tag = tag_with(3, vec![frame]);
+
+        tag.reencode_legacy_text(Codepage::ShiftJis, Encoding::Utf16);
+
+        let frame = &tag.frames()[0];
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("さくら"));
+        assert!(!frame.is_read_only() && !frame.header().preserve_on_tag_alter());
+        assert_eq!(frame.group_symbol(), Some(0x05));
+        assert_eq!(&frame.to_bytes(3)[8..11], b"\x80\x20\x05");
+    }
+
+    #[test]
+    fn it_breaks_the_signature_of_reencoded_frames() {
+        let mut title = Frame::new(*b"TIT2", b"\x00\x82\xb3\x82\xad\x82\xe7".to_vec());
+        let mut album = Frame::new(*b"TALB", b"\x00Album".to_vec());
+        for frame in [&mut title, &mut album] {
+            frame.set_group_symbol(Some(1));
+            frame.set_read_only(true);
+        }
+        let mut tag = tag_with(4, vec![title, album, Frame::new(*b"SIGN", b"\x01sig".to_vec())]);
+
+        tag.reencode_legacy_text(Codepage::ShiftJis, Encoding::Utf8);
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("さくら"));
+        assert!(tag.frames().iter().all(|frame| !frame.is_read_only()));
+        assert!(tag.frame(b"SIGN").is_none());
+    }
+
+    #[test]
+    fn it_leaves_plain_text_alone() {
+        let mut tag = tag_with(4, vec![Frame::new(*b"TIT2", b"\x00Caf\xe9".to_vec())]);
+
+        assert_eq!(tag.repair_legacy_text(), None);
+        assert_eq!(tag.frames()[0].body(), b"\x00Caf\xe9");
+    }
+}
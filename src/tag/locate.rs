@@ -0,0 +1,240 @@
+//! Types and functions for finding the ID3v2 tags in a file, wherever they are.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use header::Header;
+use tag::{HEADER_SIZE, Result};
+
+/// The number of bytes at the start of a stream that are searched for a tag, in case the tag
+/// follows some junk, such as another container's header.
+pub const MAX_LEADING_JUNK: u64 = 64 * 1024;
+
+/// The size of an ID3v1 tag, which goes after an appended ID3v2 tag.
+const V1_TAG_SIZE: u64 = 128;
+
+/// A type representing where a tag is in a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TagLocation {
+    /// The offset of the tag header from the start of the stream.
+    pub offset: u64,
+    /// The size of the whole tag, including the header and footer.
+    pub size: u64,
+    /// The major version of the tag.
+    pub version: u8,
+    /// Whether or not the tag was found from the end of the stream, by its footer.
+    pub appended: bool,
+}
+
+impl TagLocation {
+    /// Gets the offset of the end of the tag from the start of the stream.
+    pub fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+/// Finds every ID3v2 tag in a stream, returning their locations in the order they appear.
+///
+/// * The first tag is searched for in the first `MAX_LEADING_JUNK` bytes, so that a tag after
+///   some junk is found as well as one at the start.
+/// * Each tag that directly follows another, after at most `MAX_LEADING_JUNK` zero bytes of
+///   extra padding, is found.
+/// * A `v2.4.0` tag appended to the end of the stream, or just before an ID3v1 tag, is found by
+///   its footer ("3DI"), as are any appended tags directly before it.
+///
+/// Only the headers and footers are read, and the reader is left at an unspecified position.
+///
+/// # Errors
+///
+/// If there is an error reading or seeking in the reader, then this function will return
+/// `Error::Io`.
+pub fn locate_tags<R: Read + Seek>(reader: &mut R) -> Result<Vec<TagLocation>> {
+    let mut locations = Vec::new();
+
+    if let Some(mut location) = find_first(reader)? {
+        locations.push(location);
+
+        while let Some(next) = find_following(reader, location.end())? {
+            locations.push(next);
+            location = next;
+        }
+    }
+
+    let length = reader.seek(SeekFrom::End(0))?;
+    let mut end = length;
+    if length >= V1_TAG_SIZE {
+        reader.seek(SeekFrom::Start(length - V1_TAG_SIZE))?;
+        let mut identifier = [0u8; 3];
+        reader.read_exact(&mut identifier)?;
+        if &identifier == b"TAG" {
+            end -= V1_TAG_SIZE;
+        }
+    }
+
+    let mut appended = Vec::new();
+    while let Some(location) = find_appended(reader, end)? {
+        // Stop at the tags that were already found from the start of the stream
+        if let Some(last) = locations.last_mut().filter(|last| location.end() <= last.end()) {
+            last.appended |= last.offset == location.offset;
+            break;
+        }
+        end = location.offset;
+        appended.push(location);
+    }
+
+    locations.extend(appended.into_iter().rev());
+
+    Ok(locations)
+}
+
+/// Finds the first tag with a valid header in the first `MAX_LEADING_JUNK` bytes.
+fn find_first<R: Read + Seek>(reader: &mut R) -> Result<Option<TagLocation>> {
+    reader.seek(SeekFrom::Start(0))?;
+    let mut buffer = Vec::new();
+    reader.take(MAX_LEADING_JUNK + 2).read_to_end(&mut buffer)?;
+
+    for (index, window) in buffer.windows(3).enumerate() {
+        if window == b"ID3" {
+            if let Some(location) = read_location(reader, index as u64)? {
+                return Ok(Some(location));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Finds a tag that starts at `position`, after at most `MAX_LEADING_JUNK` zero bytes.
+fn find_following<R: Read + Seek>(reader: &mut R, position: u64)
+                                  -> Result<Option<TagLocation>> {
+    reader.seek(SeekFrom::Start(position))?;
+    let mut buffer = Vec::new();
+    reader.take(MAX_LEADING_JUNK).read_to_end(&mut buffer)?;
+
+    match buffer.iter().position(|&byte| byte != 0) {
+        Some(zeros) => read_location(reader, position + zeros as u64),
+        None => Ok(None),
+    }
+}
+
+/// Finds a tag that ends at `end` by its footer.
+fn find_appended<R: Read + Seek>(reader: &mut R, end: u64) -> Result<Option<TagLocation>> {
+    if end < 2 * HEADER_SIZE as u64 {
+        return Ok(None);
+    }
+
+    reader.seek(SeekFrom::Start(end - HEADER_SIZE as u64))?;
+    let mut bytes = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut bytes)?;
+
+    let footer = match parse_header(&bytes, true) {
+        Some(footer) if footer.has_footer() => footer,
+        _ => return Ok(None),
+    };
+
+    let size = 2 * HEADER_SIZE as u64 + footer.size() as u64;
+    if size > end {
+        return Ok(None);
+    }
+
+    let location = read_location(reader, end - size)?;
+    Ok(location
+        .filter(|location| location.end() == end)
+        .map(|location| TagLocation { appended: true, ..location }))
+}
+
+/// Reads the location of the tag with a valid header at `offset`, if there is one.
+fn read_location<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<Option<TagLocation>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut bytes = [0u8; HEADER_SIZE];
+    if reader.read_exact(&mut bytes).is_err() {
+        return Ok(None);
+    }
+
+    Ok(parse_header(&bytes, false).map(|header| {
+        let footer = if header.has_footer() { HEADER_SIZE as u64 } else { 0 };
+
+        TagLocation {
+            offset,
+            size: HEADER_SIZE as u64 + header.size() as u64 + footer,
+            version: header.version().major,
+            appended: false,
+        }
+    }))
+}
+
+/// Reads a tag header or footer that looks valid, with a known version and every size byte
+/// synchsafe, so that bytes that happen to start with "ID3" are not taken for a tag.
+fn parse_header(bytes: &[u8; HEADER_SIZE], footer: bool) -> Option<Header> {
+    if bytes[6..].iter().any(|&byte| byte >= 0x80) {
+        return None;
+    }
+
+    let header = if footer {
+        Header::footer_from_bytes(bytes)
+    } else {
+        Header::from_reader(&mut &bytes[..])
+    };
+
+    header.ok()
+        .filter(|header| (2..=4).contains(&header.version().major))
+}
+
+#[cfg(test)]
+mod locate_tests {
+    use std::io::Cursor;
+
+    use super::{MAX_LEADING_JUNK, TagLocation, locate_tags};
+
+    fn tag(version: u8, flags: u8, identifier: &[u8; 3]) -> Vec<u8> {
+        let mut bytes = identifier.to_vec();
+        bytes.extend_from_slice(&[version, 0, flags, 0, 0, 0, 0x10]);
+        bytes.extend_from_slice(b"TIT2\x00\x00\x00\x06\x00\x00\x00Title");
+        bytes
+    }
+
+    fn location(offset: u64, size: u64, version: u8, appended: bool) -> TagLocation {
+        TagLocation { offset, size, version, appended }
+    }
+
+    #[test]
+    fn it_finds_tags_after_junk_and_other_tags() {
+        let mut bytes = b"RIFF junk ID3 but not a tag".to_vec();
+        bytes.extend(tag(3, 0, b"ID3"));
+        bytes.extend_from_slice(&[0; 5]);
+        bytes.extend(tag(4, 0, b"ID3"));
+        bytes.extend_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+
+        assert_eq!(locate_tags(&mut Cursor::new(bytes)).unwrap(),
+                   vec![location(27, 26, 3, false), location(58, 26, 4, false)]);
+    }
+
+    #[test]
+    fn it_stops_at_long_runs_of_zeros() {
+        let mut bytes = tag(3, 0, b"ID3");
+        bytes.extend(vec![0; MAX_LEADING_JUNK as usize]);
+        bytes.extend(tag(3, 0, b"ID3"));
+
+        assert_eq!(locate_tags(&mut Cursor::new(bytes)).unwrap(), vec![location(0, 26, 3, false)]);
+    }
+
+    #[test]
+    fn it_finds_appended_tags() {
+        let mut appended = tag(4, 0x10, b"ID3");
+        appended.extend(tag(4, 0x10, b"3DI")[..10].to_vec());
+
+        let mut bytes = tag(3, 0, b"ID3");
+        bytes.extend_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+        bytes.extend(appended.clone());
+        bytes.extend(appended);
+
+        let expected = vec![location(0, 26, 3, false),
+                            location(30, 36, 4, true),
+                            location(66, 36, 4, true)];
+        assert_eq!(locate_tags(&mut Cursor::new(bytes.clone())).unwrap(), expected);
+
+        let mut v1 = b"TAG".to_vec();
+        v1.extend(vec![0; 125]);
+        bytes.extend(v1);
+        assert_eq!(locate_tags(&mut Cursor::new(bytes)).unwrap(), expected);
+    }
+}
@@ -1,40 +1,167 @@
 //! Types, structs and functions related to reading an ID3v2 tag.
 
-use std::io::Read;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
 use std::result;
 
-use header::Header;
 use frame::Frame;
+use frame::audio_encryption::AudioEncryption;
+use frame::comment::Comment;
+use frame::commercial::{Commercial, Ownership, TermsOfUse};
+use frame::equalisation::Equalisation;
+use frame::genre::Genre;
+use frame::involved_people::InvolvedPeople;
+use frame::link::{Link, LinkResolver};
+use frame::mpeg_location::MpegLocationLookup;
+use frame::music_cd::MusicCdIdentifier;
+use frame::position::Position;
+use frame::replay_gain::{CONVENTIONS, Convention, ReplayGain, USER_TEXT_KEYS};
+use frame::reverb::Reverb;
+use frame::signature::{Signature, SignatureVerifier};
+use frame::streaming::{AudioSeekPointIndex, RecommendedBuffer, SeekOffset};
+use frame::text::{Text, V23_MULTI_VALUE_FRAMES};
+use frame::timestamp::Timestamp;
+use frame::timing::{EventTimingCodes, PositionSync, SynchronisedTempo};
+use frame::unknown::FILE_DEPENDENT_FRAMES;
+use frame::user_text::UserText;
+use frame::volume::VolumeAdjustment;
+use header::Header;
 
+pub use self::borrowed::TagRef;
 pub use self::error::Error;
+pub use self::iter::{FrameEntry, FrameIter};
+pub use self::locate::{MAX_LEADING_JUNK, TagLocation, locate_tags};
+pub use self::warning::Warning;
 
+mod borrowed;
+mod convert;
 mod error;
+mod iter;
+mod legacy;
+mod locate;
+mod read;
+#[cfg(test)]
+mod test_helpers;
+mod warning;
+mod write;
 
 /// A specialised `Result` type for tag reading operations.
 pub type Result<T> = result::Result<T, Error>;
 
+/// The separator used to join several values of a text information frame in a `v2.3.0` tag,
+/// unless another is set with `Tag::set_v23_separator`.
+pub const DEFAULT_V23_SEPARATOR: &str = "/";
+
+/// The size of a tag or frame header.
+const HEADER_SIZE: usize = 10;
+
+/// Options that control how a tag is read by `Tag::from_reader_with_options`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// Whether or not to stop at the first malformed part of the tag, rather than working
+    /// around it and returning a warning. Defaults to true.
+    pub strict: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions { strict: true }
+    }
+}
+
+/// Options that control how a tag is written by `Tag::write_to_with_options`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WriteOptions {
+    /// Whether or not to write a footer after the frames, which is needed for a tag appended
+    /// to the end of a file. _Only `v2.4.0`._ Defaults to false.
+    pub footer: bool,
+}
+
 /// A type representing an ID3v2 tag.
 ///
 /// # Reference
 ///
 /// [ID3v2.3 Informal Standard](http://id3.org/id3v2.3.0)
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Tag {
     header: Header,
-    frame: Frame,
+    frames: Vec<Frame>,
+    v23_separator: String,
+    altered: bool,
+}
+
+impl Default for Tag {
+    fn default() -> Tag {
+        Tag {
+            header: Default::default(),
+            frames: Vec::new(),
+            v23_separator: DEFAULT_V23_SEPARATOR.to_string(),
+            altered: false,
+        }
+    }
 }
 
 impl Tag {
     /// Construct a new tag from a reader.
     ///
+    /// Reading stops at the first padding byte, and the reader is always left at the end of the
+    /// tag. Frame sizes written by old versions of iTunes are detected, as described for
+    /// `Tag::from_reader_with_options`.
+    ///
     /// # Errors
     ///
     /// If there is an error reading the header, then this function will return
     /// `Error::Header`.
-    pub fn from_reader<R: Read>(mut reader: &mut R) -> Result<Tag> {
-        let frame = try!(Frame::from_reader(&mut reader, header.version().major));
+    ///
+    /// If there is an error reading a frame, then this function will return `Error::Frame`,
+    /// with the offset of the frame from the start of the tag.
+    ///
+    /// If the stream ends before the end of the tag, then this function will return
+    /// `Error::UnexpectedEnd`. Unlike this function, `TagRef::from_bytes` keeps the frames
+    /// before the end of a cut off tag, and `Tag::from_reader_with_options` does too when it is
+    /// not strict.
+    ///
+    /// If there is an error reading from the reader, then this function will return
+    /// `Error::Io`.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Tag> {
+        Tag::from_reader_with_options(reader, ParseOptions::default()).map(|(tag, _)| tag)
+    }
+
+    /// Construct a new tag from a reader, following any seek frame (`SEEK`) to the tags
+    /// appended later in the stream and merging them in.
+    ///
+    /// A seek frame only gives the minimum offset to the next tag, so the stream is searched
+    /// from there for the next valid header. Frames in an appended tag replace every frame with
+    /// the same ID in the tags before it, and the seek frames themselves are removed. If no tag
+    /// is found after a seek frame, the tags read so far are returned.
+    ///
+    /// The reader is left at the end of the last tag that was read.
+    ///
+    /// # Errors
+    ///
+    /// See `Tag::from_reader`. Errors reading an appended tag are only returned once its header
+    /// has been read successfully.
+    pub fn from_seekable_reader<R: Read + Seek>(reader: &mut R) -> Result<Tag> {
+        let start = reader.stream_position()?;
+        let mut tag = Tag::from_reader(reader).map_err(|err| err.offset_by(start))?;
+        let mut seek = tag.seek_offset();
+
+        while let Some(SeekOffset { offset }) = seek {
+            let end = reader.stream_position()?;
+            let appended = match Tag::find_appended(reader, end + offset as u64)? {
+                Some(appended) => appended,
+                None => {
+                    reader.seek(SeekFrom::Start(end))?;
+                    break;
+                }
+            };
+
+            seek = appended.seek_offset();
+            tag.merge(appended);
+        }
 
-        let tag = Tag { header: header, frame: frame };
+        tag.remove_frames(b"SEEK");
+        tag.altered = false;
 
         Ok(tag)
     }
@@ -43,4 +170,970 @@ impl Tag {
     pub fn header(&self) -> &Header {
         &self.header
     }
+
+    /// Gets the major version of the tag.
+    pub fn version(&self) -> u8 {
+        self.header.version().major
+    }
+
+    /// Gets all of the frames in the tag, in the order they were read or added.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Gets the first frame with the given ID.
+    pub fn frame(&self, frame_id: &[u8; 4]) -> Option<&Frame> {
+        self.frames.iter().find(|frame| frame.id() == frame_id)
+    }
+
+    /// Adds a frame to the end of the tag.
+    pub fn add_frame(&mut self, frame: Frame) {
+        self.frames.push(frame);
+        self.altered = true;
+    }
+
+    /// Removes every frame with the given ID, returning the removed frames.
+    ///
+    /// If a read only frame in a signed group is removed, the signature no longer matches, so
+    /// the signature frames (`SIGN`) for the group are removed too, and the rest of the group
+    /// is no longer marked as read only. This applies to every method that replaces frames.
+    pub fn remove_frames(&mut self, frame_id: &[u8; 4]) -> Vec<Frame> {
+        self.retain_frames(|frame| frame.id() != frame_id)
+    }
+
+    /// Gets whether or not the frames of the tag have been altered since it was read.
+    ///
+    /// Unknown frames marked to be discarded when the tag is altered are left out when an
+    /// altered tag is written. See `Tag::write_to`.
+    pub fn is_altered(&self) -> bool {
+        self.altered
+    }
+
+    /// Removes the frames that describe the audio rather than the recording, returning the
+    /// removed frames. This should be done when the audio is altered, for example trimmed or
+    /// re-encoded, but not when it is replaced completely.
+    ///
+    /// The frames removed are those in `FILE_DEPENDENT_FRAMES`, and unknown frames marked to be
+    /// discarded when the file is altered.
+    pub fn remove_file_dependent_frames(&mut self) -> Vec<Frame> {
+        let version = self.version();
+
+        self.retain_frames(|frame| {
+            let discard = match frame.unknown() {
+                Some(unknown) => !unknown.preserve_on_file_alter(version),
+                None => FILE_DEPENDENT_FRAMES.contains(&frame.id()),
+            };
+
+            !discard
+        })
+    }
+
+    /// Gets the separator used to join several values of a text information frame in a
+    /// `v2.3.0` tag.
+    pub fn v23_separator(&self) -> &str {
+        &self.v23_separator
+    }
+
+    /// Sets the separator used to join several values of a text information frame in a `v2.3.0`
+    /// tag, such as `;` instead of the default `/`.
+    ///
+    /// The separator is also used to split the values of the frames listed in
+    /// `V23_MULTI_VALUE_FRAMES` when reading them.
+    pub fn set_v23_separator(&mut self, separator: String) {
+        self.v23_separator = separator;
+    }
+
+    /// Gets the first value of the first text information frame with the given ID.
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn text(&self, frame_id: &[u8; 4]) -> Option<String> {
+        self.frame(frame_id)
+            .and_then(|frame| Text::from_frame(frame).ok())
+            .map(|text| text.text().to_string())
+    }
+
+    /// Replaces every frame with the given ID with a single text information frame.
+    pub fn set_text(&mut self, frame_id: &[u8; 4], text: String) {
+        self.set_text_values(frame_id, vec![text]);
+    }
+
+    /// Gets every value of the first text information frame with the given ID.
+    ///
+    /// In a `v2.3.0` tag, the frames listed in `V23_MULTI_VALUE_FRAMES` are split on the
+    /// separator returned by `Tag::v23_separator`. Frames that cannot be decoded are ignored.
+    pub fn text_values(&self, frame_id: &[u8; 4]) -> Vec<String> {
+        match self.frame(frame_id).and_then(|frame| Text::from_frame(frame).ok()) {
+            Some(ref text) if self.version() == 3 && V23_MULTI_VALUE_FRAMES.contains(&frame_id) => {
+                text.split(&self.v23_separator).values().to_vec()
+            }
+            Some(text) => text.values().to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Replaces every frame with the given ID with a single text information frame holding
+    /// several values.
+    ///
+    /// In a `v2.3.0` tag the values are joined with the separator returned by
+    /// `Tag::v23_separator`.
+    pub fn set_text_values(&mut self, frame_id: &[u8; 4], values: Vec<String>) {
+        let version = self.version();
+        let mut text = Text::for_version(values, version);
+
+        if version == 3 {
+            text = text.join(&self.v23_separator);
+        }
+
+        self.remove_frames(frame_id);
+        self.add_frame(text.to_frame(*frame_id));
+    }
+
+    /// Gets the genres of the content type frame (`TCON`), parsed in the form used by the
+    /// tag's version.
+    pub fn genres(&self) -> Vec<Genre> {
+        if self.version() == 3 {
+            self.text(b"TCON").map(|text| Genre::parse_v23(&text)).unwrap_or_default()
+        } else {
+            Genre::parse_v24(&self.text_values(b"TCON"))
+        }
+    }
+
+    /// Sets the genres of the content type frame (`TCON`), written in the form used by the
+    /// tag's version.
+    pub fn set_genres(&mut self, genres: &[Genre]) {
+        if self.version() == 3 {
+            self.set_text(b"TCON", Genre::to_v23(genres));
+        } else {
+            self.set_text_values(b"TCON", Genre::to_v24(genres));
+        }
+    }
+
+    /// Gets the track number and the total number of tracks (`TRCK`).
+    pub fn track(&self) -> Option<(u32, Option<u32>)> {
+        self.position(b"TRCK").map(|position| position.pair())
+    }
+
+    /// Gets the total number of tracks (`TRCK`).
+    pub fn track_total(&self) -> Option<u32> {
+        self.track().and_then(|(_, total)| total)
+    }
+
+    /// Sets the track number and the total number of tracks (`TRCK`), keeping any leading zeros
+    /// that the previous value had.
+    pub fn set_track(&mut self, number: u32, total: Option<u32>) {
+        self.set_position(b"TRCK", number, total);
+    }
+
+    /// Gets the disc number and the total number of discs (`TPOS`).
+    pub fn disc(&self) -> Option<(u32, Option<u32>)> {
+        self.position(b"TPOS").map(|position| position.pair())
+    }
+
+    /// Gets the total number of discs (`TPOS`).
+    pub fn disc_total(&self) -> Option<u32> {
+        self.disc().and_then(|(_, total)| total)
+    }
+
+    /// Sets the disc number and the total number of discs (`TPOS`), keeping any leading zeros
+    /// that the previous value had.
+    pub fn set_disc(&mut self, number: u32, total: Option<u32>) {
+        self.set_position(b"TPOS", number, total);
+    }
+
+    /// Gets the recording time (`TDRC`).
+    ///
+    /// In `v2.3.0` tags this is joined together from `TYER`, `TDAT` and `TIME`, falling back to
+    /// `TRDA` if it happens to hold a timestamp.
+    pub fn recording_time(&self) -> Option<Timestamp> {
+        self.timestamp(b"TDRC").or_else(|| self.v23_recording_time())
+    }
+
+    /// Sets the recording time, using `TDRC` in `v2.4.0` tags or `TYER`, `TDAT` and `TIME` in
+    /// `v2.3.0` tags.
+    pub fn set_recording_time(&mut self, timestamp: Timestamp) {
+        if self.version() == 3 {
+            let (year, date, time) = timestamp.to_v23();
+
+            self.set_text(b"TYER", year);
+            for (frame_id, text) in [(b"TDAT", date), (b"TIME", time)] {
+                self.remove_frames(frame_id);
+                if let Some(text) = text {
+                    self.set_text(frame_id, text);
+                }
+            }
+        } else {
+            self.set_text(b"TDRC", timestamp.to_string());
+        }
+    }
+
+    /// Gets the original release time (`TDOR`, or the year in `TORY` in `v2.3.0` tags).
+    pub fn original_release_time(&self) -> Option<Timestamp> {
+        self.timestamp(b"TDOR").or_else(|| {
+            self.text(b"TORY").and_then(|year| Timestamp::from_v23(&year, None, None).ok())
+        })
+    }
+
+    /// Sets the original release time, using `TDOR` in `v2.4.0` tags or `TORY` (which can only
+    /// hold the year) in `v2.3.0` tags.
+    pub fn set_original_release_time(&mut self, timestamp: Timestamp) {
+        if self.version() == 3 {
+            self.set_text(b"TORY", timestamp.to_v23().0);
+        } else {
+            self.set_text(b"TDOR", timestamp.to_string());
+        }
+    }
+
+    /// Gets the release time (`TDRL`). _Only `v2.4.0`._
+    pub fn release_time(&self) -> Option<Timestamp> {
+        self.timestamp(b"TDRL")
+    }
+
+    /// Gets the encoding time (`TDEN`). _Only `v2.4.0`._
+    pub fn encoding_time(&self) -> Option<Timestamp> {
+        self.timestamp(b"TDEN")
+    }
+
+    /// Gets the tagging time (`TDTG`). _Only `v2.4.0`._
+    pub fn tagging_time(&self) -> Option<Timestamp> {
+        self.timestamp(b"TDTG")
+    }
+
+    /// Gets the `(role, name)` pairs of the involved people list (`IPLS` or `TIPL`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn involved_people(&self) -> Vec<(String, String)> {
+        self.people_in(&[b"IPLS", b"TIPL"])
+    }
+
+    /// Gets the `(instrument, musician)` pairs of the musician credits list (`TMCL`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn musician_credits(&self) -> Vec<(String, String)> {
+        self.people_in(&[b"TMCL"])
+    }
+
+    /// Gets every relative volume adjustment (`RVAD` or `RVA2`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn volume_adjustments(&self) -> Vec<VolumeAdjustment> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"RVAD" || frame.id() == b"RVA2")
+            .filter_map(|frame| VolumeAdjustment::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Gets the relative volume adjustment with the given identification, such as `track` or
+    /// `album`.
+    ///
+    /// Since `RVAD` has no identification, it matches any identification.
+    pub fn volume_adjustment(&self, identification: &str) -> Option<VolumeAdjustment> {
+        self.volume_adjustments().into_iter().find(|volume| {
+            volume.identification.is_empty() || volume.identification == identification
+        })
+    }
+
+    /// Adds a relative volume adjustment, as an `RVA2` frame in `v2.4.0` tags.
+    ///
+    /// In `v2.3.0` tags only one `RVAD` frame is allowed, so it replaces any existing one.
+    pub fn add_volume_adjustment(&mut self, volume: &VolumeAdjustment) {
+        let version = self.version();
+
+        if version == 3 {
+            self.remove_frames(b"RVAD");
+        } else {
+            self.retain_frames(|frame| {
+                frame.id() != b"RVA2" ||
+                VolumeAdjustment::from_frame(frame)
+                    .map_or(true, |existing| existing.identification != volume.identification)
+            });
+        }
+
+        self.add_frame(volume.to_frame(version));
+    }
+
+    /// Gets every equalisation curve (`EQUA` or `EQU2`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn equalisations(&self) -> Vec<Equalisation> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"EQUA" || frame.id() == b"EQU2")
+            .filter_map(|frame| Equalisation::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Adds an equalisation curve, as an `EQU2` frame in `v2.4.0` tags.
+    ///
+    /// In `v2.3.0` tags only one `EQUA` frame is allowed, so it replaces any existing one. In
+    /// `v2.4.0` tags it replaces any curve with the same interpolation method and
+    /// identification.
+    pub fn add_equalisation(&mut self, equalisation: &Equalisation) {
+        let version = self.version();
+
+        if version == 3 {
+            self.remove_frames(b"EQUA");
+        } else {
+            self.retain_frames(|frame| {
+                frame.id() != b"EQU2" ||
+                Equalisation::from_frame(frame).map_or(true, |existing| {
+                    existing.interpolation != equalisation.interpolation ||
+                    existing.identification != equalisation.identification
+                })
+            });
+        }
+
+        self.add_frame(equalisation.to_frame(version));
+    }
+
+    /// Gets the reverb (`RVRB`).
+    pub fn reverb(&self) -> Option<Reverb> {
+        self.frame(b"RVRB").and_then(|frame| Reverb::from_frame(frame).ok())
+    }
+
+    /// Sets the reverb (`RVRB`), replacing any existing one.
+    pub fn set_reverb(&mut self, reverb: &Reverb) {
+        self.remove_frames(b"RVRB");
+        self.add_frame(reverb.to_frame());
+    }
+
+    /// Gets the event timing codes (`ETCO`).
+    pub fn event_timing_codes(&self) -> Option<EventTimingCodes> {
+        self.frame(b"ETCO").and_then(|frame| EventTimingCodes::from_frame(frame).ok())
+    }
+
+    /// Sets the event timing codes (`ETCO`), replacing any existing ones.
+    pub fn set_event_timing_codes(&mut self, codes: &EventTimingCodes) {
+        self.remove_frames(b"ETCO");
+        self.add_frame(codes.to_frame());
+    }
+
+    /// Gets the synchronised tempo codes (`SYTC`).
+    pub fn synchronised_tempo(&self) -> Option<SynchronisedTempo> {
+        self.frame(b"SYTC").and_then(|frame| SynchronisedTempo::from_frame(frame).ok())
+    }
+
+    /// Sets the synchronised tempo codes (`SYTC`), replacing any existing ones.
+    pub fn set_synchronised_tempo(&mut self, tempo: &SynchronisedTempo) {
+        self.remove_frames(b"SYTC");
+        self.add_frame(tempo.to_frame());
+    }
+
+    /// Gets the MPEG location lookup table (`MLLT`).
+    pub fn mpeg_location_lookup(&self) -> Option<MpegLocationLookup> {
+        self.frame(b"MLLT").and_then(|frame| MpegLocationLookup::from_frame(frame).ok())
+    }
+
+    /// Sets the MPEG location lookup table (`MLLT`), replacing any existing one.
+    pub fn set_mpeg_location_lookup(&mut self, table: &MpegLocationLookup) {
+        self.remove_frames(b"MLLT");
+        self.add_frame(table.to_frame());
+    }
+
+    /// Gets the music CD identifier (`MCDI`).
+    pub fn music_cd_identifier(&self) -> Option<MusicCdIdentifier> {
+        self.frame(b"MCDI").and_then(|frame| MusicCdIdentifier::from_frame(frame).ok())
+    }
+
+    /// Sets the music CD identifier (`MCDI`), replacing any existing one.
+    pub fn set_music_cd_identifier(&mut self, identifier: &MusicCdIdentifier) {
+        self.remove_frames(b"MCDI");
+        self.add_frame(identifier.to_frame());
+    }
+
+    /// Gets the recommended buffer size (`RBUF`).
+    pub fn recommended_buffer(&self) -> Option<RecommendedBuffer> {
+        self.frame(b"RBUF").and_then(|frame| RecommendedBuffer::from_frame(frame).ok())
+    }
+
+    /// Sets the recommended buffer size (`RBUF`), replacing any existing one.
+    pub fn set_recommended_buffer(&mut self, buffer: &RecommendedBuffer) {
+        self.remove_frames(b"RBUF");
+        self.add_frame(buffer.to_frame());
+    }
+
+    /// Gets the offset to the next tag in the stream (`SEEK`).
+    pub fn seek_offset(&self) -> Option<SeekOffset> {
+        self.frame(b"SEEK").and_then(|frame| SeekOffset::from_frame(frame).ok())
+    }
+
+    /// Sets the offset to the next tag in the stream (`SEEK`), replacing any existing one.
+    pub fn set_seek_offset(&mut self, seek: &SeekOffset) {
+        self.remove_frames(b"SEEK");
+        self.add_frame(seek.to_frame());
+    }
+
+    /// Gets the audio seek point index (`ASPI`).
+    pub fn audio_seek_point_index(&self) -> Option<AudioSeekPointIndex> {
+        self.frame(b"ASPI").and_then(|frame| AudioSeekPointIndex::from_frame(frame).ok())
+    }
+
+    /// Sets the audio seek point index (`ASPI`), replacing any existing one.
+    pub fn set_audio_seek_point_index(&mut self, index: &AudioSeekPointIndex) {
+        self.remove_frames(b"ASPI");
+        self.add_frame(index.to_frame());
+    }
+
+    /// Gets every audio encryption frame (`AENC`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn audio_encryptions(&self) -> Vec<AudioEncryption> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"AENC")
+            .filter_map(|frame| AudioEncryption::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Adds an audio encryption frame (`AENC`), replacing any existing one with the same owner.
+    pub fn add_audio_encryption(&mut self, encryption: &AudioEncryption) {
+        self.retain_frames(|frame| {
+            frame.id() != b"AENC" ||
+            AudioEncryption::from_frame(frame).map_or(true, |existing| {
+                existing.owner != encryption.owner
+            })
+        });
+
+        self.add_frame(encryption.to_frame());
+    }
+
+    /// Gets every linked information frame (`LINK`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn links(&self) -> Vec<Link> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"LINK")
+            .filter_map(|frame| Link::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Adds a linked information frame (`LINK`).
+    pub fn add_link(&mut self, link: &Link) {
+        self.add_frame(link.to_frame());
+    }
+
+    /// Gets every signature frame (`SIGN`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn signatures(&self) -> Vec<Signature> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"SIGN")
+            .filter_map(|frame| Signature::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Adds a signature frame (`SIGN`), unless an identical one exists, and marks every frame
+    /// in the signed group as read only.
+    ///
+    /// The signature should be made from the bytes returned by `Tag::group_bytes`.
+    pub fn add_signature(&mut self, signature: &Signature) {
+        for frame in &mut self.frames {
+            if frame.group_symbol() == Some(signature.group_symbol) && !frame.is_read_only() {
+                frame.set_read_only(true);
+                self.altered = true;
+            }
+        }
+
+        if !self.signatures().contains(signature) {
+            self.add_frame(signature.to_frame());
+        }
+    }
+
+    /// Gets the canonical bytes of the group of frames with the given symbol, which are what a
+    /// signature frame (`SIGN`) for the group signs.
+    ///
+    /// Each frame in the group, in the order they appear in the tag, is written as its ID,
+    /// followed by the size of its body as a 32 bit big endian integer, followed by its body.
+    /// Signature frames and the group symbol itself are not included.
+    pub fn group_bytes(&self, group_symbol: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for frame in &self.frames {
+            if frame.group_symbol() == Some(group_symbol) && frame.id() != b"SIGN" {
+                bytes.extend_from_slice(frame.id());
+                bytes.extend_from_slice(&(frame.body().len() as u32).to_be_bytes());
+                bytes.extend_from_slice(frame.body());
+            }
+        }
+
+        bytes
+    }
+
+    /// Checks every signature frame (`SIGN`) using `verifier`, returning each signature with
+    /// whether or not it is valid.
+    pub fn verify_signatures<V: SignatureVerifier>(&self, verifier: &mut V)
+                                                   -> Vec<(Signature, bool)> {
+        self.signatures()
+            .into_iter()
+            .map(|signature| {
+                let valid = verifier.verify(&self.group_bytes(signature.group_symbol), &signature);
+                (signature, valid)
+            })
+            .collect()
+    }
+
+    /// Gets the frames that every linked information frame points to, using `resolver` to
+    /// fetch them.
+    ///
+    /// Links that cannot be resolved are ignored.
+    pub fn linked_frames<R: LinkResolver>(&self, resolver: &mut R) -> Vec<Frame> {
+        self.links().iter().filter_map(|link| resolver.resolve(link)).collect()
+    }
+
+    /// Gets the position synchronisation (`POSS`).
+    pub fn position_sync(&self) -> Option<PositionSync> {
+        self.frame(b"POSS").and_then(|frame| PositionSync::from_frame(frame).ok())
+    }
+
+    /// Sets the position synchronisation (`POSS`), replacing any existing one.
+    pub fn set_position_sync(&mut self, position: &PositionSync) {
+        self.remove_frames(b"POSS");
+        self.add_frame(position.to_frame());
+    }
+
+    /// Gets the file owner or licensee (`TOWN`).
+    pub fn file_owner(&self) -> Option<String> {
+        self.text(b"TOWN")
+    }
+
+    /// Sets the file owner or licensee (`TOWN`).
+    pub fn set_file_owner(&mut self, owner: String) {
+        self.set_text(b"TOWN", owner);
+    }
+
+    /// Gets the ownership (`OWNE`).
+    pub fn ownership(&self) -> Option<Ownership> {
+        self.frame(b"OWNE").and_then(|frame| Ownership::from_frame(frame).ok())
+    }
+
+    /// Sets the ownership (`OWNE`), replacing any existing one.
+    pub fn set_ownership(&mut self, ownership: &Ownership) {
+        self.remove_frames(b"OWNE");
+        self.add_frame(ownership.to_frame());
+    }
+
+    /// Gets every commercial frame (`COMR`).
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn commercials(&self) -> Vec<Commercial> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"COMR")
+            .filter_map(|frame| Commercial::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Adds a commercial frame (`COMR`). Several are allowed, as long as they differ.
+    pub fn add_commercial(&mut self, commercial: &Commercial) {
+        self.add_frame(commercial.to_frame());
+    }
+
+    /// Gets the terms of use (`USER`) in every language.
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn terms_of_use(&self) -> Vec<TermsOfUse> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"USER")
+            .filter_map(|frame| TermsOfUse::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Sets the terms of use (`USER`), replacing any existing terms in the same language.
+    pub fn set_terms_of_use(&mut self, terms: &TermsOfUse) {
+        self.retain_frames(|frame| {
+            frame.id() != b"USER" ||
+            TermsOfUse::from_frame(frame).map_or(true, |existing| {
+                existing.language != terms.language
+            })
+        });
+
+        self.add_frame(terms.to_frame());
+    }
+
+    /// Gets the ReplayGain metadata, reading each value from the first convention in
+    /// `CONVENTIONS` that holds it.
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn replay_gain(&self) -> ReplayGain {
+        CONVENTIONS
+            .iter()
+            .fold(ReplayGain::default(),
+                  |gain, &convention| gain.or(self.replay_gain_in(convention)))
+    }
+
+    /// Gets the ReplayGain metadata stored using a single convention.
+    ///
+    /// Frames that cannot be decoded are ignored.
+    pub fn replay_gain_in(&self, convention: Convention) -> ReplayGain {
+        match convention {
+            Convention::UserText => ReplayGain::from_user_text(&self.user_texts()),
+            Convention::VolumeAdjustment => {
+                let volumes: Vec<VolumeAdjustment> = self.frames
+                    .iter()
+                    .filter(|frame| frame.id() == b"RVA2")
+                    .filter_map(|frame| VolumeAdjustment::from_frame(frame).ok())
+                    .collect();
+
+                ReplayGain::from_volume_adjustments(&volumes)
+            }
+            Convention::Rgad => {
+                self.frame(b"RGAD")
+                    .and_then(|frame| ReplayGain::from_rgad(frame.body()).ok())
+                    .unwrap_or_default()
+            }
+            Convention::ITunNorm => {
+                self.frames
+                    .iter()
+                    .filter(|frame| frame.id() == b"COMM")
+                    .filter_map(|frame| Comment::from_frame(frame).ok())
+                    .filter(|comment| comment.description == "iTunNORM")
+                    .filter_map(|comment| ReplayGain::from_itunnorm(&comment.text))
+                    .next()
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Sets the ReplayGain metadata using each of the given conventions, replacing anything
+    /// they held before. Values that are missing are removed.
+    ///
+    /// `Convention::VolumeAdjustment` is ignored in `v2.3.0` tags, since `RVAD` cannot tell a
+    /// track gain from an album gain.
+    pub fn set_replay_gain(&mut self, gain: &ReplayGain, conventions: &[Convention]) {
+        let version = self.version();
+
+        for &convention in conventions {
+            match convention {
+                Convention::UserText => {
+                    self.retain_frames(|frame| {
+                        frame.id() != b"TXXX" ||
+                        UserText::from_frame(frame).map_or(true, |text| {
+                            !USER_TEXT_KEYS
+                                .iter()
+                                .any(|key| key.eq_ignore_ascii_case(&text.description))
+                        })
+                    });
+
+                    for text in gain.to_user_text(version) {
+                        self.add_frame(text.to_frame());
+                    }
+                }
+                Convention::VolumeAdjustment if version != 3 => {
+                    self.retain_frames(|frame| {
+                        frame.id() != b"RVA2" ||
+                        VolumeAdjustment::from_frame(frame).map_or(true, |volume| {
+                            !["track", "album"].iter().any(|identification| {
+                                volume.identification.eq_ignore_ascii_case(identification)
+                            })
+                        })
+                    });
+
+                    for volume in gain.to_volume_adjustments() {
+                        self.add_frame(volume.to_frame(version));
+                    }
+                }
+                Convention::VolumeAdjustment => {}
+                Convention::Rgad => {
+                    self.remove_frames(b"RGAD");
+
+                    if gain.track_peak.is_some() || gain.track_gain.is_some() ||
+                       gain.album_gain.is_some() {
+                        self.add_frame(Frame::new(*b"RGAD", gain.to_rgad()));
+                    }
+                }
+                Convention::ITunNorm => {
+                    self.retain_frames(|frame| {
+                        frame.id() != b"COMM" ||
+                        Comment::from_frame(frame).map_or(true, |comment| {
+                            comment.description != "iTunNORM"
+                        })
+                    });
+
+                    if let Some(text) = gain.to_itunnorm() {
+                        let comment = Comment::for_version(*b"eng",
+                                                           "iTunNORM".to_string(),
+                                                           text,
+                                                           version);
+                        self.add_frame(comment.to_frame());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets every user defined text information frame (`TXXX`) that can be decoded.
+    fn user_texts(&self) -> Vec<UserText> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.id() == b"TXXX")
+            .filter_map(|frame| UserText::from_frame(frame).ok())
+            .collect()
+    }
+
+    /// Gets the people from every involved people list frame with one of the given IDs.
+    fn people_in(&self, frame_ids: &[&[u8; 4]]) -> Vec<(String, String)> {
+        self.frames
+            .iter()
+            .filter(|frame| frame_ids.contains(&frame.id()))
+            .filter_map(|frame| InvolvedPeople::from_frame(frame).ok())
+            .flat_map(|list| list.people().to_vec())
+            .collect()
+    }
+
+    /// Gets the position held in the text information frame with the given ID.
+    fn position(&self, frame_id: &[u8; 4]) -> Option<Position> {
+        self.text(frame_id).and_then(|text| text.parse().ok())
+    }
+
+    /// Sets the position held in the text information frame with the given ID, in the same
+    /// format as the previous position.
+    fn set_position(&mut self, frame_id: &[u8; 4], number: u32, total: Option<u32>) {
+        let mut position = self.position(frame_id).unwrap_or_default();
+        position.set_number(number);
+        position.set_total(total);
+
+        self.set_text(frame_id, position.to_string());
+    }
+
+    /// Gets the timestamp held in the text information frame with the given ID.
+    fn timestamp(&self, frame_id: &[u8; 4]) -> Option<Timestamp> {
+        self.text(frame_id).and_then(|text| text.parse().ok())
+    }
+
+    /// Gets the recording time from the `v2.3.0` `TYER`, `TDAT` and `TIME` frames, or `TRDA`.
+    fn v23_recording_time(&self) -> Option<Timestamp> {
+        match self.text(b"TYER") {
+            Some(year) => {
+                let date = self.text(b"TDAT");
+                let time = self.text(b"TIME");
+
+                Timestamp::from_v23(&year, date.as_deref(), time.as_deref()).ok()
+            }
+            None => self.timestamp(b"TRDA"),
+        }
+    }
+
+    /// Reads the first tag with a valid header at or after `position`, or `None` if the end of
+    /// the stream is reached first.
+    fn find_appended<R: Read + Seek>(reader: &mut R, mut position: u64) -> Result<Option<Tag>> {
+        let mut buffer = Vec::with_capacity(4096);
+
+        loop {
+            reader.seek(SeekFrom::Start(position))?;
+            buffer.clear();
+            reader.take(4096).read_to_end(&mut buffer)?;
+
+            let found = buffer.windows(3).position(|window| window == b"ID3");
+            let index = match found {
+                Some(index) => index,
+                // Keep the last two bytes in case they are the start of an identifier
+                None if buffer.len() == 4096 => {
+                    position += 4094;
+                    continue;
+                }
+                None => return Ok(None),
+            };
+
+            let start = position + index as u64;
+            reader.seek(SeekFrom::Start(start))?;
+            match Tag::from_reader(reader) {
+                Ok(tag) => return Ok(Some(tag)),
+                Err(Error::Header { .. }) => position = start + 1,
+                Err(err) => return Err(err.offset_by(start)),
+            }
+        }
+    }
+
+    /// Adds the frames of a tag that was appended to this one, replacing every frame with the
+    /// same ID.
+    fn merge(&mut self, appended: Tag) {
+        for frame in &appended.frames {
+            self.remove_frames(frame.id());
+        }
+
+        self.frames.extend(appended.frames);
+    }
+
+    /// Keeps only the frames for which `keep` returns true, returning the removed frames.
+    ///
+    /// See `Tag::remove_frames` for what happens when a read only frame in a signed group is
+    /// removed.
+    fn retain_frames<F: FnMut(&Frame) -> bool>(&mut self, mut keep: F) -> Vec<Frame> {
+        let (kept, removed): (Vec<Frame>, Vec<Frame>) =
+            self.frames.drain(..).partition(|frame| keep(frame));
+        self.frames = kept;
+        self.altered |= !removed.is_empty();
+
+        self.break_signatures(&removed);
+
+        removed
+    }
+
+    /// Replaces the body of every frame for which `new_body` returns one, keeping the frame's
+    /// flags and group.
+    ///
+    /// A changed frame is no longer read only, and changing a read only frame in a signed group
+    /// breaks the signature in the same way as removing it (see `Tag::remove_frames`).
+    fn replace_bodies<F: FnMut(&Frame) -> Option<Vec<u8>>>(&mut self, mut new_body: F) {
+        let mut changed = Vec::new();
+
+        for frame in &mut self.frames {
+            if let Some(body) = new_body(frame) {
+                changed.push(frame.clone());
+                frame.set_body(body);
+                frame.set_read_only(false);
+            }
+        }
+
+        self.altered |= !changed.is_empty();
+        self.break_signatures(&changed);
+    }
+
+    /// Removes the signature frames (`SIGN`) of the signed groups that any of the given read
+    /// only frames belong to, since they were removed or changed, and marks the rest of those
+    /// groups as no longer read only.
+    fn break_signatures(&mut self, changed: &[Frame]) {
+        let broken: Vec<u8> = changed.iter()
+            .filter(|frame| frame.is_read_only() && frame.id() != b"SIGN")
+            .filter_map(Frame::group_symbol)
+            .filter(|&symbol| self.signatures().iter().any(|sign| sign.group_symbol == symbol))
+            .collect();
+
+        if broken.is_empty() {
+            return;
+        }
+
+        self.frames.retain(|frame| {
+            frame.id() != b"SIGN" ||
+            Signature::from_frame(frame)
+                .map_or(true, |signature| !broken.contains(&signature.group_symbol))
+        });
+
+        for frame in &mut self.frames {
+            if frame.group_symbol().is_some_and(|symbol| broken.contains(&symbol)) {
+                frame.set_read_only(false);
+            }
+        }
+    }
+
+    /// Skips over the extended header, which is currently ignored.
+    fn skip_extended_header<R: Read>(reader: &mut R, version: u8) -> Result<()> {
+        use utils;
+
+        let mut size_bytes = [0u8; 4];
+        reader.read_exact(&mut size_bytes)?;
+
+        // The size excludes itself in `v2.3.0`, but is synchsafe and includes itself in `v2.4.0`
+        let remaining = match version {
+            3 => u32::from_be_bytes(size_bytes) as u64,
+            _ => (utils::synchsafe_to_u32(&size_bytes).unwrap_or(0) as u64).saturating_sub(4),
+        };
+
+        io::copy(&mut reader.take(remaining), &mut io::sink())?;
+
+        Ok(())
+    }
+}
+
+impl LinkResolver for Tag {
+    /// Finds the first frame in this tag that `link` matches, ignoring the URL.
+    fn resolve(&mut self, link: &Link) -> Option<Frame> {
+        self.frames.iter().find(|frame| link.matches(frame)).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tag_tests {
+    use std::io::{Cursor, Seek};
+
+    use frame::Frame;
+    use frame::signature::{Signature, SignatureVerifier};
+    use tag::test_helpers::{frame_bytes, tag_bytes};
+    use super::Tag;
+
+    /// Accepts a signature if it is the length of the signed bytes.
+    struct LengthVerifier;
+
+    impl SignatureVerifier for LengthVerifier {
+        fn verify(&mut self, signed: &[u8], signature: &Signature) -> bool {
+            signature.signature == (signed.len() as u32).to_be_bytes()
+        }
+    }
+
+    #[test]
+    fn it_follows_seek_frames() {
+        let mut bytes = tag_bytes(4, 0, &[frame_bytes(b"TIT2", [0, 0], b"\x03First"),
+                                          frame_bytes(b"TALB", [0, 0], b"\x03Album"),
+                                          frame_bytes(b"SEEK", [0, 0], b"\x00\x00\x00\x04")]);
+        bytes.extend_from_slice(b"\xff\xfbID3\xff\xfb\x90\x00");
+        bytes.extend(tag_bytes(4, 0, &[frame_bytes(b"TIT2", [0, 0], b"\x03Second"),
+                                       frame_bytes(b"TPE1", [0, 0], b"\x03Artist")]));
+        let end = bytes.len() as u64;
+        bytes.extend_from_slice(b"\xff\xfb\x90\x00");
+
+        let mut reader = Cursor::new(bytes);
+        let tag = Tag::from_seekable_reader(&mut reader).unwrap();
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Second"));
+        assert_eq!(tag.text(b"TALB").as_deref(), Some("Album"));
+        assert_eq!(tag.text(b"TPE1").as_deref(), Some("Artist"));
+        assert!(tag.seek_offset().is_none());
+        assert_eq!(reader.stream_position().unwrap(), end);
+    }
+
+    #[test]
+    fn it_stops_when_no_tag_follows() {
+        let mut bytes = tag_bytes(4, 0, &[frame_bytes(b"TIT2", [0, 0], b"\x03First"),
+                                          frame_bytes(b"SEEK", [0, 0], b"\x00\x00\x00\x00")]);
+        let end = bytes.len() as u64;
+        bytes.extend_from_slice(b"\xff\xfb\x90\x00ID");
+
+        let mut reader = Cursor::new(bytes);
+        let tag = Tag::from_seekable_reader(&mut reader).unwrap();
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("First"));
+        assert_eq!(reader.stream_position().unwrap(), end);
+    }
+
+    #[test]
+    fn it_reads_group_symbols() {
+        let bytes = b"ID3\x03\x00\x00\x00\x00\x00\x11TIT2\x00\x00\x00\x07\x20\x20\x80\x00Title";
+        let tag = Tag::from_reader(&mut &bytes[..]).unwrap();
+        let frame = &tag.frames()[0];
+
+        assert_eq!(frame.group_symbol(), Some(0x80));
+        assert!(frame.is_read_only());
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn it_signs_and_verifies_groups() {
+        let mut tag = Tag::default();
+        for &(id, text) in &[(b"TIT2", "Title"), (b"TPE1", "Artist"), (b"TALB", "Album")] {
+            let mut frame = Frame::new(*id, [b"\x00", text.as_bytes()].concat());
+            if id != b"TALB" {
+                frame.set_group_symbol(Some(0x80));
+            }
+            tag.add_frame(frame);
+        }
+
+        assert_eq!(tag.group_bytes(0x80), b"TIT2\0\0\0\x06\0TitleTPE1\0\0\0\x07\0Artist");
+
+        tag.add_signature(&Signature { group_symbol: 0x80, signature: vec![0, 0, 0, 29] });
+        tag.add_signature(&Signature { group_symbol: 0x80, signature: vec![0, 0, 0, 29] });
+        assert_eq!(tag.signatures().len(), 1);
+        assert!(tag.frame(b"TIT2").unwrap().is_read_only());
+        assert!(!tag.frame(b"TALB").unwrap().is_read_only());
+        assert!(tag.verify_signatures(&mut LengthVerifier)[0].1);
+
+        tag.set_text(b"TALB", "Other album".to_string());
+        assert_eq!(tag.signatures().len(), 1);
+
+        tag.set_text(b"TIT2", "Other title".to_string());
+        assert!(tag.signatures().is_empty());
+        assert!(!tag.frame(b"TPE1").unwrap().is_read_only());
+    }
 }
@@ -0,0 +1,400 @@
+//! Functions for reading a tag, strictly or while recovering from malformed frames.
+
+use std::io::Read;
+
+use frame;
+use frame::Frame;
+use frame::header::is_valid_frame_id;
+use header::Header;
+use tag::{Error, HEADER_SIZE, ParseOptions, Result, Tag, Warning};
+
+impl Tag {
+    /// Construct a new tag from a reader, returning it with a list of the problems that were
+    /// worked around while reading it.
+    ///
+    /// If `options.strict` is true, this is the same as `Tag::from_reader`. Otherwise, the tag
+    /// is read leniently:
+    ///
+    /// * Unrecognized flags in the tag header and frame headers are ignored.
+    /// * Frames that cannot be read, such as those with a size that runs past the end of the
+    ///   tag, are skipped.
+    /// * Bytes that are not a valid frame are skipped until the next valid frame is found.
+    /// * An extended header that cannot be read is skipped in the same way.
+    ///
+    /// Frames that can be read but not decoded are kept as they are, like they always are.
+    ///
+    /// The footer of a `v2.4.0` tag that has one is read and checked against the header, so the
+    /// reader is left after it. In lenient mode, a footer that does not match is only a
+    /// warning. Older versions cannot have a footer, so the footer flag is an unrecognized flag
+    /// in their headers.
+    ///
+    /// In lenient mode, a stream that ends before the end of the tag keeps the frames read so
+    /// far, and reports it as a warning. In either mode, a `v2.4.0` tag whose frame sizes only
+    /// fit together as plain 32 bit integers, as old versions of iTunes wrote them, is read
+    /// that way and reported as a warning.
+    ///
+    /// # Errors
+    ///
+    /// In lenient mode, if there is an error reading the header other than an unrecognized
+    /// flag, then this function will return `Error::Header`, and if there is an error reading
+    /// from the reader, then this function will return `Error::Io`.
+    ///
+    /// See `Tag::from_reader` for the errors in strict mode.
+    pub fn from_reader_with_options<R: Read>(reader: &mut R, options: ParseOptions)
+                                             -> Result<(Tag, Vec<Warning>)> {
+        let strict = options.strict;
+        let mut warnings = Vec::new();
+
+        let (header, unknown_flags) = Header::read_with_flags(reader, strict)
+            .map_err(|error| Error::Header { offset: 0, error })?;
+        if unknown_flags != 0 {
+            warnings.push(Warning::UnknownTagFlags(unknown_flags));
+        }
+        let version = header.version().major;
+
+        let mut body = Vec::new();
+        reader.take(header.size() as u64).read_to_end(&mut body)?;
+        if body.len() < header.size() as usize {
+            let missing = header.size() as u64 - body.len() as u64;
+            if strict {
+                let offset = (HEADER_SIZE + body.len()) as u64;
+                return Err(Error::UnexpectedEnd { offset, missing });
+            }
+            warnings.push(Warning::UnexpectedEnd { missing });
+        } else if header.has_footer() {
+            let offset = (HEADER_SIZE + body.len()) as u64;
+            let mut footer = Vec::new();
+            reader.take(HEADER_SIZE as u64).read_to_end(&mut footer)?;
+
+            if footer.len() < HEADER_SIZE {
+                let missing = (HEADER_SIZE - footer.len()) as u64;
+                if strict {
+                    let offset = offset + footer.len() as u64;
+                    return Err(Error::UnexpectedEnd { offset, missing });
+                }
+                warnings.push(Warning::UnexpectedEnd { missing });
+            } else {
+                let mut bytes = [0u8; HEADER_SIZE];
+                bytes.copy_from_slice(&footer);
+
+                match header.validate_footer(&bytes) {
+                    Ok(()) => {}
+                    Err(error) if strict => return Err(Error::Header { offset, error }),
+                    Err(error) => warnings.push(Warning::InvalidFooter { offset, error }),
+                }
+            }
+        }
+
+        let mut position = 0;
+        let mut resync = false;
+
+        if header.has_extended_header() {
+            let mut rest = &body[..];
+            match Tag::skip_extended_header(&mut rest, version) {
+                Ok(()) if strict || !rest.is_empty() => position = body.len() - rest.len(),
+                Err(err) if strict => return Err(err),
+                _ => {
+                    warnings.push(Warning::InvalidExtendedHeader);
+                    resync = true;
+                }
+            }
+        }
+
+        let synchsafe_sizes = version != 4 ||
+                              frames_fit(&body, position, version, true) ||
+                              !frames_fit(&body, position, version, false);
+        if !synchsafe_sizes {
+            warnings.push(Warning::NonSynchsafeFrameSizes);
+        }
+
+        let mut frames = Vec::new();
+
+        while position < body.len() {
+            let offset = (HEADER_SIZE + position) as u64;
+            let rest = &body[position..];
+
+            if !resync {
+                // Padding has been reached, so there are no more frames
+                if rest[0] == 0 {
+                    break;
+                }
+
+                if strict {
+                    let (frame, _) = Frame::read(&mut &rest[..], version, true, synchsafe_sizes)
+                        .map_err(|error| {
+                            let mut frame_id = [0; 4];
+                            let length = rest.len().min(4);
+                            frame_id[..length].copy_from_slice(&rest[..length]);
+
+                            Error::Frame { offset, frame_id, error }
+                        })?;
+                    position += HEADER_SIZE + frame.header().size() as usize;
+                    frames.push(frame);
+                    continue;
+                }
+
+                if is_valid_frame_id(&rest[..rest.len().min(4)]) {
+                    let frame_id = [rest[0], rest[1], rest[2], rest[3]];
+
+                    match Frame::read(&mut &rest[..], version, false, synchsafe_sizes) {
+                        Ok((frame, flags)) => {
+                            if flags != [0, 0] {
+                                warnings.push(Warning::UnknownFrameFlags {
+                                    offset,
+                                    frame_id,
+                                    flags,
+                                });
+                            }
+
+                            position += HEADER_SIZE + frame.header().size() as usize;
+                            frames.push(frame);
+                            continue;
+                        }
+                        Err(error) => {
+                            warnings.push(Warning::InvalidFrame { offset, frame_id, error });
+                        }
+                    }
+                }
+            }
+
+            let start = if resync { position } else { position + 1 };
+            resync = false;
+
+            match find_frame(&body, start, version, synchsafe_sizes) {
+                Some(next) => {
+                    let length = (next - position) as u64;
+                    warnings.push(Warning::SkippedBytes { offset, length });
+                    position = next;
+                }
+                None => {
+                    if rest.iter().any(|&byte| byte != 0) {
+                        let length = rest.len() as u64;
+                        warnings.push(Warning::SkippedBytes { offset, length });
+                    }
+                    break;
+                }
+            }
+        }
+
+        let tag = Tag { header, frames, ..Default::default() };
+
+        Ok((tag, warnings))
+    }
+}
+
+/// Gets the position of the first frame in `body` at or after `start` that has a valid ID and
+/// can be read completely.
+///
+/// Only the frame headers are read, so that the bodies are not copied at every position.
+fn find_frame(body: &[u8], start: usize, version: u8, synchsafe_sizes: bool) -> Option<usize> {
+    (start..body.len()).find(|&position| {
+        let rest = &body[position..];
+
+        frame_size(rest, version, synchsafe_sizes)
+            .is_some_and(|size| HEADER_SIZE + size as usize <= rest.len())
+    })
+}
+
+/// Gets the body size from the header of the frame at the start of `bytes`, if it has a valid
+/// ID and can be read.
+fn frame_size(bytes: &[u8], version: u8, synchsafe_sizes: bool) -> Option<u32> {
+    let bytes = bytes.get(..HEADER_SIZE).filter(|bytes| is_valid_frame_id(&bytes[..4]))?;
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3], bytes[4],
+                 bytes[5], bytes[6], bytes[7], bytes[8], bytes[9]];
+
+    frame::header::Header::from_bytes(&bytes, version, false, synchsafe_sizes)
+        .ok()
+        .map(|(header, _)| header.size())
+}
+
+/// Gets whether or not every frame from `start` has a valid ID, and the last one ends exactly
+/// at the padding or the end of `body`, when their sizes are read in the given way.
+pub(crate) fn frames_fit(body: &[u8], start: usize, version: u8, synchsafe_sizes: bool) -> bool {
+    let mut position = start;
+
+    loop {
+        let rest = match body.get(position..) {
+            Some(rest) if !rest.is_empty() && rest[0] != 0 => rest,
+            Some(_) => return true,
+            None => return false,
+        };
+
+        let size = match frame_size(rest, version, synchsafe_sizes) {
+            Some(size) => size,
+            None => return false,
+        };
+
+        position += HEADER_SIZE + size as usize;
+    }
+}
+
+#[cfg(test)]
+mod read_tests {
+    use frame;
+    use header;
+    use tag::{Error, ParseOptions, Tag, Warning};
+    use tag::test_helpers::{frame_bytes, tag_bytes};
+
+    fn lenient() -> ParseOptions {
+        ParseOptions { strict: false }
+    }
+
+    #[test]
+    fn it_tolerates_unknown_flags() {
+        let bytes = tag_bytes(3, 0x01, &[frame_bytes(b"TIT2", [0x01, 0x00], b"\x00Title")]);
+
+        assert!(Tag::from_reader(&mut &bytes[..]).is_err());
+        assert!(Tag::from_reader_with_options(&mut &bytes[..], ParseOptions::default()).is_err());
+
+        let (tag, warnings) = Tag::from_reader_with_options(&mut &bytes[..], lenient()).unwrap();
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Title"));
+        match warnings[..] {
+            [Warning::UnknownTagFlags(0x01),
+             Warning::UnknownFrameFlags { offset: 10, flags: [0x01, 0x00], .. }] => {}
+            _ => panic!("unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn it_only_reads_footers_of_v24_tags() {
+        let mut bytes = tag_bytes(3, 0x10, &[frame_bytes(b"TIT2", [0, 0], b"\x00Title")]);
+        bytes.extend_from_slice(b"3DI\x03\x00\x10\x00\x00\x00\x10");
+
+        match Tag::from_reader(&mut &bytes[..]) {
+            Err(Error::Header { offset: 0, error: header::Error::UnknownFlag(0x10) }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let mut reader = &bytes[..];
+        let (tag, warnings) = Tag::from_reader_with_options(&mut reader, lenient()).unwrap();
+
+        assert!(!tag.header().has_footer());
+        assert_eq!(reader.len(), 10);
+        match warnings[..] {
+            [Warning::UnknownTagFlags(0x10)] => {}
+            _ => panic!("unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn it_resyncs_after_broken_frames() {
+        let bytes = tag_bytes(3, 0, &[
+            frame_bytes(b"TIT2", [0, 0], b"\x00Title"),
+            b"junk".to_vec(),
+            frame_bytes(b"TALB", [0, 0], b"\x00Album"),
+            frame_bytes(b"TPE1", [0, 0], b"\x00Artist")[..12].to_vec(),
+        ]);
+
+        let (tag, warnings) = Tag::from_reader_with_options(&mut &bytes[..], lenient()).unwrap();
+
+        assert_eq!(tag.frames().len(), 2);
+        assert_eq!(tag.text(b"TALB").as_deref(), Some("Album"));
+        match warnings[..] {
+            [Warning::SkippedBytes { offset: 26, length: 4 },
+             Warning::InvalidFrame { offset: 46, frame_id: [b'T', b'P', b'E', b'1'], .. },
+             Warning::SkippedBytes { offset: 46, length: 12 }] => {}
+            _ => panic!("unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn it_skips_frames_larger_than_the_tag() {
+        let huge = b"TIT2\xff\xff\xff\xf0\x00\x00\x00Title";
+        let bytes = tag_bytes(3, 0, &[huge.to_vec(), frame_bytes(b"TALB", [0, 0], b"\x00Album")]);
+
+        match frame::Frame::from_reader(&mut &huge[..], 3) {
+            Err(frame::Error::Io(_)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let (tag, warnings) = Tag::from_reader_with_options(&mut &bytes[..], lenient()).unwrap();
+
+        assert_eq!(tag.frames().len(), 1);
+        assert_eq!(tag.text(b"TALB").as_deref(), Some("Album"));
+        match warnings[..] {
+            [Warning::InvalidFrame { offset: 10, .. },
+             Warning::SkippedBytes { offset: 10, length: 16 }] => {}
+            _ => panic!("unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn it_keeps_frames_of_truncated_tags_only_when_lenient() {
+        let mut bytes = tag_bytes(3, 0, &[frame_bytes(b"TIT2", [0, 0], b"\x00Title"),
+                                 frame_bytes(b"TALB", [0, 0], b"\x00Album")]);
+        bytes.truncate(bytes.len() - 3);
+
+        match Tag::from_reader(&mut &bytes[..]) {
+            Err(Error::UnexpectedEnd { offset: 39, missing: 3 }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let (tag, warnings) = Tag::from_reader_with_options(&mut &bytes[..], lenient()).unwrap();
+
+        assert_eq!(tag.text(b"TIT2").as_deref(), Some("Title"));
+        match warnings[..] {
+            [Warning::UnexpectedEnd { missing: 3 }, Warning::InvalidFrame { offset: 26, .. },
+             Warning::SkippedBytes { offset: 26, length: 13 }] => {}
+            _ => panic!("unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn it_reads_v24_frame_flags() {
+        let body = b"\x80\x00\x00\x00\x04\x00T\xff\x00e";
+        let bytes = tag_bytes(4, 0, &[frame_bytes(b"TIT2", [0x10, 0x43], body)]);
+        let tag = Tag::from_reader(&mut &bytes[..]).unwrap();
+        let frame = &tag.frames()[0];
+
+        assert!(frame.is_read_only());
+        assert_eq!(frame.group_symbol(), Some(0x80));
+        assert!(frame.header().is_unsynchronised());
+        assert_eq!(frame.body(), b"\x00T\xffe");
+    }
+
+    #[test]
+    fn it_detects_non_synchsafe_frame_sizes() {
+        let mut text = b"\x00".to_vec();
+        text.extend(vec![b'a'; 255]);
+
+        let mut bytes = b"ID3\x04\x00\x00\x00\x00\x02\x24".to_vec();
+        bytes.extend(frame_bytes(b"TIT2", [0, 0], &text));
+        bytes.extend(frame_bytes(b"TALB", [0, 0], b"\x00Album"));
+        bytes.extend_from_slice(&[0; 10]);
+
+        for &strict in &[true, false] {
+            let options = ParseOptions { strict };
+            let (tag, warnings) = Tag::from_reader_with_options(&mut &bytes[..], options).unwrap();
+
+            assert_eq!(tag.text(b"TIT2").map(|title| title.len()), Some(255));
+            assert_eq!(tag.text(b"TALB").as_deref(), Some("Album"));
+            match warnings[..] {
+                [Warning::NonSynchsafeFrameSizes] => {}
+                _ => panic!("unexpected warnings: {:?}", warnings),
+            }
+        }
+    }
+
+    #[test]
+    fn it_locates_errors() {
+        let bytes = tag_bytes(3, 0, &[frame_bytes(b"TIT2", [0, 0], b"\x00Title"),
+                             frame_bytes(b"TALB", [0, 0x01], b"\x00Album")]);
+
+        match Tag::from_reader(&mut &bytes[..]) {
+            Err(Error::Frame {
+                offset: 26,
+                frame_id: [b'T', b'A', b'L', b'B'],
+                error: frame::Error::Header(frame::header::Error::UnknownFlag([0x00, 0x01])),
+            }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        match Tag::from_reader(&mut &b"ID2\x03\x00\x00\x00\x00\x00\x01"[..]) {
+            Err(Error::Header { offset: 0, error: header::Error::InvalidIdentifier(id) }) => {
+                assert_eq!(&id, b"ID2");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Functions shared by the tests of the tag module for building tags and their bytes.
+
+use frame::Frame;
+use tag::Tag;
+use utils;
+
+/// Construct a new tag of the given major version holding the given frames.
+pub fn tag_with(version: u8, frames: Vec<Frame>) -> Tag {
+    let mut tag = Tag::default();
+    tag.header.set_major_version(version);
+    tag.frames = frames;
+    tag
+}
+
+/// Gets the bytes of a frame with the given flags and body.
+///
+/// The size is written as a plain integer, which is also a valid synchsafe integer for bodies
+/// shorter than 128 bytes.
+pub fn frame_bytes(frame_id: &[u8; 4], flags: [u8; 2], body: &[u8]) -> Vec<u8> {
+    let mut bytes = frame_id.to_vec();
+    bytes.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&flags);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+/// Gets the bytes of a tag of the given major version, with the given flags, holding the given
+/// frame bytes.
+pub fn tag_bytes(version: u8, flags: u8, frames: &[Vec<u8>]) -> Vec<u8> {
+    let body = frames.concat();
+
+    let mut bytes = vec![b'I', b'D', b'3', version, 0, flags];
+    bytes.extend_from_slice(&utils::u32_to_synchsafe(body.len() as u32));
+    bytes.extend(body);
+    bytes
+}
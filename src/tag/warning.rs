@@ -0,0 +1,88 @@
+use std::fmt;
+
+use frame;
+use header;
+
+/// A list specifying the problems that were worked around when reading a tag leniently.
+///
+/// Offsets are counted in bytes from the start of the tag header.
+#[derive(Debug)]
+pub enum Warning {
+    /// The extended header could not be read, so it was skipped by searching for the first
+    /// frame.
+    InvalidExtendedHeader,
+    /// The footer did not match the header, or could not be read, so it was ignored.
+    InvalidFooter {
+        /// The offset of the footer.
+        offset: u64,
+        /// What was wrong with the footer.
+        error: header::Error,
+    },
+    /// A frame could not be read, so it was skipped.
+    InvalidFrame {
+        /// The offset of the frame header.
+        offset: u64,
+        /// The ID of the frame.
+        frame_id: [u8; 4],
+        /// Why the frame could not be read.
+        error: frame::Error,
+    },
+    /// The frame sizes in a `v2.4.0` tag were plain 32 bit integers rather than synchsafe
+    /// integers, as old versions of iTunes wrote them, so they were read that way.
+    NonSynchsafeFrameSizes,
+    /// Bytes that were not a valid frame were skipped to reach the next valid frame, or the
+    /// end of the tag.
+    SkippedBytes {
+        /// The offset of the first skipped byte.
+        offset: u64,
+        /// The number of bytes skipped.
+        length: u64,
+    },
+    /// The stream ended before the end of the tag.
+    UnexpectedEnd {
+        /// The number of bytes of the tag that were missing.
+        missing: u64,
+    },
+    /// A frame header had flags that were not recognized, which were ignored.
+    UnknownFrameFlags {
+        /// The offset of the frame header.
+        offset: u64,
+        /// The ID of the frame.
+        frame_id: [u8; 4],
+        /// The unrecognized bits of the status and encoding flags.
+        flags: [u8; 2],
+    },
+    /// The tag header had flags that were not recognized, which were ignored.
+    UnknownTagFlags(u8),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::InvalidExtendedHeader => write!(f, "Invalid extended header skipped"),
+            Warning::InvalidFooter { offset, ref error } => {
+                write!(f, "Invalid footer at offset {} ignored: {}", offset, error)
+            }
+            Warning::InvalidFrame { offset, ref frame_id, ref error } => {
+                write!(f, "Invalid {} frame at offset {} skipped: {}",
+                       String::from_utf8_lossy(frame_id), offset, error)
+            }
+            Warning::NonSynchsafeFrameSizes => {
+                write!(f, "Frame sizes read as plain integers rather than synchsafe integers")
+            }
+            Warning::SkippedBytes { offset, length } => {
+                write!(f, "{} bytes skipped at offset {}", length, offset)
+            }
+            Warning::UnexpectedEnd { missing } => {
+                write!(f, "Tag ended {} bytes early", missing)
+            }
+            Warning::UnknownFrameFlags { offset, ref frame_id, flags } => {
+                write!(f, "Unknown flags {:02x} {:02x} ignored in {} frame at offset {}",
+                       flags[0], flags[1], String::from_utf8_lossy(frame_id), offset)
+            }
+            Warning::UnknownTagFlags(flags) => {
+                write!(f, "Unknown tag flags {:02x} ignored", flags)
+            }
+        }
+    }
+}
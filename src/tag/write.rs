@@ -0,0 +1,182 @@
+//! Functions for writing a tag.
+
+use std::io::Write;
+
+use tag::{Error, Result, Tag, WriteOptions};
+use utils;
+
+impl Tag {
+    /// Writes the tag to a writer, without padding, an extended header or a footer.
+    ///
+    /// This is the same as `Tag::write_to_with_options` with the default options.
+    ///
+    /// # Errors
+    ///
+    /// See `Tag::write_to_with_options`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.write_to_with_options(writer, WriteOptions::default())
+    }
+
+    /// Writes the tag to a writer, without padding or an extended header, and with a footer if
+    /// `options.footer` is true.
+    ///
+    /// Frames that this library cannot decode are written exactly as they were read. If the
+    /// tag has been altered (see `Tag::is_altered`), those marked to be discarded when the tag
+    /// is altered are left out.
+    ///
+    /// # Errors
+    ///
+    /// If the version is not 3 or 4, or a footer is asked for and the version is not 4, then
+    /// this function will return `Error::UnsupportedVersion`. A new tag has no version until
+    /// `Tag::convert` is called.
+    ///
+    /// If there is an error writing to the writer, then this function will return `Error::Io`.
+    pub fn write_to_with_options<W: Write>(&self, writer: &mut W, options: WriteOptions)
+                                           -> Result<()> {
+        let version = self.version();
+        let supported = version == 4 || (version == 3 && !options.footer);
+        if !supported {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let mut body = Vec::new();
+        for frame in &self.frames {
+            let discard = self.altered &&
+                          frame.unknown().is_some_and(|unknown| {
+                              !unknown.preserve_on_tag_alter(version)
+                          });
+
+            if !discard {
+                body.extend(frame.to_bytes(version));
+            }
+        }
+
+        let mut flags = 0;
+        if self.header.is_experimental() {
+            flags |= 0b0010_0000;
+        }
+        if options.footer {
+            flags |= 0b0001_0000;
+        }
+
+        let mut header = b"ID3".to_vec();
+        header.extend_from_slice(&[version, self.header.version().revision, flags]);
+        header.extend_from_slice(&utils::u32_to_synchsafe(body.len() as u32));
+
+        writer.write_all(&header)?;
+        writer.write_all(&body)?;
+
+        // The footer is a copy of the header with a different identifier
+        if options.footer {
+            header[..3].copy_from_slice(b"3DI");
+            writer.write_all(&header)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod write_tests {
+    use std::io::Cursor;
+
+    use frame::Frame;
+    use header;
+    use tag::{Error, ParseOptions, Tag, Warning, WriteOptions, locate_tags};
+
+    // A `v2.4.0` tag with an unknown frame marked to be discarded when the tag is altered, an
+    // unknown frame that is always kept, and a compressed `TIT2` frame
+    const TAG: &[u8] = b"ID3\x04\x00\x00\x00\x00\x00\x24\
+                         XAAA\x00\x00\x00\x02\x40\x00\xff\x00\
+                         XBBB\x00\x00\x00\x01\x00\x00\x01\
+                         TIT2\x00\x00\x00\x03\x00\x08\x78\x9c\x03";
+
+    #[test]
+    fn it_writes_unknown_frames_back_exactly() {
+        let tag = Tag::from_reader(&mut &TAG[..]).unwrap();
+        assert!(tag.frames().iter().all(|frame| frame.unknown().is_some()));
+
+        let mut bytes = Vec::new();
+        tag.write_to(&mut bytes).unwrap();
+
+        assert_eq!(bytes, TAG);
+    }
+
+    #[test]
+    fn it_discards_unknown_frames_when_altered() {
+        let mut tag = Tag::from_reader(&mut &TAG[..]).unwrap();
+        tag.add_frame(Frame::new(*b"TALB", b"\x03Album".to_vec()));
+
+        let mut bytes = Vec::new();
+        tag.write_to(&mut bytes).unwrap();
+        let written = Tag::from_reader(&mut &bytes[..]).unwrap();
+
+        let ids: Vec<_> = written.frames().iter().map(|frame| *frame.id()).collect();
+        assert_eq!(ids, vec![*b"XBBB", *b"TIT2", *b"TALB"]);
+        assert_eq!(&bytes[10..21], &TAG[22..33]);
+    }
+
+    #[test]
+    fn it_writes_new_frames() {
+        let mut tag = Tag::default();
+        assert!(matches!(tag.write_to(&mut Vec::new()), Err(Error::UnsupportedVersion(0))));
+
+        tag.convert(3).unwrap();
+        let mut frame = Frame::new(*b"TIT2", b"\x00Title".to_vec());
+        frame.set_group_symbol(Some(0x80));
+        tag.add_frame(frame);
+
+        let mut bytes = Vec::new();
+        tag.write_to(&mut bytes).unwrap();
+
+        assert_eq!(&bytes[..10], b"ID3\x03\x00\x00\x00\x00\x00\x11");
+        assert_eq!(&bytes[10..], b"TIT2\x00\x00\x00\x07\x00\x20\x80\x00Title");
+        let written = Tag::from_reader(&mut &bytes[..]).unwrap();
+        assert_eq!(written.frames()[0].group_symbol(), Some(0x80));
+        assert_eq!(written.text(b"TIT2").as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn it_removes_file_dependent_frames() {
+        let mut tag = Tag::from_reader(&mut &TAG[..]).unwrap();
+        tag.convert(4).unwrap();
+        tag.add_frame(Frame::new(*b"TLEN", b"\x00180000".to_vec()));
+
+        let removed = tag.remove_file_dependent_frames();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id(), b"TLEN");
+    }
+
+    #[test]
+    fn it_writes_and_checks_footers() {
+        let mut tag = Tag::default();
+        tag.convert(4).unwrap();
+        tag.add_frame(Frame::new(*b"TIT2", b"\x03Title".to_vec()));
+
+        let mut bytes = vec![0xff, 0xfb, 0x90, 0x00];
+        tag.write_to_with_options(&mut bytes, WriteOptions { footer: true }).unwrap();
+        assert_eq!(&bytes[4..14], b"ID3\x04\x00\x10\x00\x00\x00\x10");
+        assert_eq!(&bytes[30..], b"3DI\x04\x00\x10\x00\x00\x00\x10");
+
+        let locations = locate_tags(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!((locations[0].offset, locations[0].appended), (4, true));
+
+        let mut reader = &bytes[4..];
+        assert!(Tag::from_reader(&mut reader).unwrap().header().has_footer());
+        assert!(reader.is_empty());
+
+        bytes[39] = 0x11;
+        match Tag::from_reader(&mut &bytes[4..]) {
+            Err(Error::Header { offset: 26, error: header::Error::FooterMismatch(_) }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let options = ParseOptions { strict: false };
+        let (_, warnings) = Tag::from_reader_with_options(&mut &bytes[4..], options).unwrap();
+        assert!(matches!(warnings[..], [Warning::InvalidFooter { offset: 26, .. }]));
+
+        tag.convert(3).unwrap();
+        let result = tag.write_to_with_options(&mut Vec::new(), WriteOptions { footer: true });
+        assert!(matches!(result, Err(Error::UnsupportedVersion(3))));
+    }
+}
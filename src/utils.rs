@@ -22,7 +22,7 @@
 /// # Reference
 ///
 /// [ID3v2.4 Informal Standard (Section 6.4)](http://id3.org/id3v2.4.0-structure)
-#[cfg_attr(rustfmt, rustfmt_skip)]
+#[rustfmt::skip]
 pub fn synchsafe_to_u32(bytes: &[u8]) -> Option<u32> {
     if bytes.len() == 4 {
         Some(
@@ -36,6 +36,66 @@ pub fn synchsafe_to_u32(bytes: &[u8]) -> Option<u32> {
     }
 }
 
+/// Converts from regular integers to 'synchsafe' integers
+///
+/// Only the low 28 bits of `integer` are kept, since a synchsafe integer cannot hold more.
+///
+/// # Examples
+///
+/// ```
+/// # use id3::utils::u32_to_synchsafe;
+/// let synchsafe = u32_to_synchsafe(0b11111111_11111111);
+///
+/// assert_eq!(synchsafe, [0b0000_0000, 0b0000_0011, 0b0111_1111, 0b0111_1111]);
+/// ```
+///
+/// # Reference
+///
+/// [ID3v2.4 Informal Standard (Section 6.2)](http://id3.org/id3v2.4.0-structure)
+pub fn u32_to_synchsafe(integer: u32) -> [u8; 4] {
+    [
+        (integer >> 21 & 0x7F) as u8,
+        (integer >> 14 & 0x7F) as u8,
+        (integer >> 7 & 0x7F) as u8,
+        (integer & 0x7F) as u8,
+    ]
+}
+
+/// Reverses unsynchronisation, removing the `$00` byte that was inserted after every `$FF`
+/// byte.
+///
+/// # Examples
+///
+/// ```
+/// # use id3::utils::resynchronise;
+/// let bytes = resynchronise(&[0xFF, 0x00, 0xE0, 0xFF, 0x00, 0x00]);
+///
+/// assert_eq!(bytes, [0xFF, 0xE0, 0xFF, 0x00]);
+/// ```
+///
+/// # Reference
+///
+/// [ID3v2.4 Informal Standard (Section 6.1)](http://id3.org/id3v2.4.0-structure)
+pub fn resynchronise(bytes: &[u8]) -> Vec<u8> {
+    let mut resynchronised = Vec::with_capacity(bytes.len());
+    let mut previous = 0;
+
+    for &byte in bytes {
+        if !(previous == 0xFF && byte == 0x00) {
+            resynchronised.push(byte);
+        }
+        // A `$00` that was removed cannot start another pair
+        previous = if previous == 0xFF && byte == 0x00 { 0x00 } else { byte };
+    }
+
+    resynchronised
+}
+
+/// Formats bytes as space separated pairs of hexadecimal digits, for error messages.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ")
+}
+
 #[cfg(test)]
 mod synchsafe_to_u32_tests {
     use super::synchsafe_to_u32;